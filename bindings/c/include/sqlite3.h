@@ -78,6 +78,8 @@ int sqlite3_close_v2(sqlite3 *db);
 
 const char *sqlite3_db_filename(sqlite3 *db, const char *db_name);
 
+const char *sqlite3_db_name(sqlite3 *db, int n);
+
 int sqlite3_trace_v2(sqlite3 *_db,
                      unsigned int _mask,
                      void (*_callback)(unsigned int, void*, void*, void*),
@@ -87,6 +89,8 @@ void sqlite3_progress_handler(sqlite3 *_db, int _n, int (*_callback)(void *), vo
 
 int sqlite3_busy_timeout(sqlite3 *_db, int _ms);
 
+int sqlite3_unlock_notify(sqlite3 *db, void (*x_notify)(void **, int), void *arg);
+
 int sqlite3_set_authorizer(sqlite3 *_db, int (*_callback)(void), void *_context);
 
 void *sqlite3_context_db_handle(void *_context);
@@ -173,6 +177,8 @@ int sqlite3_backup_pagecount(void *_backup);
 
 int sqlite3_backup_finish(void *_backup);
 
+const char *sqlite3_sql(sqlite3_stmt *stmt);
+
 char *sqlite3_expanded_sql(sqlite3_stmt *_stmt);
 
 int sqlite3_data_count(sqlite3_stmt *stmt);
@@ -227,6 +233,8 @@ const void *sqlite3_value_blob(void *value);
 
 int sqlite3_value_bytes(void *value);
 
+int sqlite3_value_frombind(void *value);
+
 void *sqlite3_value_dup(void *value);
 
 void sqlite3_value_free(void *value);
@@ -247,9 +255,13 @@ void sqlite3_result_int(void *_context, int _val);
 
 void sqlite3_result_double(void *_context, double _val);
 
-void sqlite3_result_text(void *_context, const char *_text, int _len, void *_destroy);
+void sqlite3_result_text(void *context, const char *text, int len, void *destroy);
+
+void sqlite3_result_blob(void *context, const void *blob, int len, void *destroy);
+
+void sqlite3_result_zeroblob(void *context, int n);
 
-void sqlite3_result_blob(void *_context, const void *_blob, int _len, void *_destroy);
+int sqlite3_result_zeroblob64(void *context, uint64_t n);
 
 void sqlite3_result_error_nomem(void *_context);
 
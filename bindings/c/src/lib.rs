@@ -1,9 +1,10 @@
 #![allow(clippy::missing_safety_doc)]
 #![allow(non_camel_case_types)]
 
+use std::collections::HashMap;
 use std::ffi::{self, CStr, CString};
 use std::num::NonZeroUsize;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, OnceLock};
 use tracing::trace;
 use turso_core::{CheckpointMode, DatabaseOpts, LimboError, Value};
@@ -121,6 +122,23 @@ struct sqlite3Inner {
     pub(crate) p_err: *mut ffi::c_void,
     pub(crate) filename: CString,
     pub(crate) stmt_list: *mut sqlite3_stmt,
+    /// Database names and paths returned by `sqlite3_db_name` and `sqlite3_db_filename`,
+    /// kept for the lifetime of the connection so the returned pointers stay valid.
+    pub(crate) db_strings: Vec<CString>,
+}
+
+impl sqlite3Inner {
+    fn intern_db_string(&mut self, value: &str) -> *const ffi::c_char {
+        let value = CString::new(value).unwrap_or_default();
+        let idx = match self.db_strings.iter().position(|s| *s == value) {
+            Some(idx) => idx,
+            None => {
+                self.db_strings.push(value);
+                self.db_strings.len() - 1
+            }
+        };
+        self.db_strings[idx].as_ptr()
+    }
 }
 
 impl sqlite3 {
@@ -130,6 +148,15 @@ impl sqlite3 {
         conn: Arc<turso_core::Connection>,
         filename: CString,
     ) -> Self {
+        unlock_notify_registry().lock().unwrap().entries.insert(
+            conn_key(&conn),
+            UnlockNotifyEntry {
+                conn: conn.clone(),
+                database: Arc::as_ptr(&db) as usize,
+                blocker: None,
+                notify: None,
+            },
+        );
         let inner = sqlite3Inner {
             _io: io,
             _db: db,
@@ -141,6 +168,7 @@ impl sqlite3 {
             p_err: std::ptr::null_mut(),
             filename,
             stmt_list: std::ptr::null_mut(),
+            db_strings: Vec::new(),
         };
         #[allow(clippy::arc_with_non_send_sync)]
         let inner = Arc::new(Mutex::new(inner));
@@ -164,10 +192,12 @@ pub struct sqlite3_stmt {
     /// High-water mark of `search_count` already published to the global
     /// counter, so each step contributes only the delta.
     pub(crate) prev_search_count: i64,
+    /// The SQL text this statement was prepared from, returned by sqlite3_sql().
+    pub(crate) sql: CString,
 }
 
 impl sqlite3_stmt {
-    pub fn new(db: *mut sqlite3, stmt: turso_core::Statement) -> Self {
+    pub fn new(db: *mut sqlite3, stmt: turso_core::Statement, sql: &str) -> Self {
         let n_cols = stmt.num_columns();
         Self {
             db,
@@ -177,6 +207,7 @@ impl sqlite3_stmt {
             text_cache: vec![vec![]; n_cols],
             value_cache: (0..n_cols).map(|_| None).collect(),
             prev_search_count: 0,
+            sql: CString::new(sql).unwrap_or_default(),
         }
     }
     #[inline]
@@ -192,6 +223,150 @@ impl sqlite3_stmt {
     }
 }
 
+// ===== Unlock notification infrastructure =====
+
+type UnlockNotifyFn = unsafe extern "C" fn(*mut *mut ffi::c_void, ffi::c_int);
+
+/// Per-connection state for sqlite3_unlock_notify(), keyed by [conn_key].
+struct UnlockNotifyEntry {
+    conn: Arc<turso_core::Connection>,
+    /// Connections opened on the same file share a Database, so its address
+    /// identifies which connections can block each other.
+    database: usize,
+    /// Connection holding the write lock when this one last got SQLITE_BUSY.
+    blocker: Option<usize>,
+    /// Registered callback and its argument (*mut c_void stored as usize for Send).
+    notify: Option<(UnlockNotifyFn, usize)>,
+}
+
+// SAFETY: other threads only read the connection's atomic transaction state.
+unsafe impl Send for UnlockNotifyEntry {}
+
+#[derive(Default)]
+struct UnlockNotifyRegistry {
+    entries: HashMap<usize, UnlockNotifyEntry>,
+    /// Blocked connections, keyed by the connection blocking them.
+    blocked_by: HashMap<usize, Vec<usize>>,
+}
+
+impl UnlockNotifyRegistry {
+    fn set_blocker(&mut self, key: usize, blocker: Option<usize>) {
+        let Some(entry) = self.entries.get_mut(&key) else {
+            return;
+        };
+        if entry.blocker == blocker {
+            return;
+        }
+        if let Some(old) = std::mem::replace(&mut entry.blocker, blocker) {
+            if let Some(waiters) = self.blocked_by.get_mut(&old) {
+                waiters.retain(|waiter| *waiter != key);
+                if waiters.is_empty() {
+                    self.blocked_by.remove(&old);
+                }
+            }
+            UNLOCK_NOTIFY_BLOCKED.fetch_sub(1, Ordering::SeqCst);
+        }
+        if let Some(new) = blocker {
+            self.blocked_by.entry(new).or_default().push(key);
+            UNLOCK_NOTIFY_BLOCKED.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Detach every callback waiting on `blocker`, clearing the waiters' blocked state.
+    fn take_callbacks(&mut self, blocker: usize) -> Vec<(UnlockNotifyFn, usize)> {
+        let Some(waiters) = self.blocked_by.remove(&blocker) else {
+            return Vec::new();
+        };
+        UNLOCK_NOTIFY_BLOCKED.fetch_sub(waiters.len(), Ordering::SeqCst);
+        waiters
+            .into_iter()
+            .filter_map(|waiter| {
+                let entry = self.entries.get_mut(&waiter)?;
+                entry.blocker = None;
+                entry.notify.take()
+            })
+            .collect()
+    }
+
+    fn remove(&mut self, key: usize) {
+        self.set_blocker(key, None);
+        self.entries.remove(&key);
+    }
+}
+
+static UNLOCK_NOTIFY: OnceLock<Mutex<UnlockNotifyRegistry>> = OnceLock::new();
+
+/// Number of connections currently recorded as blocked, readable without taking the
+/// registry lock so that statements can skip it when nobody is waiting.
+static UNLOCK_NOTIFY_BLOCKED: AtomicUsize = AtomicUsize::new(0);
+
+fn unlock_notify_registry() -> &'static Mutex<UnlockNotifyRegistry> {
+    UNLOCK_NOTIFY.get_or_init(|| Mutex::new(UnlockNotifyRegistry::default()))
+}
+
+fn conn_key(conn: &Arc<turso_core::Connection>) -> usize {
+    Arc::as_ptr(conn) as usize
+}
+
+/// Bookkeeping after a statement ran on `conn` with result code `rc`: remember who
+/// blocked it on SQLITE_BUSY, and once it no longer holds the write lock, fire the
+/// callbacks of connections that were blocked by it.
+///
+/// Must not be called with the connection's handle locked, as callbacks may call
+/// back into the API.
+fn update_unlock_notify(conn: &Arc<turso_core::Connection>, rc: ffi::c_int) {
+    let busy = matches!(rc, SQLITE_BUSY | SQLITE_LOCKED);
+    // Common case: nothing to record, and either we still hold the write lock or
+    // nobody is blocked at all, so there is no one to notify.
+    if !busy && (conn.is_in_write_tx() || UNLOCK_NOTIFY_BLOCKED.load(Ordering::SeqCst) == 0) {
+        return;
+    }
+    let key = conn_key(conn);
+    let mut registry = unlock_notify_registry().lock().unwrap();
+    if busy {
+        if let Some(entry) = registry.entries.get(&key) {
+            // A registered callback keeps waiting on the original blocker
+            if entry.notify.is_none() {
+                let blocker = registry
+                    .entries
+                    .iter()
+                    .find(|(other, e)| {
+                        **other != key && e.database == entry.database && e.conn.is_in_write_tx()
+                    })
+                    .map(|(other, _)| *other);
+                registry.set_blocker(key, blocker);
+            }
+        }
+    }
+    if conn.is_in_write_tx() {
+        return;
+    }
+    let callbacks = registry.take_callbacks(key);
+    drop(registry);
+    fire_unlock_notify_callbacks(callbacks);
+}
+
+/// Invoke callbacks, batching the arguments of callbacks that share a function like
+/// SQLite does.
+fn fire_unlock_notify_callbacks(callbacks: Vec<(UnlockNotifyFn, usize)>) {
+    let mut batches: Vec<(UnlockNotifyFn, Vec<*mut ffi::c_void>)> = Vec::new();
+    for (callback, arg) in callbacks {
+        let arg = arg as *mut ffi::c_void;
+        match batches
+            .iter_mut()
+            .find(|(cb, _)| std::ptr::fn_addr_eq(*cb, callback))
+        {
+            Some((_, args)) => args.push(arg),
+            None => batches.push((callback, vec![arg])),
+        }
+    }
+    for (callback, mut args) in batches {
+        // SAFETY: the caller of sqlite3_unlock_notify keeps the arguments valid
+        // until the callback is invoked.
+        unsafe { callback(args.as_mut_ptr(), args.len() as ffi::c_int) };
+    }
+}
+
 // ===== Custom SQL function registration infrastructure =====
 
 /// Context passed to scalar function callbacks registered via sqlite3_create_function_v2.
@@ -619,7 +794,14 @@ pub unsafe extern "C" fn sqlite3_close(db: *mut sqlite3) -> ffi::c_int {
     if db.is_null() {
         return SQLITE_OK;
     }
+    let key = conn_key(&(*db).inner.lock().unwrap().conn);
+    let callbacks = {
+        let mut registry = unlock_notify_registry().lock().unwrap();
+        registry.remove(key);
+        registry.take_callbacks(key)
+    };
     let _ = Box::from_raw(db);
+    fire_unlock_notify_callbacks(callbacks);
     SQLITE_OK
 }
 
@@ -637,15 +819,55 @@ pub unsafe extern "C" fn sqlite3_db_filename(
     if db.is_null() {
         return std::ptr::null();
     }
-    if !db_name.is_null() {
-        let name = CStr::from_ptr(db_name);
-        if name.to_bytes() != b"main" {
-            return std::ptr::null();
-        }
+    let db = &*db;
+    let mut inner = db.inner.lock().unwrap();
+    if db_name.is_null() {
+        return inner.filename.as_ptr();
+    }
+    let Ok(name) = CStr::from_ptr(db_name).to_str() else {
+        return std::ptr::null();
+    };
+    if name.eq_ignore_ascii_case("main") {
+        return inner.filename.as_ptr();
+    }
+    // Temp and in-memory databases are listed with an empty path
+    let path = inner
+        .conn
+        .list_all_databases()
+        .into_iter()
+        .find(|(_, alias, _)| alias.eq_ignore_ascii_case(name))
+        .map(|(_, _, path)| path);
+    match path {
+        Some(path) => inner.intern_db_string(&path),
+        // Like sqlite3_db_name(), report temp even before it has been created
+        None if name.eq_ignore_ascii_case("temp") => inner.intern_db_string(""),
+        None => std::ptr::null(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn sqlite3_db_name(db: *mut sqlite3, n: ffi::c_int) -> *const ffi::c_char {
+    if db.is_null() || n < 0 {
+        return std::ptr::null();
     }
     let db = &*db;
-    let inner = db.inner.lock().unwrap();
-    inner.filename.as_ptr()
+    let mut inner = db.inner.lock().unwrap();
+    let n = n as usize;
+    // The temp schema always has index 1, even before it has been created
+    let name = if n == turso_core::TEMP_DB_ID {
+        Some("temp".to_string())
+    } else {
+        inner
+            .conn
+            .list_all_databases()
+            .into_iter()
+            .find(|(seq, _, _)| *seq == n)
+            .map(|(_, name, _)| name)
+    };
+    match name {
+        Some(name) => inner.intern_db_string(&name),
+        None => std::ptr::null(),
+    }
 }
 
 #[no_mangle]
@@ -786,6 +1008,81 @@ pub unsafe extern "C" fn sqlite3_busy_timeout(db: *mut sqlite3, ms: ffi::c_int)
     SQLITE_OK
 }
 
+/// Register a callback to be invoked when the connection blocking `db` releases its lock.
+///
+/// If the most recent statement on `db` failed with SQLITE_BUSY because another connection
+/// to the same database held the write lock, `callback` is invoked with `context` once that
+/// connection commits, rolls back or is closed. If `db` is not blocked, the callback is
+/// invoked immediately. Only one callback can be registered per connection; registering a
+/// new one replaces it, and a NULL callback cancels it.
+///
+/// Returns SQLITE_LOCKED without registering anything if waiting would deadlock, that is,
+/// if the blocking connection is itself waiting on `db`, directly or through others.
+#[no_mangle]
+pub unsafe extern "C" fn sqlite3_unlock_notify(
+    db: *mut sqlite3,
+    callback: Option<UnlockNotifyFn>,
+    context: *mut ffi::c_void,
+) -> ffi::c_int {
+    if db.is_null() {
+        return SQLITE_MISUSE;
+    }
+    let key = match (*db).inner.lock() {
+        Ok(inner) => conn_key(&inner.conn),
+        Err(_) => return SQLITE_MISUSE,
+    };
+
+    let mut registry = unlock_notify_registry().lock().unwrap();
+    let Some(callback) = callback else {
+        if let Some(entry) = registry.entries.get_mut(&key) {
+            entry.notify = None;
+        }
+        return SQLITE_OK;
+    };
+    let blocker = registry
+        .entries
+        .get(&key)
+        .and_then(|entry| entry.blocker)
+        .filter(|blocker| {
+            registry
+                .entries
+                .get(blocker)
+                .is_some_and(|e| e.conn.is_in_write_tx())
+        });
+    let Some(blocker) = blocker else {
+        // Not blocked (anymore): there is nothing to wait for
+        registry.set_blocker(key, None);
+        if let Some(entry) = registry.entries.get_mut(&key) {
+            entry.notify = None;
+        }
+        drop(registry);
+        fire_unlock_notify_callbacks(vec![(callback, context as usize)]);
+        return SQLITE_OK;
+    };
+
+    // Follow the chain of connections waiting on each other starting from the blocker;
+    // getting back to `db` means nobody would ever be notified.
+    let mut waiting = Some(blocker);
+    for _ in 0..registry.entries.len() {
+        let Some(current) = waiting else {
+            break;
+        };
+        if current == key {
+            return SQLITE_LOCKED;
+        }
+        waiting = registry
+            .entries
+            .get(&current)
+            .filter(|e| e.notify.is_some())
+            .and_then(|e| e.blocker);
+    }
+
+    if let Some(entry) = registry.entries.get_mut(&key) {
+        entry.notify = Some((callback, context as usize));
+    }
+    SQLITE_OK
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn sqlite3_set_authorizer(
     _db: *mut sqlite3,
@@ -848,7 +1145,8 @@ pub unsafe extern "C" fn sqlite3_prepare_v2(
     if !tail.is_null() {
         *tail = sql.add(stmt.tail_offset());
     }
-    let new_stmt = Box::leak(Box::new(sqlite3_stmt::new(raw_db, stmt)));
+    let stmt_sql = &sql_str[..stmt.tail_offset().min(sql_str.len())];
+    let new_stmt = Box::leak(Box::new(sqlite3_stmt::new(raw_db, stmt, stmt_sql)));
 
     new_stmt.next = db.stmt_list;
     db.stmt_list = new_stmt;
@@ -951,6 +1249,9 @@ pub unsafe extern "C" fn sqlite3_step(stmt: *mut sqlite3_stmt) -> ffi::c_int {
     let delta = current - stmt.prev_search_count;
     stmt.prev_search_count = current;
     sqlite3_search_count = sqlite3_search_count.saturating_add(delta as ffi::c_int);
+    let conn = db_inner.conn.clone();
+    drop(db_inner);
+    update_unlock_notify(&conn, rc);
     rc
 }
 
@@ -995,8 +1296,13 @@ pub unsafe extern "C" fn sqlite3_exec(
         let is_dql = is_query_statement(trimmed);
         if !is_dql {
             // For DML/DDL, use normal execute path
-            let db_inner = db_ref.inner.lock().unwrap();
-            match db_inner.conn.execute(trimmed) {
+            let conn = db_ref.inner.lock().unwrap().conn.clone();
+            let result = conn.execute(trimmed);
+            update_unlock_notify(
+                &conn,
+                result.as_ref().map_or_else(limbo_err_code, |_| SQLITE_OK),
+            );
+            match result {
                 Ok(_) => continue,
                 Err(e) => {
                     return handle_limbo_err(e, err);
@@ -1510,9 +1816,102 @@ pub unsafe extern "C" fn sqlite3_backup_finish(_backup: *mut ffi::c_void) -> ffi
     stub!();
 }
 
+/// Returns the SQL text the statement was prepared from. The string is owned by the
+/// statement and stays valid until it is finalized.
 #[no_mangle]
-pub unsafe extern "C" fn sqlite3_expanded_sql(_stmt: *mut sqlite3_stmt) -> *mut ffi::c_char {
-    stub!();
+pub unsafe extern "C" fn sqlite3_sql(stmt: *mut sqlite3_stmt) -> *const ffi::c_char {
+    if stmt.is_null() {
+        return std::ptr::null();
+    }
+    let stmt = &*stmt;
+    stmt.sql.as_ptr()
+}
+
+/// Returns the statement SQL with bound parameters substituted as SQL literals. The
+/// string is obtained from sqlite3_malloc() and must be released with sqlite3_free().
+#[no_mangle]
+pub unsafe extern "C" fn sqlite3_expanded_sql(stmt: *mut sqlite3_stmt) -> *mut ffi::c_char {
+    if stmt.is_null() {
+        return std::ptr::null_mut();
+    }
+    let stmt = &*stmt;
+    let Ok(sql) = stmt.sql.to_str() else {
+        return std::ptr::null_mut();
+    };
+    let expanded = expand_sql(sql, &stmt.stmt);
+    let buf = libc::malloc(expanded.len() + 1) as *mut u8;
+    if buf.is_null() {
+        return std::ptr::null_mut();
+    }
+    std::ptr::copy_nonoverlapping(expanded.as_ptr(), buf, expanded.len());
+    *buf.add(expanded.len()) = 0;
+    buf as *mut ffi::c_char
+}
+
+/// Substitutes the values bound to `stmt` for the parameters in `sql`. Parameters
+/// inside string literals, quoted identifiers and comments are left untouched, and
+/// unbound parameters expand to NULL, as in SQLite.
+fn expand_sql(sql: &str, stmt: &turso_core::Statement) -> String {
+    let bytes = sql.as_bytes();
+    let mut out = String::with_capacity(sql.len());
+    // Index a bare `?` gets: one past the largest index seen so far.
+    let mut next_index = 1;
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let mut index = None;
+        match bytes[i] {
+            quote @ (b'\'' | b'"' | b'`') => {
+                i += 1;
+                while i < bytes.len() {
+                    i += 1;
+                    if bytes[i - 1] == quote {
+                        if bytes.get(i) != Some(&quote) {
+                            break;
+                        }
+                        i += 1;
+                    }
+                }
+            }
+            b'[' => i = sql[i..].find(']').map_or(bytes.len(), |n| i + n + 1),
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = sql[i..].find('\n').map_or(bytes.len(), |n| i + n + 1)
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = sql[i + 2..].find("*/").map_or(bytes.len(), |n| i + n + 4)
+            }
+            b'?' => {
+                i += 1;
+                while i < bytes.len() && bytes[i].is_ascii_digit() {
+                    i += 1;
+                }
+                index = if i > start + 1 {
+                    sql[start + 1..i].parse().ok().and_then(NonZeroUsize::new)
+                } else {
+                    NonZeroUsize::new(next_index)
+                };
+            }
+            b':' | b'@' | b'$' => {
+                i += 1;
+                while i < bytes.len()
+                    && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || bytes[i] >= 0x80)
+                {
+                    i += 1;
+                }
+                index = stmt.parameter_index(&sql[start..i]);
+            }
+            _ => i += sql[i..].chars().next().map_or(1, char::len_utf8),
+        }
+        match index {
+            Some(index) => {
+                next_index = next_index.max(index.get() + 1);
+                let quoted = stmt.get_parameter(index).exec_quote();
+                out.push_str(quoted.to_text().unwrap_or("NULL"));
+            }
+            None => out.push_str(&sql[start..i]),
+        }
+    }
+    out
 }
 
 #[no_mangle]
@@ -2006,6 +2405,17 @@ pub unsafe extern "C" fn sqlite3_value_bytes(value: *mut ffi::c_void) -> ffi::c_
     }
 }
 
+/// Returns non-zero if the function argument came from a bound parameter
+/// (`f(?)`) rather than from a literal or column in the SQL text (`f(42)`).
+#[no_mangle]
+pub unsafe extern "C" fn sqlite3_value_frombind(value: *mut ffi::c_void) -> ffi::c_int {
+    if value.is_null() {
+        return 0;
+    }
+    let v = &*(value as *const ExtValue);
+    v.from_bind() as ffi::c_int
+}
+
 /// Deep-copies an `sqlite3_value`.  Returns a heap-allocated copy that must
 /// be freed with `sqlite3_value_free`.  Diesel uses this to create
 /// `OwnedSqliteValue` instances.
@@ -2307,14 +2717,33 @@ pub unsafe extern "C" fn sqlite3_result_double(context: *mut ffi::c_void, val: f
     ctx.result = ExtValue::from_float(val);
 }
 
+/// Hands the buffer passed to `sqlite3_result_text`/`sqlite3_result_blob` back to its
+/// owner. The result always holds its own copy of the value, so SQLITE_STATIC (0) and
+/// SQLITE_TRANSIENT (-1) buffers are left to the caller, and any other destructor is
+/// called right away since the buffer is no longer needed.
+unsafe fn sqlite3_result_destroy(buffer: *const ffi::c_void, destroy: *mut ffi::c_void) {
+    let static_ptr = std::ptr::null_mut();
+    let transient_ptr = usize::MAX as *mut ffi::c_void;
+    if buffer.is_null() || destroy == static_ptr || destroy == transient_ptr {
+        return;
+    }
+    let destroy: unsafe extern "C" fn(*mut ffi::c_void) = std::mem::transmute(destroy);
+    destroy(buffer as *mut ffi::c_void);
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn sqlite3_result_text(
     context: *mut ffi::c_void,
     text: *const ffi::c_char,
     len: ffi::c_int,
-    _destroy: *mut ffi::c_void,
+    destroy: *mut ffi::c_void,
 ) {
-    if context.is_null() || text.is_null() {
+    if context.is_null() {
+        sqlite3_result_destroy(text as *const ffi::c_void, destroy);
+        return;
+    }
+    if text.is_null() {
+        sqlite3_result_null(context);
         return;
     }
     let ctx = &mut *(context as *mut SqliteContext);
@@ -2325,6 +2754,7 @@ pub unsafe extern "C" fn sqlite3_result_text(
         String::from_utf8_lossy(bytes).into_owned()
     };
     ctx.result = ExtValue::from_text(s);
+    sqlite3_result_destroy(text as *const ffi::c_void, destroy);
 }
 
 #[no_mangle]
@@ -2332,14 +2762,43 @@ pub unsafe extern "C" fn sqlite3_result_blob(
     context: *mut ffi::c_void,
     blob: *const ffi::c_void,
     len: ffi::c_int,
-    _destroy: *mut ffi::c_void,
+    destroy: *mut ffi::c_void,
 ) {
-    if context.is_null() || blob.is_null() || len < 0 {
+    if context.is_null() || len < 0 {
+        sqlite3_result_destroy(blob, destroy);
+        return;
+    }
+    if blob.is_null() {
+        sqlite3_result_null(context);
         return;
     }
     let ctx = &mut *(context as *mut SqliteContext);
     let bytes = std::slice::from_raw_parts(blob as *const u8, len as usize).to_vec();
     ctx.result = ExtValue::from_blob(bytes);
+    sqlite3_result_destroy(blob, destroy);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn sqlite3_result_zeroblob(context: *mut ffi::c_void, n: ffi::c_int) {
+    sqlite3_result_zeroblob64(context, n.max(0) as u64);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn sqlite3_result_zeroblob64(
+    context: *mut ffi::c_void,
+    n: u64,
+) -> ffi::c_int {
+    if context.is_null() {
+        return SQLITE_MISUSE;
+    }
+    if n > Value::MAX_BLOB_LENGTH as u64 {
+        sqlite3_result_error_toobig(context);
+        return SQLITE_TOOBIG;
+    }
+    let ctx = &mut *(context as *mut SqliteContext);
+    // vec![0; n] takes zeroed memory straight from the allocator, no fill pass.
+    ctx.result = ExtValue::from_blob(vec![0; n as usize]);
+    SQLITE_OK
 }
 
 #[no_mangle]
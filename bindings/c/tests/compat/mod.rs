@@ -27,6 +27,7 @@ extern "C" {
     fn sqlite3_close(db: *mut sqlite3) -> i32;
     fn sqlite3_open(filename: *const libc::c_char, db: *mut *mut sqlite3) -> i32;
     fn sqlite3_db_filename(db: *mut sqlite3, db_name: *const libc::c_char) -> *const libc::c_char;
+    fn sqlite3_db_name(db: *mut sqlite3, n: libc::c_int) -> *const libc::c_char;
     fn sqlite3_exec(
         db: *mut sqlite3,
         sql: *const libc::c_char,
@@ -125,6 +126,11 @@ extern "C" {
         arg: *mut libc::c_void,
     );
     fn sqlite3_busy_timeout(db: *mut sqlite3, ms: i32) -> i32;
+    fn sqlite3_unlock_notify(
+        db: *mut sqlite3,
+        callback: Option<unsafe extern "C" fn(*mut *mut libc::c_void, i32)>,
+        arg: *mut libc::c_void,
+    ) -> i32;
     fn sqlite3_interrupt(db: *mut sqlite3);
     fn sqlite3_get_table(
         db: *mut sqlite3,
@@ -139,6 +145,9 @@ extern "C" {
     fn sqlite3_value_type(value: *mut libc::c_void) -> i32;
     fn sqlite3_value_blob(value: *mut libc::c_void) -> *const libc::c_void;
     fn sqlite3_value_bytes(value: *mut libc::c_void) -> i32;
+    fn sqlite3_value_frombind(value: *mut libc::c_void) -> i32;
+    fn sqlite3_sql(stmt: *mut sqlite3_stmt) -> *const libc::c_char;
+    fn sqlite3_expanded_sql(stmt: *mut sqlite3_stmt) -> *mut libc::c_char;
     fn sqlite3_result_int64(context: *mut libc::c_void, val: i64);
     fn sqlite3_create_function_v2(
         db: *mut sqlite3,
@@ -162,6 +171,19 @@ extern "C" {
     fn sqlite3_db_handle(stmt: *mut sqlite3_stmt) -> *mut sqlite3;
     fn sqlite3_value_int(value: *mut libc::c_void) -> i32;
     fn sqlite3_result_int(context: *mut libc::c_void, val: i32);
+    fn sqlite3_result_text(
+        context: *mut libc::c_void,
+        text: *const libc::c_char,
+        len: i32,
+        destroy: *mut libc::c_void,
+    );
+    fn sqlite3_result_blob(
+        context: *mut libc::c_void,
+        blob: *const libc::c_void,
+        len: i32,
+        destroy: *mut libc::c_void,
+    );
+    fn sqlite3_result_zeroblob(context: *mut libc::c_void, n: i32);
     fn sqlite3_initialize() -> i32;
     fn sqlite3_open_v2(
         filename: *const libc::c_char,
@@ -184,6 +206,7 @@ const SQLITE_MISUSE: i32 = 21;
 const SQLITE_RANGE: i32 = 25;
 const SQLITE_CANTOPEN: i32 = 14;
 const SQLITE_INTERRUPT: i32 = 9;
+const SQLITE_BUSY: i32 = 5;
 const SQLITE_ROW: i32 = 100;
 const SQLITE_DONE: i32 = 101;
 const SQLITE_PREPARE_PERSISTENT: u32 = 0x01;
@@ -2231,6 +2254,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_sqlite3_bind_parameter_index_repeated_name() {
+        const SQLITE_OK: i32 = 0;
+
+        unsafe {
+            let mut db: *mut sqlite3 = ptr::null_mut();
+            let mut stmt: *mut sqlite3_stmt = ptr::null_mut();
+
+            assert_eq!(sqlite3_open(c":memory:".as_ptr(), &mut db), SQLITE_OK);
+
+            assert_eq!(
+                sqlite3_prepare_v2(
+                    db,
+                    c"SELECT :a, :b, :a, $c".as_ptr(),
+                    -1,
+                    &mut stmt,
+                    ptr::null_mut()
+                ),
+                SQLITE_OK
+            );
+
+            // A repeated name shares the index of its first occurrence
+            assert_eq!(sqlite3_bind_parameter_count(stmt), 3);
+            assert_eq!(sqlite3_bind_parameter_index(stmt, c":a".as_ptr()), 1);
+            assert_eq!(sqlite3_bind_parameter_index(stmt, c":b".as_ptr()), 2);
+            assert_eq!(sqlite3_bind_parameter_index(stmt, c"$c".as_ptr()), 3);
+
+            // The sigil is part of the name
+            assert_eq!(sqlite3_bind_parameter_index(stmt, c"a".as_ptr()), 0);
+            assert_eq!(sqlite3_bind_parameter_index(stmt, c":c".as_ptr()), 0);
+            assert_eq!(sqlite3_bind_parameter_index(stmt, c":unknown".as_ptr()), 0);
+
+            assert_eq!(sqlite3_finalize(stmt), SQLITE_OK);
+            assert_eq!(sqlite3_close(db), SQLITE_OK);
+        }
+    }
+
     #[test]
     fn test_sqlite3_db_filename() {
         const SQLITE_OK: i32 = 0;
@@ -2264,14 +2324,74 @@ mod tests {
             assert!(!filename_default.is_null());
             assert_eq!(filename, filename_default);
 
-            // Test with non-existent database name
+            // The temp database has no file, whether it was created yet or not
             let filename = sqlite3_db_filename(db, c"temp".as_ptr());
+            assert!(!filename.is_null());
+            assert_eq!(std::ffi::CStr::from_ptr(filename).to_str().unwrap(), "");
+
+            // Test with non-existent database name
+            let filename = sqlite3_db_filename(db, c"nosuch".as_ptr());
             assert!(filename.is_null());
 
             assert_eq!(sqlite3_close(db), SQLITE_OK);
         }
     }
 
+    #[test]
+    fn test_sqlite3_db_name() {
+        const SQLITE_OK: i32 = 0;
+
+        unsafe {
+            let temp_file = tempfile::NamedTempFile::with_suffix(".db").unwrap();
+            let path = std::ffi::CString::new(temp_file.path().to_str().unwrap()).unwrap();
+            let mut db = ptr::null_mut();
+            assert_eq!(sqlite3_open(path.as_ptr(), &mut db), SQLITE_OK);
+
+            let name = sqlite3_db_name(db, 0);
+            assert_eq!(std::ffi::CStr::from_ptr(name).to_str().unwrap(), "main");
+            let name = sqlite3_db_name(db, 1);
+            assert_eq!(std::ffi::CStr::from_ptr(name).to_str().unwrap(), "temp");
+            assert!(sqlite3_db_name(db, 2).is_null());
+            assert!(sqlite3_db_name(db, -1).is_null());
+
+            let filename = sqlite3_db_filename(db, c"main".as_ptr());
+            let filename_pathbuf =
+                std::fs::canonicalize(std::ffi::CStr::from_ptr(filename).to_str().unwrap())
+                    .unwrap();
+            assert_eq!(filename_pathbuf, temp_file.path().canonicalize().unwrap());
+
+            // Attached databases are listed after main and temp
+            let aux_file = tempfile::NamedTempFile::with_suffix(".db").unwrap();
+            let attach = std::ffi::CString::new(format!(
+                "ATTACH '{}' AS aux; ATTACH ':memory:' AS mem;",
+                aux_file.path().to_str().unwrap()
+            ))
+            .unwrap();
+            let rc = sqlite3_exec(db, attach.as_ptr(), None, ptr::null_mut(), ptr::null_mut());
+            assert_eq!(rc, SQLITE_OK);
+
+            let name = sqlite3_db_name(db, 2);
+            assert_eq!(std::ffi::CStr::from_ptr(name).to_str().unwrap(), "aux");
+            let name = sqlite3_db_name(db, 3);
+            assert_eq!(std::ffi::CStr::from_ptr(name).to_str().unwrap(), "mem");
+
+            let filename = sqlite3_db_filename(db, c"aux".as_ptr());
+            assert!(!filename.is_null());
+            let filename_pathbuf =
+                std::fs::canonicalize(std::ffi::CStr::from_ptr(filename).to_str().unwrap())
+                    .unwrap();
+            assert_eq!(filename_pathbuf, aux_file.path().canonicalize().unwrap());
+
+            let filename = sqlite3_db_filename(db, c"mem".as_ptr());
+            assert!(!filename.is_null());
+            assert_eq!(std::ffi::CStr::from_ptr(filename).to_str().unwrap(), "");
+
+            assert!(sqlite3_db_filename(db, c"missing".as_ptr()).is_null());
+
+            assert_eq!(sqlite3_close(db), SQLITE_OK);
+        }
+    }
+
     #[test]
     fn test_sqlite3_next_stmt() {
         const SQLITE_OK: i32 = 0;
@@ -3720,4 +3840,417 @@ mod tests {
             assert_eq!(sqlite3_close(db), SQLITE_OK);
         }
     }
+
+    #[test]
+    fn test_sqlite3_value_frombind() {
+        unsafe extern "C" fn frombind_fn(
+            ctx: *mut libc::c_void,
+            _argc: i32,
+            argv: *mut *mut libc::c_void,
+        ) {
+            sqlite3_result_int(ctx, sqlite3_value_frombind(*argv));
+        }
+
+        unsafe {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("test.db");
+            let path_cstr = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+            let mut db: *mut sqlite3 = ptr::null_mut();
+            assert_eq!(sqlite3_open(path_cstr.as_ptr(), &mut db), SQLITE_OK);
+
+            assert_eq!(
+                sqlite3_create_function_v2(
+                    db,
+                    c"frombind".as_ptr(),
+                    1,
+                    SQLITE_UTF8,
+                    ptr::null_mut(),
+                    Some(frombind_fn),
+                    None,
+                    None,
+                    None,
+                ),
+                SQLITE_OK
+            );
+
+            // Bound parameter: the UDF must see value_frombind() == 1
+            let mut stmt: *mut sqlite3_stmt = ptr::null_mut();
+            assert_eq!(
+                sqlite3_prepare_v2(
+                    db,
+                    c"SELECT frombind(?)".as_ptr(),
+                    -1,
+                    &mut stmt,
+                    ptr::null_mut(),
+                ),
+                SQLITE_OK
+            );
+            assert_eq!(sqlite3_bind_int(stmt, 1, 42), SQLITE_OK);
+            assert_eq!(sqlite3_step(stmt), SQLITE_ROW);
+            assert_eq!(sqlite3_column_int(stmt, 0), 1);
+            assert_eq!(sqlite3_finalize(stmt), SQLITE_OK);
+
+            // Literal: the UDF must see value_frombind() == 0
+            let mut stmt: *mut sqlite3_stmt = ptr::null_mut();
+            assert_eq!(
+                sqlite3_prepare_v2(
+                    db,
+                    c"SELECT frombind(42)".as_ptr(),
+                    -1,
+                    &mut stmt,
+                    ptr::null_mut(),
+                ),
+                SQLITE_OK
+            );
+            assert_eq!(sqlite3_step(stmt), SQLITE_ROW);
+            assert_eq!(sqlite3_column_int(stmt, 0), 0);
+            assert_eq!(sqlite3_finalize(stmt), SQLITE_OK);
+
+            // The flag follows the register value: a column written into the
+            // same register on a later row must not inherit it.
+            assert_eq!(
+                sqlite3_exec(
+                    db,
+                    c"CREATE TABLE t(c, v); INSERT INTO t VALUES (1, 10), (0, 20), (1, 30);"
+                        .as_ptr(),
+                    None,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                ),
+                SQLITE_OK
+            );
+            let mut stmt: *mut sqlite3_stmt = ptr::null_mut();
+            assert_eq!(
+                sqlite3_prepare_v2(
+                    db,
+                    c"SELECT frombind(CASE WHEN c THEN ?1 ELSE v END) FROM t ORDER BY rowid"
+                        .as_ptr(),
+                    -1,
+                    &mut stmt,
+                    ptr::null_mut(),
+                ),
+                SQLITE_OK
+            );
+            assert_eq!(sqlite3_bind_int(stmt, 1, 42), SQLITE_OK);
+            for expected in [1, 0, 1] {
+                assert_eq!(sqlite3_step(stmt), SQLITE_ROW);
+                assert_eq!(sqlite3_column_int(stmt, 0), expected);
+            }
+            assert_eq!(sqlite3_step(stmt), SQLITE_DONE);
+            assert_eq!(sqlite3_finalize(stmt), SQLITE_OK);
+
+            // A literal equal to the bound value is still not from a bind.
+            let mut stmt: *mut sqlite3_stmt = ptr::null_mut();
+            assert_eq!(
+                sqlite3_prepare_v2(
+                    db,
+                    c"SELECT frombind(CASE WHEN c THEN ?1 ELSE 42 END) FROM t ORDER BY rowid"
+                        .as_ptr(),
+                    -1,
+                    &mut stmt,
+                    ptr::null_mut(),
+                ),
+                SQLITE_OK
+            );
+            assert_eq!(sqlite3_bind_int(stmt, 1, 42), SQLITE_OK);
+            for expected in [1, 0, 1] {
+                assert_eq!(sqlite3_step(stmt), SQLITE_ROW);
+                assert_eq!(sqlite3_column_int(stmt, 0), expected);
+            }
+            assert_eq!(sqlite3_step(stmt), SQLITE_DONE);
+            assert_eq!(sqlite3_finalize(stmt), SQLITE_OK);
+
+            assert_eq!(sqlite3_value_frombind(ptr::null_mut()), 0);
+
+            assert_eq!(sqlite3_close(db), SQLITE_OK);
+        }
+    }
+
+    #[test]
+    fn test_sqlite3_result_zeroblob_and_text_destructors() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static DESTROYED: AtomicUsize = AtomicUsize::new(0);
+
+        unsafe extern "C" fn zeroblob_fn(
+            ctx: *mut libc::c_void,
+            _argc: i32,
+            argv: *mut *mut libc::c_void,
+        ) {
+            sqlite3_result_zeroblob(ctx, sqlite3_value_int(*argv));
+        }
+
+        unsafe extern "C" fn transient_text_fn(
+            ctx: *mut libc::c_void,
+            _argc: i32,
+            _argv: *mut *mut libc::c_void,
+        ) {
+            let mut buf = *b"transient";
+            // SQLITE_TRANSIENT: the text must be copied before this returns.
+            sqlite3_result_text(
+                ctx,
+                buf.as_ptr() as *const libc::c_char,
+                buf.len() as i32,
+                -1isize as *mut libc::c_void,
+            );
+            buf.fill(b'x');
+        }
+
+        unsafe extern "C" fn destroy_text(p: *mut libc::c_void) {
+            DESTROYED.fetch_add(1, Ordering::SeqCst);
+            libc::free(p);
+        }
+
+        unsafe extern "C" fn owned_text_fn(
+            ctx: *mut libc::c_void,
+            _argc: i32,
+            _argv: *mut *mut libc::c_void,
+        ) {
+            let text = libc::strdup(c"owned".as_ptr());
+            sqlite3_result_text(ctx, text, -1, destroy_text as *mut libc::c_void);
+        }
+
+        unsafe extern "C" fn rejected_blob_fn(
+            ctx: *mut libc::c_void,
+            _argc: i32,
+            _argv: *mut *mut libc::c_void,
+        ) {
+            // A negative length is rejected, but the buffer is still released.
+            let blob = libc::strdup(c"rejected".as_ptr());
+            sqlite3_result_blob(
+                ctx,
+                blob as *const libc::c_void,
+                -1,
+                destroy_text as *mut libc::c_void,
+            );
+        }
+
+        unsafe extern "C" fn null_text_fn(
+            ctx: *mut libc::c_void,
+            _argc: i32,
+            _argv: *mut *mut libc::c_void,
+        ) {
+            sqlite3_result_text(ctx, c"previous".as_ptr(), -1, ptr::null_mut());
+            sqlite3_result_text(ctx, ptr::null(), -1, -1isize as *mut libc::c_void);
+        }
+
+        unsafe {
+            let mut db: *mut sqlite3 = ptr::null_mut();
+            assert_eq!(sqlite3_open(c":memory:".as_ptr(), &mut db), SQLITE_OK);
+
+            type ScalarFn = unsafe extern "C" fn(*mut libc::c_void, i32, *mut *mut libc::c_void);
+            let funcs: [(&std::ffi::CStr, i32, ScalarFn); 5] = [
+                (c"zb", 1, zeroblob_fn),
+                (c"transient_text", 0, transient_text_fn),
+                (c"owned_text", 0, owned_text_fn),
+                (c"rejected_blob", 0, rejected_blob_fn),
+                (c"null_text", 0, null_text_fn),
+            ];
+            for (name, n_args, func) in funcs {
+                assert_eq!(
+                    sqlite3_create_function_v2(
+                        db,
+                        name.as_ptr(),
+                        n_args,
+                        SQLITE_UTF8,
+                        ptr::null_mut(),
+                        Some(func),
+                        None,
+                        None,
+                        None,
+                    ),
+                    SQLITE_OK
+                );
+            }
+
+            let mut stmt: *mut sqlite3_stmt = ptr::null_mut();
+            assert_eq!(
+                sqlite3_prepare_v2(
+                    db,
+                    c"SELECT zb(5), length(zb(5)), zb(5) = x'0000000000', transient_text(), \
+                      owned_text(), rejected_blob(), null_text()"
+                        .as_ptr(),
+                    -1,
+                    &mut stmt,
+                    ptr::null_mut(),
+                ),
+                SQLITE_OK
+            );
+            assert_eq!(sqlite3_step(stmt), SQLITE_ROW);
+            assert_eq!(sqlite3_column_type(stmt, 0), SQLITE_BLOB);
+            let blob = sqlite3_column_blob(stmt, 0) as *const u8;
+            assert_eq!(std::slice::from_raw_parts(blob, 5), &[0u8; 5]);
+            assert_eq!(sqlite3_column_int(stmt, 1), 5);
+            assert_eq!(sqlite3_column_int(stmt, 2), 1);
+            let text = std::ffi::CStr::from_ptr(sqlite3_column_text(stmt, 3));
+            assert_eq!(text.to_str().unwrap(), "transient");
+            let text = std::ffi::CStr::from_ptr(sqlite3_column_text(stmt, 4));
+            assert_eq!(text.to_str().unwrap(), "owned");
+            assert_eq!(sqlite3_column_type(stmt, 5), SQLITE_NULL);
+            assert_eq!(sqlite3_column_type(stmt, 6), SQLITE_NULL);
+            assert_eq!(sqlite3_step(stmt), SQLITE_DONE);
+            assert_eq!(sqlite3_finalize(stmt), SQLITE_OK);
+            // The custom destructor ran exactly once per call, rejected or not
+            assert_eq!(DESTROYED.load(Ordering::SeqCst), 2);
+
+            // Without a context the buffer is released too
+            let text = libc::strdup(c"orphan".as_ptr());
+            sqlite3_result_text(ptr::null_mut(), text, -1, destroy_text as *mut libc::c_void);
+            assert_eq!(DESTROYED.load(Ordering::SeqCst), 3);
+
+            assert_eq!(sqlite3_close(db), SQLITE_OK);
+        }
+    }
+
+    #[test]
+    fn test_sqlite3_sql_and_expanded_sql() {
+        unsafe {
+            let mut db: *mut sqlite3 = ptr::null_mut();
+            assert_eq!(sqlite3_open(c":memory:".as_ptr(), &mut db), SQLITE_OK);
+
+            let mut stmt: *mut sqlite3_stmt = ptr::null_mut();
+            assert_eq!(
+                sqlite3_prepare_v2(db, c"SELECT ?, 1".as_ptr(), -1, &mut stmt, ptr::null_mut(),),
+                SQLITE_OK
+            );
+            assert_eq!(sqlite3_bind_int(stmt, 1, 42), SQLITE_OK);
+
+            // sqlite3_sql returns the original text, bindings untouched
+            let sql = sqlite3_sql(stmt);
+            assert!(!sql.is_null());
+            assert_eq!(
+                std::ffi::CStr::from_ptr(sql).to_str().unwrap(),
+                "SELECT ?, 1"
+            );
+
+            // sqlite3_expanded_sql substitutes the bound value
+            let expanded = sqlite3_expanded_sql(stmt);
+            assert!(!expanded.is_null());
+            assert_eq!(
+                std::ffi::CStr::from_ptr(expanded).to_str().unwrap(),
+                "SELECT 42, 1"
+            );
+            sqlite3_free(expanded as *mut libc::c_void);
+
+            // The original text stays valid across execution
+            assert_eq!(sqlite3_step(stmt), SQLITE_ROW);
+            assert_eq!(
+                std::ffi::CStr::from_ptr(sqlite3_sql(stmt))
+                    .to_str()
+                    .unwrap(),
+                "SELECT ?, 1"
+            );
+            assert_eq!(sqlite3_finalize(stmt), SQLITE_OK);
+
+            assert!(sqlite3_sql(ptr::null_mut()).is_null());
+
+            assert_eq!(sqlite3_close(db), SQLITE_OK);
+        }
+    }
+
+    unsafe extern "C" fn unlock_notify_count(args: *mut *mut libc::c_void, n: i32) {
+        for i in 0..n as usize {
+            let counter = *args.add(i) as *const std::sync::atomic::AtomicI32;
+            (*counter).fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    // SQLite only reports shared-cache table locks to unlock_notify
+    #[test]
+    #[cfg(not(feature = "sqlite3"))]
+    fn test_sqlite3_unlock_notify_fires_after_blocker_commits() {
+        use std::sync::atomic::{AtomicI32, Ordering};
+
+        unsafe {
+            let temp_file = tempfile::NamedTempFile::with_suffix(".db").unwrap();
+            let path = std::ffi::CString::new(temp_file.path().to_str().unwrap()).unwrap();
+            let mut db1: *mut sqlite3 = ptr::null_mut();
+            let mut db2: *mut sqlite3 = ptr::null_mut();
+            assert_eq!(sqlite3_open(path.as_ptr(), &mut db1), SQLITE_OK);
+            assert_eq!(sqlite3_open(path.as_ptr(), &mut db2), SQLITE_OK);
+
+            assert_eq!(
+                sqlite3_exec(
+                    db1,
+                    c"CREATE TABLE t(x)".as_ptr(),
+                    None,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                ),
+                SQLITE_OK
+            );
+
+            // Not blocked: the callback fires right away
+            let fired = AtomicI32::new(0);
+            let fired_arg = &fired as *const AtomicI32 as *mut libc::c_void;
+            assert_eq!(
+                sqlite3_unlock_notify(db2, Some(unlock_notify_count), fired_arg),
+                SQLITE_OK
+            );
+            assert_eq!(fired.swap(0, Ordering::SeqCst), 1);
+
+            // db1 takes the write lock, so db2's write is busy
+            assert_eq!(
+                sqlite3_exec(
+                    db1,
+                    c"BEGIN; INSERT INTO t VALUES (1)".as_ptr(),
+                    None,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                ),
+                SQLITE_OK
+            );
+            let mut stmt: *mut sqlite3_stmt = ptr::null_mut();
+            assert_eq!(
+                sqlite3_prepare_v2(
+                    db2,
+                    c"INSERT INTO t VALUES (2)".as_ptr(),
+                    -1,
+                    &mut stmt,
+                    ptr::null_mut(),
+                ),
+                SQLITE_OK
+            );
+            assert_eq!(sqlite3_step(stmt), SQLITE_BUSY);
+
+            assert_eq!(
+                sqlite3_unlock_notify(db2, Some(unlock_notify_count), fired_arg),
+                SQLITE_OK
+            );
+            assert_eq!(fired.load(Ordering::SeqCst), 0);
+
+            // Statements that keep the lock do not release waiters
+            assert_eq!(
+                sqlite3_exec(
+                    db1,
+                    c"INSERT INTO t VALUES (3)".as_ptr(),
+                    None,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                ),
+                SQLITE_OK
+            );
+            assert_eq!(fired.load(Ordering::SeqCst), 0);
+
+            assert_eq!(
+                sqlite3_exec(
+                    db1,
+                    c"COMMIT".as_ptr(),
+                    None,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                ),
+                SQLITE_OK
+            );
+            assert_eq!(fired.load(Ordering::SeqCst), 1);
+
+            // The retry now goes through
+            sqlite3_reset(stmt);
+            assert_eq!(sqlite3_step(stmt), SQLITE_DONE);
+            assert_eq!(sqlite3_finalize(stmt), SQLITE_OK);
+            assert_eq!(fired.load(Ordering::SeqCst), 1);
+
+            assert_eq!(sqlite3_close(db2), SQLITE_OK);
+            assert_eq!(sqlite3_close(db1), SQLITE_OK);
+        }
+    }
 }
@@ -10,7 +10,9 @@ use codspeed_criterion_compat::{
 
 use std::sync::Arc;
 use turso_core::types::Value;
-use turso_core::vdbe::hash_table::{HashTable, HashTableConfig};
+use turso_core::vdbe::hash_table::{
+    HashTable, HashTableConfig, JoinMode, DEFAULT_MAX_LOAD_FACTOR, DEFAULT_REPARTITION_FANOUT,
+};
 use turso_core::vdbe::CollationSeq;
 use turso_core::{IOResult, MemoryIO, Numeric};
 
@@ -29,6 +31,17 @@ fn create_hash_table(mem_budget: usize) -> HashTable {
         temp_store: turso_core::TempStore::Default,
         track_matched: false,
         partition_count: None,
+        mmap_spill_reads: false,
+        repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+        max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+        enable_bloom_filter: false,
+        expected_entries: None,
+        join_mode: JoinMode::Inner,
+        null_safe: false,
+        seed: 0,
+        deferred_index: false,
+        max_matches_per_key: None,
+        shrink_on_finalize: true,
     };
     HashTable::new(config, io).unwrap()
 }
@@ -228,6 +241,35 @@ fn bench_build_and_probe(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmark: Probe throughput on sequential integer keys, the common rowid join
+/// pattern, which shows how well the join key hash spreads adjacent keys.
+#[turso_macros::codspeed_criterion_benchmark]
+fn bench_probe_sequential_keys(c: &mut Criterion) {
+    let mut group = c.benchmark_group("HashTable Probe (Sequential Keys)");
+    let count = 1_000_000;
+
+    let mut ht = create_hash_table(1024 * 1024 * 1024);
+    insert_integer_entries(&mut ht, count);
+    let _ = ht.finalize_build(None);
+    assert!(!ht.has_spilled());
+
+    group.throughput(Throughput::Elements(count as u64));
+    group.bench_function(BenchmarkId::new("probe", count), |b| {
+        b.iter(|| {
+            let mut found = 0;
+            for i in 0..count {
+                let key = turso_core::alloc::vec![Value::from_i64(i as i64)];
+                if ht.probe(key, None).unwrap().is_some() {
+                    found += 1;
+                }
+            }
+            black_box(found)
+        });
+    });
+
+    group.finish();
+}
+
 /// Benchmark: Text key hashing (tests NOCASE optimization)
 #[turso_macros::codspeed_criterion_benchmark]
 fn bench_text_key_hashing(c: &mut Criterion) {
@@ -251,6 +293,17 @@ fn bench_text_key_hashing(c: &mut Criterion) {
                         temp_store: turso_core::TempStore::Default,
                         track_matched: false,
                         partition_count: None,
+                        mmap_spill_reads: false,
+                        repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+                        max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+                        enable_bloom_filter: false,
+                        expected_entries: None,
+                        join_mode: JoinMode::Inner,
+                        null_safe: false,
+                        seed: 0,
+                        deferred_index: false,
+                        max_matches_per_key: None,
+                        shrink_on_finalize: true,
                     };
                     let mut ht = HashTable::new(config, io).unwrap();
                     insert_text_key_entries(&mut ht, count);
@@ -275,6 +328,17 @@ fn bench_text_key_hashing(c: &mut Criterion) {
                         temp_store: turso_core::TempStore::Default,
                         track_matched: false,
                         partition_count: None,
+                        mmap_spill_reads: false,
+                        repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+                        max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+                        enable_bloom_filter: false,
+                        expected_entries: None,
+                        join_mode: JoinMode::Inner,
+                        null_safe: false,
+                        seed: 0,
+                        deferred_index: false,
+                        max_matches_per_key: None,
+                        shrink_on_finalize: true,
                     };
                     let mut ht = HashTable::new(config, io).unwrap();
                     insert_text_key_entries(&mut ht, count);
@@ -318,14 +382,14 @@ fn bench_large_payload_spill(c: &mut Criterion) {
 criterion_group! {
     name = benches;
     config = Criterion::default().with_profiler(PProfProfiler::new(100, Output::Flamegraph(None)));
-    targets = bench_build_tight_budget, bench_build_relaxed_budget, bench_build_and_probe, bench_text_key_hashing, bench_large_payload_spill
+    targets = bench_build_tight_budget, bench_build_relaxed_budget, bench_build_and_probe, bench_probe_sequential_keys, bench_text_key_hashing, bench_large_payload_spill
 }
 
 #[cfg(feature = "codspeed")]
 criterion_group! {
     name = benches;
     config = Criterion::default();
-    targets = bench_build_tight_budget, bench_build_relaxed_budget, bench_build_and_probe, bench_text_key_hashing, bench_large_payload_spill
+    targets = bench_build_tight_budget, bench_build_relaxed_budget, bench_build_and_probe, bench_probe_sequential_keys, bench_text_key_hashing, bench_large_payload_spill
 }
 
 criterion_main!(benches);
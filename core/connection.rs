@@ -390,6 +390,9 @@ pub struct Connection {
     pub(super) attached_databases: RwLock<DatabaseCatalog>,
     pub(super) query_only: AtomicBool,
     pub(super) vdbe_trace: AtomicBool,
+    /// If enabled, hash joins and DISTINCT read spilled partitions back through
+    /// memory-mapped files instead of `pread`. Off by default.
+    pub(super) mmap_spill_reads: AtomicBool,
     /// If enabled, the UPDATE/DELETE statements must have a WHERE clause
     pub(super) dml_require_where: AtomicBool,
     /// SQLite DQS misfeature: when ON (default), unresolved double-quoted identifiers
@@ -470,6 +473,8 @@ pub struct Connection {
     pub(crate) n_active_root_statements: AtomicI32,
     /// Whether pragma ignore_check_constraints=ON for this connection
     pub(super) check_constraints_pragma: AtomicBool,
+    /// Whether pragma case_sensitive_like=ON for this connection
+    pub(super) case_sensitive_like_pragma: AtomicBool,
     /// Track when each virtual table instance is currently in transaction.
     pub(crate) vtab_txn_states: RwLock<HashSet<u64>>,
     /// Connection-level named savepoint stack used to mirror savepoint state
@@ -1819,6 +1824,15 @@ impl Connection {
         self.check_constraints_pragma.load(Ordering::Acquire)
     }
 
+    pub fn set_case_sensitive_like(&self, enable: bool) {
+        self.case_sensitive_like_pragma
+            .store(enable, Ordering::Release);
+    }
+
+    pub fn case_sensitive_like(&self) -> bool {
+        self.case_sensitive_like_pragma.load(Ordering::Acquire)
+    }
+
     pub(crate) fn clear_deferred_foreign_key_violations(&self) -> isize {
         self.fk_deferred_violations.swap(0, Ordering::Release)
     }
@@ -3616,6 +3630,14 @@ impl Connection {
         self.vdbe_trace.load(Ordering::SeqCst)
     }
 
+    pub fn get_mmap_spill_reads(&self) -> bool {
+        self.mmap_spill_reads.load(Ordering::SeqCst)
+    }
+
+    pub fn set_mmap_spill_reads(&self, value: bool) {
+        self.mmap_spill_reads.store(value, Ordering::SeqCst);
+    }
+
     pub fn get_dml_require_where(&self) -> bool {
         self.dml_require_where.load(Ordering::SeqCst)
     }
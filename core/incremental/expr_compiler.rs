@@ -421,7 +421,7 @@ impl CompiledExpression {
 
                 // The compiled expression puts the result in register 0
                 match state.get_register(0) {
-                    Register::Value(v) => Ok(v.clone()),
+                    Register::Value(v) | Register::Bound(v) => Ok(v.clone()),
                     _ => Ok(Value::Null),
                 }
             }
@@ -3010,7 +3010,8 @@ impl IndexMethodCursor for FtsCursor {
             LimboError::InternalError("FTS insert requires at least rowid".into())
         })?;
         let rowid = match rowid_reg {
-            Register::Value(Value::Numeric(crate::numeric::Numeric::Integer(i))) => *i,
+            Register::Value(Value::Numeric(crate::numeric::Numeric::Integer(i)))
+            | Register::Bound(Value::Numeric(crate::numeric::Numeric::Integer(i))) => *i,
             _ => {
                 return Err(LimboError::InternalError(
                     "FTS rowid must be integer".into(),
@@ -3023,7 +3024,7 @@ impl IndexMethodCursor for FtsCursor {
 
         for ((_col, field), reg) in self.text_fields.iter().zip(&values[..values.len() - 1]) {
             match reg {
-                Register::Value(Value::Text(t)) => {
+                Register::Value(Value::Text(t)) | Register::Bound(Value::Text(t)) => {
                     doc.add_text(*field, t.as_str());
                 }
                 Register::Value(Value::Null) => continue,
@@ -3058,7 +3059,8 @@ impl IndexMethodCursor for FtsCursor {
             LimboError::InternalError("FTS delete requires at least rowid".into())
         })?;
         let rowid = match rowid_reg {
-            Register::Value(Value::Numeric(crate::numeric::Numeric::Integer(i))) => *i,
+            Register::Value(Value::Numeric(crate::numeric::Numeric::Integer(i)))
+            | Register::Bound(Value::Numeric(crate::numeric::Numeric::Integer(i))) => *i,
             _ => {
                 return Err(LimboError::InternalError(
                     "FTS rowid must be integer".into(),
@@ -3095,14 +3097,17 @@ impl IndexMethodCursor for FtsCursor {
 
         // values[0] = pattern index
         let pattern_idx = match &values[0] {
-            Register::Value(Value::Numeric(crate::numeric::Numeric::Integer(i))) => *i,
+            Register::Value(Value::Numeric(crate::numeric::Numeric::Integer(i)))
+            | Register::Bound(Value::Numeric(crate::numeric::Numeric::Integer(i))) => *i,
             _ => FTS_PATTERN_SCORE,
         };
         self.current_pattern = pattern_idx;
 
         // values[1] = query string
         let query_str = match &values[1] {
-            Register::Value(Value::Text(t)) => t.as_str().to_string(),
+            Register::Value(Value::Text(t)) | Register::Bound(Value::Text(t)) => {
+                t.as_str().to_string()
+            }
             _ => return Err(LimboError::InternalError("FTS query must be text".into())),
         };
 
@@ -3121,7 +3126,10 @@ impl IndexMethodCursor for FtsCursor {
             | FTS_PATTERN_COMBINED_ORDERED_LIMIT => {
                 if values.len() > 2 {
                     match &values[2] {
-                        Register::Value(Value::Numeric(crate::numeric::Numeric::Integer(i))) => *i,
+                        Register::Value(Value::Numeric(crate::numeric::Numeric::Integer(i)))
+                        | Register::Bound(Value::Numeric(crate::numeric::Numeric::Integer(i))) => {
+                            *i
+                        }
                         _ => {
                             tracing::debug!(
                                 "FTS query_start: LIMIT value is not an integer, using default 10"
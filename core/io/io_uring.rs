@@ -1,13 +1,13 @@
 #![allow(clippy::arc_with_non_send_sync)]
 
 use super::{
-    common, Completion, CompletionInner, File, OpenFlags, SharedWalLockKind, SharedWalMappedRegion,
-    IO,
+    common, Completion, CompletionInner, File, MappedRegion, OpenFlags, SharedWalLockKind,
+    SharedWalMappedRegion, IO,
 };
 use crate::error::io_error;
 use crate::io::clock::{Clock, DefaultClock, MonotonicInstant, WallClockInstant};
 use crate::io::unix::{
-    unix_shared_wal_lock_byte, unix_shared_wal_map, unix_shared_wal_unlock_byte,
+    unix_map_read, unix_shared_wal_lock_byte, unix_shared_wal_map, unix_shared_wal_unlock_byte,
 };
 use crate::storage::wal::CKPT_BATCH_PAGES;
 use crate::sync::Mutex;
@@ -880,6 +880,10 @@ impl File for UringFile {
     fn shared_wal_map(&self, offset: u64, len: usize) -> Result<Box<dyn SharedWalMappedRegion>> {
         unix_shared_wal_map(offset, len, self.file.as_raw_fd())
     }
+
+    fn map_read(&self, pos: u64, len: usize) -> Result<Option<Box<dyn MappedRegion>>> {
+        unix_map_read(pos, len, self.file.as_raw_fd())
+    }
 }
 
 impl Drop for UringFile {
@@ -148,6 +148,11 @@ pub trait SharedWalMappedRegion: Send + Sync {
     }
 }
 
+/// A read-only memory mapping of a file region, see [File::map_read].
+pub trait MappedRegion: Send + Sync {
+    fn as_slice(&self) -> &[u8];
+}
+
 pub trait File: Send + Sync {
     fn lock_file(&self, exclusive: bool) -> Result<()>;
     fn unlock_file(&self) -> Result<()>;
@@ -252,6 +257,14 @@ pub trait File: Send + Sync {
             "shared WAL coordination memory mapping is not supported for this file".into(),
         ))
     }
+
+    /// Optional method implemented by IOs that can memory-map files.
+    /// Maps `len` bytes starting at `pos` read-only. Returns `None` when mapping is not
+    /// supported, in which case callers must fall back to `pread`.
+    /// The region must have been fully written (and its write completed) before mapping.
+    fn map_read(&self, _pos: u64, _len: usize) -> Result<Option<Box<dyn MappedRegion>>> {
+        Ok(None)
+    }
 }
 
 pub struct TempFile {
@@ -331,6 +344,12 @@ impl TempFile {
             Self::new(io)
         }
     }
+
+    /// Directory holding the file, removed when the [TempFile] is dropped.
+    #[cfg(test)]
+    pub(crate) fn dir(&self) -> Option<&std::path::Path> {
+        self._temp_dir.as_ref().map(|dir| dir.path())
+    }
 }
 
 impl core::ops::Deref for TempFile {
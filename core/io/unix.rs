@@ -1,4 +1,6 @@
-use super::{Completion, File, OpenFlags, SharedWalLockKind, SharedWalMappedRegion, IO};
+use super::{
+    Completion, File, MappedRegion, OpenFlags, SharedWalLockKind, SharedWalMappedRegion, IO,
+};
 use crate::error::{io_error, CompletionError, LimboError};
 use crate::io::clock::{Clock, DefaultClock, MonotonicInstant, WallClockInstant};
 use crate::io::common;
@@ -122,6 +124,35 @@ impl Drop for UnixSharedWalMapping {
     }
 }
 
+pub(crate) struct UnixReadMapping {
+    mapping_ptr: NonNull<u8>,
+    mapping_len: usize,
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+unsafe impl Send for UnixReadMapping {}
+unsafe impl Sync for UnixReadMapping {}
+
+impl MappedRegion for UnixReadMapping {
+    fn as_slice(&self) -> &[u8] {
+        // SAFETY: the mapping stays valid until `self` is dropped and is never written through.
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for UnixReadMapping {
+    fn drop(&mut self) {
+        let rc = unsafe { libc::munmap(self.mapping_ptr.as_ptr().cast(), self.mapping_len) };
+        if rc != 0 {
+            tracing::error!(
+                "munmap failed for read-only file mapping: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+}
+
 pub(crate) fn unix_shared_wal_lock_byte(
     fd: RawFd,
     offset: u64,
@@ -274,6 +305,55 @@ pub(crate) fn unix_shared_wal_map(
     }))
 }
 
+/// Map `len` bytes of `fd` starting at `offset` read-only.
+pub(crate) fn unix_map_read(
+    offset: u64,
+    len: usize,
+    fd: RawFd,
+) -> Result<Option<Box<dyn MappedRegion>>> {
+    if len == 0 {
+        return Ok(None);
+    }
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if page_size <= 0 {
+        return Ok(None);
+    }
+    let page_size = page_size as u64;
+    let aligned_offset = offset / page_size * page_size;
+    let prefix_len = (offset - aligned_offset) as usize;
+    let mapping_len = prefix_len
+        .checked_add(len)
+        .ok_or_else(|| LimboError::InternalError("mmap length overflow".into()))?;
+    let mapping_ptr = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            mapping_len,
+            libc::PROT_READ,
+            libc::MAP_SHARED,
+            fd,
+            aligned_offset as libc::off_t,
+        )
+    };
+    if mapping_ptr == libc::MAP_FAILED {
+        // Not fatal: callers fall back to pread.
+        debug!(
+            "mmap of fd={fd} offset={offset} len={len} failed: {}",
+            std::io::Error::last_os_error()
+        );
+        return Ok(None);
+    }
+    let mapping_ptr =
+        NonNull::new(mapping_ptr.cast::<u8>()).expect("mmap returned null for read mapping");
+    let ptr = NonNull::new(unsafe { mapping_ptr.as_ptr().add(prefix_len) })
+        .expect("aligned mmap base plus prefix_len returned null");
+    Ok(Some(Box::new(UnixReadMapping {
+        mapping_ptr,
+        mapping_len,
+        ptr,
+        len,
+    })))
+}
+
 impl File for UnixFile {
     fn lock_file(&self, exclusive: bool) -> Result<()> {
         let fd = self.file.as_fd();
@@ -541,6 +621,10 @@ impl File for UnixFile {
     fn shared_wal_map(&self, offset: u64, len: usize) -> Result<Box<dyn SharedWalMappedRegion>> {
         unix_shared_wal_map(offset, len, self.file.as_raw_fd())
     }
+
+    fn map_read(&self, pos: u64, len: usize) -> Result<Option<Box<dyn MappedRegion>>> {
+        unix_map_read(pos, len, self.file.as_raw_fd())
+    }
 }
 
 /// Append iovec entries for `buf` to `iovecs`, splitting `buf` into chunks of
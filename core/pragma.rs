@@ -207,6 +207,7 @@ pub fn pragma_for(pragma: &PragmaName) -> Pragma {
             PragmaFlags::NoColumns1 | PragmaFlags::Result0,
             &["cache_spill"],
         ),
+        CaseSensitiveLike => Pragma::new(PragmaFlags::NoColumns, &[]),
         #[cfg(target_vendor = "apple")]
         PragmaName::Fullfsync => Pragma::new(
             PragmaFlags::NoColumns1 | PragmaFlags::Result0,
@@ -224,6 +225,10 @@ pub fn pragma_for(pragma: &PragmaName) -> Pragma {
             PragmaFlags::NoColumns1 | PragmaFlags::Result0,
             &["vdbe_trace"],
         ),
+        MmapSpillReads => Pragma::new(
+            PragmaFlags::NoColumns1 | PragmaFlags::Result0,
+            &["mmap_spill_reads"],
+        ),
     }
 }
 
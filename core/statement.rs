@@ -1232,6 +1232,11 @@ impl Statement {
         Ok(())
     }
 
+    /// Returns the value bound at `index`, or NULL if nothing is bound there.
+    pub fn get_parameter(&self, index: NonZero<usize>) -> Value {
+        self.state.get_parameter(index)
+    }
+
     pub fn clear_bindings(&mut self) {
         self.state.clear_bindings();
     }
@@ -421,6 +421,11 @@ fn update_pragma(
             connection.set_short_column_names(enabled);
             Ok(TransactionMode::None)
         }
+        PragmaName::CaseSensitiveLike => {
+            let enabled = parse_pragma_enabled(&value);
+            connection.set_case_sensitive_like(enabled);
+            Ok(TransactionMode::None)
+        }
         PragmaName::LegacyFileFormat | PragmaName::EmptyResultCallbacks => {
             Ok(TransactionMode::None)
         }
@@ -771,6 +776,11 @@ fn update_pragma(
             connection.set_vdbe_trace(enabled);
             Ok(TransactionMode::None)
         }
+        PragmaName::MmapSpillReads => {
+            let enabled = parse_pragma_enabled(&value);
+            connection.set_mmap_spill_reads(enabled);
+            Ok(TransactionMode::None)
+        }
 
         PragmaName::FunctionList => query_pragma(
             PragmaName::FunctionList,
@@ -897,9 +907,10 @@ fn query_pragma(
             program.add_pragma_result_column(pragma.to_string());
             Ok(TransactionMode::None)
         }
-        PragmaName::LegacyFileFormat | PragmaName::EmptyResultCallbacks => {
-            Ok(TransactionMode::None)
-        }
+        // Like SQLite, case_sensitive_like can only be set, not queried
+        PragmaName::LegacyFileFormat
+        | PragmaName::EmptyResultCallbacks
+        | PragmaName::CaseSensitiveLike => Ok(TransactionMode::None),
         PragmaName::WalCheckpoint => {
             // Checkpoint uses 3 registers: P1, P2, P3. Ref Insn::Checkpoint for more info.
             // Allocate two more here as one was allocated at the top.
@@ -1477,6 +1488,14 @@ fn query_pragma(
             Ok(TransactionMode::None)
         }
         PragmaName::VdbeTrace => Ok(TransactionMode::None),
+        PragmaName::MmapSpillReads => {
+            let register = program.alloc_register();
+            let enabled = connection.get_mmap_spill_reads();
+            program.emit_int(enabled as i64, register);
+            program.emit_result_row(register, 1);
+            program.add_pragma_result_column(pragma.to_string());
+            Ok(TransactionMode::None)
+        }
         PragmaName::FreelistCount => {
             let value = pager.freepage_list();
             let register = program.alloc_register();
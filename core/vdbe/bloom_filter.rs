@@ -10,7 +10,7 @@ use crate::types::{Value, ValueRef};
 const DEFAULT_EXPECTED_ITEMS: u32 = 1024;
 
 /// Default false positive rate (1%).
-const DEFAULT_FALSE_POSITIVE_RATE: f32 = 0.01;
+pub(crate) const DEFAULT_FALSE_POSITIVE_RATE: f32 = 0.01;
 
 /// A bloom filter for fast probabilistic set membership testing.
 ///
@@ -121,6 +121,14 @@ impl BloomFilter {
         self.inner.contains(&hash)
     }
 
+    /// Estimated false positive rate for the items inserted so far,
+    /// `(1 - e^(-k * n / m))^k` for `n` items, `k` hashes and `m` bits.
+    pub fn estimated_false_positive_rate(&self) -> f64 {
+        let num_hashes = self.inner.num_hashes() as f64;
+        let num_bits = self.inner.num_bits() as f64;
+        (1.0 - (-num_hashes * self.count as f64 / num_bits).exp()).powf(num_hashes)
+    }
+
     pub fn count(&self) -> usize {
         self.count
     }
@@ -40,7 +40,8 @@ use crate::vdbe::affinity::{
     apply_numeric_affinity, real_to_i64, try_for_float, Affinity, NumericParseResult, ParsedNumber,
 };
 use crate::vdbe::hash_table::{
-    HashEntry, HashInsertResult, HashTable, HashTableConfig, PendingHashInsert, DEFAULT_MEM_BUDGET,
+    HashEntry, HashInsertResult, HashTable, HashTableConfig, JoinKey, JoinMode, PendingHashInsert,
+    DEFAULT_BUCKETS, DEFAULT_MAX_LOAD_FACTOR, DEFAULT_MEM_BUDGET, DEFAULT_REPARTITION_FANOUT,
 };
 use crate::vdbe::insn::InsertFlags;
 use crate::vdbe::metrics::HashJoinMetrics;
@@ -5762,6 +5763,7 @@ pub fn op_decr_jump_zero(
     if !target_pc.is_offset() {
         crate::bail_corrupt_error!("Unresolved label: {target_pc:?}");
     }
+    state.registers[*reg].unbind();
     match &mut state.registers[*reg] {
         Register::Value(Value::Numeric(Numeric::Integer(n))) => {
             *n = n.saturating_sub(1);
@@ -5771,7 +5773,7 @@ pub fn op_decr_jump_zero(
                 state.pc += 1;
             }
         }
-        Register::Value(_) | Register::Record(_) => {
+        Register::Value(_) | Register::Bound(_) | Register::Record(_) => {
             bail_constraint_error!("datatype mismatch");
         }
         Register::Aggregate(_) => {
@@ -7621,7 +7623,12 @@ pub fn op_function(
                         };
 
                         // 4. Execute Like
-                        let matches = Value::exec_like(&pattern_cow, &match_cow, escape_char)?;
+                        let matches = Value::exec_like_with_case(
+                            &pattern_cow,
+                            &match_cow,
+                            escape_char,
+                            program.connection.case_sensitive_like(),
+                        )?;
                         state.registers[*dest].set_int(matches as i64);
                     }
                 }
@@ -8778,18 +8785,16 @@ pub fn op_function(
                 ..
             } => {
                 let mut ext_values = Vec::with_capacity(arg_count);
-                if arg_count != 0 {
-                    let register_slice = &state.registers[*start_reg..*start_reg + arg_count];
-                    for ov in register_slice.iter() {
-                        ext_values.push(ov.get_value().to_ffi());
-                    }
+                let register_slice = &state.registers[*start_reg..*start_reg + arg_count];
+                for ov in register_slice {
+                    ext_values.push(ov.get_value().to_ffi());
                 }
                 let argv_ptr = if ext_values.is_empty() {
                     std::ptr::null()
                 } else {
                     ext_values.as_ptr()
                 };
-                let mut result = unsafe {
+                let call = || unsafe {
                     callback(
                         context,
                         arg_count as i32,
@@ -8798,6 +8803,12 @@ pub fn op_function(
                         value_destructor,
                     )
                 };
+                let mut result = if register_slice.iter().any(Register::is_bound) {
+                    let bound = register_slice.iter().map(Register::is_bound).collect();
+                    turso_ext::with_bound_args(&ext_values, bound, call)
+                } else {
+                    call()
+                };
                 let value = Value::from_ffi_ref(&result);
                 if let Some(value_destructor) = value_destructor {
                     unsafe { value_destructor(&mut result) };
@@ -9916,7 +9927,7 @@ pub fn op_insert(
                     if existing_key == Some(key) {
                         let record = match &state.registers[*record_reg] {
                             Register::Record(r) => std::borrow::Cow::Borrowed(r),
-                            Register::Value(value) => {
+                            Register::Value(value) | Register::Bound(value) => {
                                 let values = [value];
                                 let record = ImmutableRecord::from_values(values, values.len())?;
                                 std::borrow::Cow::Owned(record)
@@ -9938,7 +9949,7 @@ pub fn op_insert(
                 if !state.active_op_state.insert().is_noop_update {
                     let record = match &state.registers[*record_reg] {
                         Register::Record(r) => std::borrow::Cow::Borrowed(r),
-                        Register::Value(value) => {
+                        Register::Value(value) | Register::Bound(value) => {
                             let values = [value];
                             let record = ImmutableRecord::from_values(values, values.len())?;
                             std::borrow::Cow::Owned(record)
@@ -10036,7 +10047,7 @@ pub fn op_insert(
 
                     let record = match &state.registers[*record_reg] {
                         Register::Record(r) => std::borrow::Cow::Borrowed(r),
-                        Register::Value(value) => {
+                        Register::Value(value) | Register::Bound(value) => {
                             let values = [value];
                             let record = ImmutableRecord::from_values(values, values.len())?;
                             std::borrow::Cow::Owned(record)
@@ -12325,7 +12336,7 @@ pub fn op_is_null(
     _pager: &Arc<Pager>,
 ) -> Result<InsnFunctionStepResult> {
     load_insn!(IsNull { reg, target_pc }, insn);
-    if matches!(state.registers[*reg], Register::Value(Value::Null)) {
+    if state.registers[*reg].is_null() {
         state.pc = target_pc.as_offset_int();
     } else {
         state.pc += 1;
@@ -13122,7 +13133,7 @@ pub fn op_add_imm(
 
     let current = &state.registers[*register];
     let current_value = match current {
-        Register::Value(val) => val,
+        Register::Value(val) | Register::Bound(val) => val,
         Register::Aggregate(_) => &Value::Null,
         Register::Record(_) => &Value::Null,
     };
@@ -13151,7 +13162,7 @@ pub fn op_variable(
     _pager: &Arc<Pager>,
 ) -> Result<InsnFunctionStepResult> {
     load_insn!(Variable { index, dest }, insn);
-    state.registers[*dest].set_value(state.get_parameter(*index));
+    state.registers[*dest] = Register::Bound(state.get_parameter(*index));
     state.pc += 1;
     Ok(InsnFunctionStepResult::Step)
 }
@@ -14709,10 +14720,16 @@ pub fn op_if_neg(
     load_insn!(IfNeg { reg, target_pc }, insn);
 
     match &state.registers[*reg] {
-        Register::Value(Value::Numeric(Numeric::Integer(i))) if *i < 0 => {
+        Register::Value(Value::Numeric(Numeric::Integer(i)))
+        | Register::Bound(Value::Numeric(Numeric::Integer(i)))
+            if *i < 0 =>
+        {
             state.pc = target_pc.as_offset_int();
         }
-        Register::Value(Value::Numeric(Numeric::Float(f))) if f64::from(*f) < 0.0 => {
+        Register::Value(Value::Numeric(Numeric::Float(f)))
+        | Register::Bound(Value::Numeric(Numeric::Float(f)))
+            if f64::from(*f) < 0.0 =>
+        {
             state.pc = target_pc.as_offset_int();
         }
         Register::Value(Value::Null) => {
@@ -14904,7 +14921,7 @@ pub fn op_hash_build(
     }) {
         Some(op_state) => op_state,
         None => OpHashBuildState {
-            key_values: crate::alloc::Vec::try_with_capacity_ext(data.num_keys)?,
+            key_values: JoinKey::with_capacity(data.num_keys)?,
             key_idx: 0,
             payload_values: crate::alloc::Vec::try_with_capacity_ext(data.num_payload)?,
             payload_idx: 0,
@@ -14929,13 +14946,24 @@ pub fn op_hash_build(
         state.hash_tables.entry(data.hash_table_id)
     {
         let config = HashTableConfig {
-            initial_buckets: 1024,
+            initial_buckets: DEFAULT_BUCKETS,
             mem_budget,
             num_keys: data.num_keys,
             collations: data.collations.try_to_vec()?,
             temp_store,
             track_matched: data.track_matched,
             partition_count: None,
+            mmap_spill_reads: program.connection.get_mmap_spill_reads(),
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: pager.io.generate_random_number() as u64,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
         e.insert(HashTable::new(config, pager.io.clone())?);
     }
@@ -14945,8 +14973,9 @@ pub fn op_hash_build(
         let i = op_state.key_idx;
         let reg = &state.registers[data.key_start_reg + i];
         let value = reg.get_value().clone();
-        // The resume state preallocates space for every key register before the loop starts.
-        op_state.key_values.push(value);
+        // The resume state preallocates space for every key register before the loop starts,
+        // except for a single key, which is only allocated for if it is not an integer.
+        op_state.key_values.push(value)?;
         op_state.key_idx += 1;
     }
 
@@ -14995,7 +15024,7 @@ pub fn op_hash_build(
     if let Some(ht) = state.hash_tables.get_mut(&data.hash_table_id) {
         let rowid = op_state.rowid.expect("rowid set");
         let pending = PendingHashInsert {
-            key_values: std::mem::replace(&mut op_state.key_values, crate::alloc::vec![]),
+            key_values: std::mem::take(&mut op_state.key_values),
             rowid,
             payload_values: std::mem::replace(&mut op_state.payload_values, crate::alloc::vec![]),
         };
@@ -15035,13 +15064,24 @@ pub fn op_hash_distinct(
         state.hash_tables.entry(data.hash_table_id)
     {
         let config = HashTableConfig {
-            initial_buckets: 1024,
+            initial_buckets: DEFAULT_BUCKETS,
             mem_budget,
             num_keys: data.num_keys,
             collations: data.collations.try_to_vec()?,
             temp_store,
             track_matched: false,
             partition_count: None,
+            mmap_spill_reads: program.connection.get_mmap_spill_reads(),
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: pager.io.generate_random_number() as u64,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
         e.insert(HashTable::new(config, pager.io.clone())?);
     }
@@ -15082,7 +15122,7 @@ pub fn op_hash_build_finalize(
     if let Some(ht) = state.hash_tables.get_mut(hash_table_id) {
         // Finalize the build phase, may flush remaining partitions to disk if spilled
         match ht.finalize_build(Some(&mut state.metrics.hash_join))? {
-            crate::types::IOResult::Done(()) => {}
+            crate::types::IOResult::Done(_) => {}
             crate::types::IOResult::IO(io) => {
                 return Ok(InsnFunctionStepResult::IO(io));
             }
@@ -15641,6 +15681,7 @@ pub fn op_hash_grace_advance_partition(
 }
 
 fn apply_affinity_char(target: &mut Register, affinity: Affinity) -> bool {
+    target.unbind();
     if let Register::Value(value) = target {
         if matches!(value, Value::Blob(_)) {
             return true;
@@ -16783,7 +16824,7 @@ mod tests {
         }
 
         match ht.finalize_build(None).unwrap() {
-            IOResult::Done(()) => {}
+            IOResult::Done(_) => {}
             IOResult::IO(_) => panic!("memory IO should complete synchronously"),
         }
         assert!(ht.has_spilled(), "test requires spilled hash table");
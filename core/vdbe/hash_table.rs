@@ -11,13 +11,24 @@ use crate::{
         Arc, RwLock,
     },
     translate::collate::CollationSeq,
-    types::{IOCompletions, IOResult, Value, ValueRef},
-    vdbe::metrics::HashJoinMetrics,
+    types::{AsValueRef, IOCompletions, IOResult, Value, ValueRef},
+    vdbe::{
+        bloom_filter::{BloomFilter, DEFAULT_FALSE_POSITIVE_RATE},
+        metrics::HashJoinMetrics,
+    },
     CompletionError, Numeric, Result,
 };
 use branches::{mark_unlikely, unlikely};
+use rand::Rng;
 use rapidhash::fast::RapidHasher;
-use std::{cell::RefCell, cmp::Ordering, hash::Hasher};
+use smallvec::SmallVec;
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::VecDeque,
+    hash::Hasher,
+    ops::{ControlFlow, Deref},
+};
 use turso_macros::{turso_assert_eq, AtomicEnum};
 
 const DEFAULT_SEED: u64 = 1337;
@@ -30,11 +41,24 @@ pub const DEFAULT_MEM_BUDGET: usize = 32 * 1024;
 /// TODO: make configurable via PRAGMA
 #[cfg(not(debug_assertions))]
 pub const DEFAULT_MEM_BUDGET: usize = 64 * 1024 * 1024;
-const DEFAULT_BUCKETS: usize = 1024;
+/// Initial bucket count. The bucket vector is counted against the memory budget, so
+/// debug builds start small to leave the small debug budget to the entries.
+#[cfg(debug_assertions)]
+pub const DEFAULT_BUCKETS: usize = 64;
+#[cfg(not(debug_assertions))]
+pub const DEFAULT_BUCKETS: usize = 1024;
 /// Minimum number of partitions for grace hash join.
 pub const MIN_PARTITIONS: usize = 16;
 /// Maximum number of partitions for adaptive partitioning.
 pub const MAX_PARTITIONS: usize = 128;
+/// Default number of sub-partitions an oversized spilled partition is split into.
+/// See [HashTableConfig::repartition_fanout].
+pub const DEFAULT_REPARTITION_FANOUT: usize = 8;
+/// Default average number of entries per bucket above which the bucket count doubles.
+pub const DEFAULT_MAX_LOAD_FACTOR: f64 = 4.0;
+/// Maximum number of times a spilled partition is re-partitioned. A partition that
+/// still exceeds the budget at this depth fails the build.
+const MAX_REPARTITION_DEPTH: usize = 3;
 const NULL_HASH: u8 = 0;
 const INT_HASH: u8 = 1;
 const FLOAT_HASH: u8 = 2;
@@ -56,31 +80,34 @@ fn hash_text_nocase(hasher: &mut impl Hasher, text: &str) {
 
 /// Hash function for join keys using rapidhash
 /// Takes collation into account when hashing text values
-fn hash_join_key(key_values: &[ValueRef], collations: &[CollationSeq]) -> u64 {
-    let mut hasher = RapidHasher::new(DEFAULT_SEED);
+/// `seed` is the table's [HashTableConfig::seed], so build and probe of one table agree.
+fn hash_join_key(key_values: &[ValueRef], collations: &[CollationSeq], seed: u64) -> u64 {
+    hash_key_values(key_values, collations, seed)
+}
+
+/// [hash_join_key] over owned values, so probes can hash their keys without first
+/// collecting a `Vec<ValueRef>`.
+fn hash_join_key_owned(key_values: &[Value], collations: &[CollationSeq], seed: u64) -> u64 {
+    hash_key_values(key_values, collations, seed)
+}
+
+#[inline]
+fn hash_key_values(key_values: &[impl AsValueRef], collations: &[CollationSeq], seed: u64) -> u64 {
+    let mut hasher = RapidHasher::new(seed);
 
     for (idx, value) in key_values.iter().enumerate() {
-        match value {
+        // Numerically equal integers and REALs share a canonical form, so e.g. 10 and 10.0 hash the same.
+        match canonicalize_numeric(value.as_value_ref()) {
             ValueRef::Null => {
                 hasher.write_u8(NULL_HASH);
             }
             ValueRef::Numeric(Numeric::Integer(i)) => {
-                // Hash integers in the same bucket as numerically equivalent REALs so e.g. 10 and 10.0 have the same hash.
-                let f = *i as f64;
-                if (f as i64) == *i && f.is_finite() {
-                    hasher.write_u8(FLOAT_HASH);
-                    let bits = normalized_f64_bits(f);
-                    hasher.write(&bits.to_le_bytes());
-                } else {
-                    // Fallback to the integer domain when the float representation would lose precision.
-                    hasher.write_u8(INT_HASH);
-                    hasher.write_i64(*i);
-                }
+                hasher.write_u8(INT_HASH);
+                hasher.write_i64(i);
             }
             ValueRef::Numeric(Numeric::Float(f)) => {
                 hasher.write_u8(FLOAT_HASH);
-                let bits = normalized_f64_bits(f64::from(*f));
-                hasher.write(&bits.to_le_bytes());
+                hasher.write(&f64::from(f).to_bits().to_le_bytes());
             }
             ValueRef::Text(text) => {
                 let collation = collations.get(idx).unwrap_or(&CollationSeq::Binary);
@@ -115,14 +142,20 @@ fn hash_join_key(key_values: &[ValueRef], collations: &[CollationSeq]) -> u64 {
     hasher.finish()
 }
 
-/// Normalize signed zero so 0.0 and -0.0 hash the same.
+/// Canonical form of a numeric key value: a REAL holding an integral value within the
+/// i64 range becomes that INTEGER (with -0.0 becoming 0), so that numerically equal
+/// keys hash and compare alike. Other REALs, including integral ones outside the i64
+/// range, stay REAL, and integers too large to be exact as f64 are left untouched.
 #[inline]
-const fn normalized_f64_bits(f: f64) -> u64 {
-    if f == 0.0 {
-        0.0f64.to_bits()
-    } else {
-        f.to_bits()
+fn canonicalize_numeric(value: ValueRef) -> ValueRef {
+    if let ValueRef::Numeric(Numeric::Float(f)) = value {
+        let f = f64::from(f);
+        // -2^63 is exactly i64::MIN, while 2^63 is already past i64::MAX
+        if f.trunc() == f && (-9223372036854775808.0..9223372036854775808.0).contains(&f) {
+            return ValueRef::Numeric(Numeric::Integer(f as i64));
+        }
     }
+    value
 }
 
 /// Check if any of the key values is NULL.
@@ -137,13 +170,22 @@ fn has_null_key_ref(key_values: &[ValueRef]) -> bool {
 }
 
 /// Check if two key value arrays are equal, taking collation into account.
-fn keys_equal(key1: &[Value], key2: &[ValueRef], collations: &[CollationSeq]) -> bool {
+/// With `null_safe` (`IS NOT DISTINCT FROM`) NULL equals NULL, otherwise it equals nothing.
+fn keys_equal(
+    key1: &[Value],
+    key2: &[impl AsValueRef],
+    collations: &[CollationSeq],
+    null_safe: bool,
+) -> bool {
+    if null_safe {
+        return keys_equal_distinct(key1, key2, collations);
+    }
     if key1.len() != key2.len() {
         return false;
     }
     for (idx, (v1, v2)) in key1.iter().zip(key2.iter()).enumerate() {
         let collation = collations.get(idx).copied().unwrap_or(CollationSeq::Binary);
-        if !values_equal(v1.as_ref(), *v2, collation) {
+        if !values_equal(v1.as_ref(), v2.as_value_ref(), collation) {
             return false;
         }
     }
@@ -152,12 +194,16 @@ fn keys_equal(key1: &[Value], key2: &[ValueRef], collations: &[CollationSeq]) ->
 
 /// Check if two values are equal, using the specified collation for text comparison.
 /// NOTE: In SQL, NULL = NULL evaluates to NULL (falsy), so this returns false for NULL comparisons.
+///
+/// Numbers compare exactly, like SQLite's `=`: there is no epsilon, only `-0.0` equals
+/// `0.0` and integral REALs equal the matching INTEGER. NaN cannot reach a join key,
+/// since a NaN [Value] is stored as NULL, so it never matches either.
 fn values_equal(v1: ValueRef, v2: ValueRef, collation: CollationSeq) -> bool {
     match (v1, v2) {
         // NULL = NULL is false in SQL (actually NULL, which is falsy)
         (ValueRef::Null, _) | (_, ValueRef::Null) => false,
-        (ValueRef::Numeric(n1), ValueRef::Numeric(n2)) => {
-            ValueRef::Numeric(n1) == ValueRef::Numeric(n2)
+        (ValueRef::Numeric(_), ValueRef::Numeric(_)) => {
+            canonicalize_numeric(v1) == canonicalize_numeric(v2)
         }
         (ValueRef::Blob(b1), ValueRef::Blob(b2)) => b1 == b2,
         (ValueRef::Text(t1), ValueRef::Text(t2)) => {
@@ -177,13 +223,17 @@ fn values_equal_distinct(v1: ValueRef, v2: ValueRef, collation: CollationSeq) ->
     }
 }
 
-fn keys_equal_distinct(key1: &[Value], key2: &[ValueRef], collations: &[CollationSeq]) -> bool {
+fn keys_equal_distinct(
+    key1: &[Value],
+    key2: &[impl AsValueRef],
+    collations: &[CollationSeq],
+) -> bool {
     if key1.len() != key2.len() {
         return false;
     }
     for (idx, (v1, v2)) in key1.iter().zip(key2.iter()).enumerate() {
         let collation = collations.get(idx).copied().unwrap_or(CollationSeq::Binary);
-        if !values_equal_distinct(v1.as_ref(), *v2, collation) {
+        if !values_equal_distinct(v1.as_ref(), v2.as_value_ref(), collation) {
             return false;
         }
     }
@@ -200,21 +250,189 @@ pub enum HashTableState {
     Closed,
 }
 
+/// Which matches a probe produces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JoinMode {
+    /// Every build entry with equal keys, through [HashTable::probe] and
+    /// [HashTable::next_match].
+    #[default]
+    Inner,
+    /// At most one entry per probe, for `EXISTS`: [HashTable::next_match] returns `None`.
+    Semi,
+    /// Only whether any entry matches, for `NOT EXISTS`, see [HashTable::probe_exists].
+    /// Probes behave as in `Semi` mode.
+    Anti,
+}
+
+/// How a join should be executed given the expected size of its build side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinStrategy {
+    /// The whole build side fits within the memory budget.
+    HashInMemory,
+    /// The build side overflows the budget, but splitting it into at most
+    /// [MAX_PARTITIONS] spilled partitions lets each one be loaded on its own.
+    GraceHash,
+    /// The build side is too large for even a single grace partition to fit,
+    /// so partitions would have to be re-split; sorting both sides is cheaper.
+    SortMerge,
+}
+
+/// Recommend a join strategy before building a hash table, from the estimated number
+/// of build rows and their average size.
+pub fn recommend_strategy(
+    estimated_build_rows: usize,
+    avg_row_bytes: usize,
+    mem_budget: usize,
+) -> JoinStrategy {
+    let build_bytes = estimated_build_rows.saturating_mul(avg_row_bytes.max(1));
+    if build_bytes <= mem_budget {
+        return JoinStrategy::HashInMemory;
+    }
+    // Grace processing loads one partition at a time, so every partition has to fit.
+    if build_bytes.div_ceil(MAX_PARTITIONS) <= mem_budget {
+        JoinStrategy::GraceHash
+    } else {
+        JoinStrategy::SortMerge
+    }
+}
+
 /// A probe entry returned by `grace_next_probe_entry()`.
 /// The VDBE writes these to registers for HashProbe to use.
 #[derive(Debug)]
 pub struct GraceProbeEntry {
-    pub key_values: Vec<Value>,
+    pub key_values: JoinKey,
     pub probe_rowid: i64,
 }
 
+/// Join key values of a [HashEntry]. A single INTEGER key, by far the most common
+/// join key, is stored inline instead of in its own heap allocation.
+#[derive(Debug, Clone)]
+pub enum JoinKey {
+    /// A single key holding an integer value.
+    Integer(Value),
+    /// Any other key: several values, or a single non-integer value.
+    Values(Vec<Value>),
+}
+
+impl JoinKey {
+    /// An empty key with room for `num_keys` values. A single key is not allocated
+    /// for up front, since it is stored inline if it turns out to be an integer.
+    pub fn with_capacity(num_keys: usize) -> Result<Self> {
+        if num_keys == 1 {
+            return Ok(Self::default());
+        }
+        Ok(Self::Values(Vec::try_with_capacity_ext(num_keys)?))
+    }
+
+    /// Builds a key from a single value.
+    fn single(value: Value) -> Result<Self> {
+        if value.as_int().is_some() {
+            return Ok(Self::Integer(value));
+        }
+        let mut values = Vec::try_with_capacity_ext(1)?;
+        values
+            .push_within_capacity(value)
+            .expect("key values vector was preallocated");
+        Ok(Self::Values(values))
+    }
+
+    fn try_from_slice(key_values: &[Value]) -> Result<Self> {
+        match key_values {
+            [value] => Self::single(value.clone()),
+            _ => Ok(Self::Values(key_values.iter().cloned().try_collect()?)),
+        }
+    }
+
+    /// Hash of the key, see [hash_join_key].
+    fn hash(&self, collations: &[CollationSeq], seed: u64) -> Result<u64> {
+        match self {
+            Self::Integer(value) => Ok(hash_join_key(&[value.as_ref()], collations, seed)),
+            Self::Values(values) => Ok(hash_join_key_owned(values, collations, seed)),
+        }
+    }
+
+    const fn is_heap_allocated(&self) -> bool {
+        matches!(self, Self::Values(_))
+    }
+
+    /// Appends a key value.
+    pub fn push(&mut self, value: Value) -> Result<()> {
+        match self {
+            Self::Values(values) if values.capacity() == 0 => *self = Self::single(value)?,
+            Self::Values(values) => {
+                values.try_reserve(1)?;
+                values.push(value);
+            }
+            Self::Integer(_) => {
+                let mut values = Vec::try_with_capacity_ext(2)?;
+                let Self::Integer(first) = std::mem::take(self) else {
+                    unreachable!()
+                };
+                values.push(first);
+                values.push(value);
+                *self = Self::Values(values);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for JoinKey {
+    fn default() -> Self {
+        Self::Values(vec![])
+    }
+}
+
+impl From<Vec<Value>> for JoinKey {
+    fn from(mut values: Vec<Value>) -> Self {
+        if values.len() == 1 && values[0].as_int().is_some() {
+            Self::Integer(values.pop().expect("single key value"))
+        } else {
+            Self::Values(values)
+        }
+    }
+}
+
+impl Deref for JoinKey {
+    type Target = [Value];
+
+    fn deref(&self) -> &[Value] {
+        match self {
+            Self::Integer(value) => std::slice::from_ref(value),
+            Self::Values(values) => values,
+        }
+    }
+}
+
+impl IntoIterator for JoinKey {
+    type Item = Value;
+    type IntoIter =
+        std::iter::Chain<std::option::IntoIter<Value>, <Vec<Value> as IntoIterator>::IntoIter>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            Self::Integer(value) => Some(value).into_iter().chain(vec![]),
+            Self::Values(values) => None.into_iter().chain(values),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a JoinKey {
+    type Item = &'a Value;
+    type IntoIter = std::slice::Iter<'a, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 /// A single entry in a hash table bucket.
 #[derive(Debug, Clone)]
 pub struct HashEntry {
     /// Hash value of the join keys.
     pub hash: u64,
     /// The join key values.
-    pub key_values: Vec<Value>,
+    pub key_values: JoinKey,
     /// The rowid of the row in the build table.
     /// During probe phase, we'll use SeekRowid to fetch the full row
     /// (unless payload_values contains all needed columns).
@@ -226,11 +444,12 @@ pub struct HashEntry {
     pub payload_values: Vec<Value>,
 }
 
+/// A build row that has not been stored in the hash table yet.
 #[derive(Debug)]
-pub(crate) struct PendingHashInsert {
-    pub(crate) key_values: Vec<Value>,
-    pub(crate) rowid: i64,
-    pub(crate) payload_values: Vec<Value>,
+pub struct PendingHashInsert {
+    pub key_values: JoinKey,
+    pub rowid: i64,
+    pub payload_values: Vec<Value>,
 }
 
 #[derive(Debug)]
@@ -243,7 +462,7 @@ pub(crate) enum HashInsertResult {
 }
 
 impl HashEntry {
-    fn new(hash: u64, key_values: Vec<Value>, rowid: i64) -> Self {
+    fn new(hash: u64, key_values: JoinKey, rowid: i64) -> Self {
         Self {
             hash,
             key_values,
@@ -254,7 +473,7 @@ impl HashEntry {
 
     const fn new_with_payload(
         hash: u64,
-        key_values: Vec<Value>,
+        key_values: JoinKey,
         rowid: i64,
         payload_values: Vec<Value>,
     ) -> Self {
@@ -450,14 +669,21 @@ impl HashEntry {
         let (num_keys, varint_len) = read_varint(&buf[offset..])?;
         offset += varint_len;
 
-        let mut key_values = Vec::try_with_capacity_ext(num_keys as usize)?;
-        for _ in 0..num_keys {
+        let key_values = if num_keys == 1 {
             let (value, consumed) = Self::deserialize_value(&buf[offset..])?;
-            key_values
-                .push_within_capacity(value)
-                .expect("key values vector was preallocated");
             offset += consumed;
-        }
+            JoinKey::single(value)?
+        } else {
+            let mut key_values = Vec::try_with_capacity_ext(num_keys as usize)?;
+            for _ in 0..num_keys {
+                let (value, consumed) = Self::deserialize_value(&buf[offset..])?;
+                key_values
+                    .push_within_capacity(value)
+                    .expect("key values vector was preallocated");
+                offset += consumed;
+            }
+            JoinKey::Values(key_values)
+        };
 
         // Read number of payload values and payload values
         let (num_payload, varint_len) = read_varint(&buf[offset..])?;
@@ -584,6 +810,33 @@ impl Partitioning {
     }
 }
 
+/// Routes the hashes of a spilled partition that was re-partitioned to its
+/// sub-partitions. Sub-partitions get logical indices past all existing ones.
+#[derive(Debug, Clone, Copy)]
+struct PartitionSplit {
+    /// Logical index of the partition that was split.
+    parent: usize,
+    /// Number of splits between a top-level partition and the sub-partitions.
+    depth: usize,
+    /// Logical index of the first sub-partition.
+    first_child: usize,
+    partitioning: Partitioning,
+}
+
+impl PartitionSplit {
+    /// Entries of the parent all share the top bits of their hash, so sub-partitions
+    /// are chosen by a hash re-seeded per depth rather than by the next bits.
+    fn child(&self, hash: u64) -> usize {
+        let mut hasher = RapidHasher::new(DEFAULT_SEED.wrapping_add(self.depth as u64));
+        hasher.write_u64(hash);
+        self.first_child + self.partitioning.index(hasher.finish())
+    }
+
+    fn contains(&self, partition_idx: usize) -> bool {
+        (self.first_child..self.first_child + self.partitioning.count).contains(&partition_idx)
+    }
+}
+
 /// A bucket in the hash table. Uses chaining for collision resolution.
 #[derive(Debug, Clone)]
 pub struct HashBucket {
@@ -732,6 +985,88 @@ impl SpilledPartition {
     fn current_chunk(&self) -> Option<&SpillChunk> {
         self.chunks.get(self.current_chunk_idx)
     }
+
+    /// Deserialize the entries in `data` into buckets. A trailing entry cut off by the
+    /// end of `data` is kept in `partial_entry` to be completed by the next chunk.
+    fn parse_entries(&mut self, data: &[u8]) -> Result<()> {
+        let (buckets, parsed_entries) = (&mut self.buckets, &mut self.parsed_entries);
+        parse_spilled_entries(data, &mut self.partial_entry, |entry| {
+            let bucket_idx = (entry.hash as usize) % buckets.len();
+            buckets[bucket_idx].insert(entry)?;
+            *parsed_entries += 1;
+            Ok(())
+        })
+    }
+
+    /// Advance past the chunk that was just parsed, finishing the load after the last one.
+    fn finish_chunk(&mut self, track_matched: bool) -> Result<ParseChunkResult> {
+        self.current_chunk_idx += 1;
+
+        if self.has_more_chunks() {
+            return Ok(ParseChunkResult::MoreChunks);
+        }
+        if unlikely(!self.partial_entry.is_empty()) {
+            return Err(LimboError::Corrupt("HashEntry: truncated entry".into()));
+        }
+        let total_num_entries = self.total_num_entries();
+        turso_assert!(
+            self.parsed_entries == total_num_entries,
+            "parsed entry count mismatch"
+        );
+        if track_matched && self.matched_bits.is_empty() {
+            // Only initialize matched_bits on the first load. On subsequent
+            // reloads (after eviction), the existing bits are preserved so that
+            // probe marks set during earlier passes are not lost.
+            self.matched_bits = self
+                .buckets
+                .iter()
+                .map(|b| vec![false; b.entries.len()])
+                .try_collect()?;
+        }
+        self.state = PartitionState::Loaded;
+        self.resident_mem = HashTable::partition_bucket_mem(&self.buckets);
+        // Release staging buffer to free memory now that buckets are built.
+        self.buffer_len.store(0, atomic::Ordering::SeqCst);
+        self.read_buffer.write().clear();
+        Ok(ParseChunkResult::Done {
+            resident_mem: self.resident_mem,
+        })
+    }
+}
+
+/// Deserialize the entries in `data`, passing each to `on_entry`. A trailing entry cut
+/// off by the end of `data` is appended to `partial_entry`.
+fn parse_spilled_entries(
+    data: &[u8],
+    partial_entry: &mut Vec<u8>,
+    mut on_entry: impl FnMut(HashEntry) -> Result<()>,
+) -> Result<()> {
+    let mut offset = 0;
+    while offset < data.len() {
+        let Some((entry_len, varint_size)) = read_varint_partial(&data[offset..])? else {
+            partial_entry.try_reserve(data.len() - offset)?;
+            partial_entry.extend_from_slice(&data[offset..]);
+            break;
+        };
+
+        let total_needed = varint_size + entry_len as usize;
+        if offset + total_needed > data.len() {
+            partial_entry.try_reserve(data.len() - offset)?;
+            partial_entry.extend_from_slice(&data[offset..]);
+            break;
+        }
+
+        let start = offset + varint_size;
+        let end = start + entry_len as usize;
+        let (entry, consumed) = HashEntry::deserialize(&data[start..end])?;
+        turso_assert!(
+            consumed == entry_len as usize,
+            "expected to consume entire entry"
+        );
+        on_entry(entry)?;
+        offset += total_needed;
+    }
+    Ok(())
 }
 
 /// In-memory partition buffer for grace hash join.
@@ -767,6 +1102,79 @@ impl PartitionBuffer {
     }
 }
 
+/// Length of [HashTableStats::chain_length_histogram]. The last slot counts every
+/// bucket with at least `CHAIN_LENGTH_HISTOGRAM_LEN - 1` entries.
+pub const CHAIN_LENGTH_HISTOGRAM_LEN: usize = 16;
+
+/// Entry counts and memory usage of a [HashTable], see [HashTable::stats].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HashTableStats {
+    /// Entries in the table, whether held in memory or spilled to disk.
+    pub num_entries: usize,
+    /// Entries written to spilled partitions on disk, included in `num_entries`.
+    pub spilled_entries: usize,
+    /// Logical size of the entries held in memory, including loaded partitions.
+    pub mem_used: usize,
+    /// `mem_used` plus the capacity of the vectors holding in-memory buckets and
+    /// their entries. This is what the memory budget is checked against.
+    pub allocated_mem: usize,
+    /// Estimated false positive rate of the Bloom filter over build keys, `None` when
+    /// the table has no filter. Rises as the build outgrows `expected_entries`.
+    pub bloom_filter_false_positive_rate: Option<f64>,
+    /// Number of in-memory buckets by chain length: slot `i` counts the buckets with
+    /// `i` entries, and the last slot those with `CHAIN_LENGTH_HISTOGRAM_LEN - 1` or
+    /// more. A few buckets far out in the tail point at a skewed join key.
+    pub chain_length_histogram: Vec<usize>,
+}
+
+impl std::fmt::Display for HashTableStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "entries: {} ({} spilled)",
+            self.num_entries, self.spilled_entries
+        )?;
+        writeln!(
+            f,
+            "memory: {} used, {} allocated",
+            self.mem_used, self.allocated_mem
+        )?;
+        if let Some(rate) = self.bloom_filter_false_positive_rate {
+            writeln!(f, "bloom filter false positive rate: {rate:.4}")?;
+        }
+        writeln!(f, "chain length | buckets")?;
+        let overflow = self.chain_length_histogram.len().saturating_sub(1);
+        for (len, &buckets) in self.chain_length_histogram.iter().enumerate() {
+            // Empty rows would mostly be noise in logs.
+            if buckets == 0 {
+                continue;
+            }
+            let len = if len == overflow {
+                format!("{len}+")
+            } else {
+                len.to_string()
+            };
+            writeln!(f, "{len:>12} | {buckets}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Summary of a finished build, returned by [HashTable::finalize_build] so callers
+/// don't need a second pass over the table to size a join.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BuildSummary {
+    /// Entries in the table, as in [HashTableStats::num_entries].
+    pub num_entries: usize,
+    /// Distinct key hashes among the entries held in memory, plus every spilled entry
+    /// since spilled keys aren't read back. Hash collisions can only lower the count.
+    pub estimated_distinct_keys: usize,
+    /// Number of entries in the longest bucket held in memory.
+    pub max_chain_length: usize,
+    /// Memory used, as in [HashTableStats::mem_used].
+    pub mem_used: usize,
+}
+
 /// Configuration for the hash table.
 #[derive(Debug, Clone)]
 pub struct HashTableConfig {
@@ -782,8 +1190,50 @@ pub struct HashTableConfig {
     pub temp_store: crate::TempStore,
     /// Whether to track which entries have been matched during probing (for FULL OUTER JOIN).
     pub track_matched: bool,
-    /// Optional override for the number of partitions (must be power of two).
+    /// Optional override for the number of partitions (must be power of two) the
+    /// table is split into when it first spills.
     pub partition_count: Option<usize>,
+    /// Number of sub-partitions (power of two) a spilled partition is split into when
+    /// it would not fit `mem_budget` once loaded back. Unlike `partition_count`, which
+    /// sets how many partitions the whole table is split into on the first spill, this
+    /// applies to each oversized partition on its own, at every re-partition level.
+    /// 1 disables re-partitioning.
+    pub repartition_fanout: usize,
+    /// Average number of entries per bucket above which the in-memory bucket count is
+    /// doubled. `f64::INFINITY` keeps `initial_buckets` for the lifetime of the table.
+    pub max_load_factor: f64,
+    /// Parse spilled partitions straight out of a read-only mapping of the spill file
+    /// instead of `pread`-ing each chunk into a staging buffer. Falls back to `pread`
+    /// when the IO backend cannot map files.
+    pub mmap_spill_reads: bool,
+    /// Keep a Bloom filter over the hashes of build keys so probes for keys that were
+    /// never inserted return without touching a bucket or spilled partition.
+    pub enable_bloom_filter: bool,
+    /// Expected number of build entries, used to size the Bloom filter. `None` uses
+    /// the filter's default size.
+    pub expected_entries: Option<usize>,
+    /// Which matches probes produce.
+    pub join_mode: JoinMode,
+    /// Match NULL keys with NULL, for `IS NOT DISTINCT FROM` joins. When false, rows
+    /// with a NULL in any key column are never stored or matched, as `NULL = NULL`
+    /// is not true in SQL.
+    pub null_safe: bool,
+    /// Seed of the join key hash. Defaults to a random value so crafted keys cannot
+    /// be made to collide into one bucket and degrade probes to a linear scan.
+    pub seed: u64,
+    /// Collect join inserts in a flat buffer without hashing them, and hash them all
+    /// into buckets sized for the final count in [HashTable::finalize_build], so the
+    /// buckets are never doubled while building. Falls back to hashing on insert once
+    /// the table would spill.
+    pub deferred_index: bool,
+    /// Stop [HashTable::next_match] after this many matches of the current probe keys,
+    /// counting the one returned by [HashTable::probe], for joins that only need the
+    /// first few matches of a key. `None` returns every match.
+    pub max_matches_per_key: Option<usize>,
+    /// Release the slack capacity the bucket vectors grew into while building, in
+    /// [HashTable::finalize_build], so it is not held for the whole probe phase. Costs a
+    /// reallocation per bucket, which latency-sensitive callers can opt out of.
+    pub shrink_on_finalize: bool,
 }
 
 impl Default for HashTableConfig {
@@ -796,6 +1246,17 @@ impl Default for HashTableConfig {
             temp_store: crate::TempStore::Default,
             track_matched: false,
             partition_count: None,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: rand::rng().random(),
+            mmap_spill_reads: false,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         }
     }
 }
@@ -812,6 +1273,23 @@ struct SpillState {
     temp_file: TempFile,
     /// Partitioning strategy for this spill.
     partitioning: Partitioning,
+    /// Partitions that were re-partitioned because they exceeded the memory budget.
+    splits: Vec<PartitionSplit>,
+    /// Partition currently being re-partitioned, see [Repartition].
+    repartition: Option<Repartition>,
+}
+
+/// An oversized spilled partition being re-partitioned. Its chunks are read back one
+/// at a time and their entries routed to the sub-partitions of `split`, so the
+/// partition is never held in memory as a whole.
+struct Repartition {
+    /// The partition being split, no longer listed in [SpillState::partitions].
+    parent: SpilledPartition,
+    split: PartitionSplit,
+    /// Hash of the first entry read back.
+    first_hash: Option<u64>,
+    /// Whether every entry read back so far has `first_hash`.
+    single_hash: bool,
 }
 
 impl SpillState {
@@ -828,6 +1306,8 @@ impl SpillState {
             next_spill_offset: 0,
             temp_file: TempFile::with_temp_store(io, temp_store)?,
             partitioning,
+            splits: vec![],
+            repartition: None,
         })
     }
 
@@ -842,6 +1322,23 @@ impl SpillState {
             .iter()
             .find(|p| p.partition_idx == logical_idx)
     }
+
+    /// Logical index of the partition holding entries with this hash, following splits.
+    fn partition_for_hash(&self, hash: u64) -> usize {
+        let mut partition_idx = self.partitioning.index(hash);
+        while let Some(split) = self.splits.iter().find(|s| s.parent == partition_idx) {
+            partition_idx = split.child(hash);
+        }
+        partition_idx
+    }
+
+    /// How many times the entries of a partition have been re-partitioned.
+    fn split_depth(&self, partition_idx: usize) -> usize {
+        self.splits
+            .iter()
+            .find(|s| s.contains(partition_idx))
+            .map_or(0, |s| s.depth)
+    }
 }
 
 /// Probe-side buffering/spilling state for grace hash join.
@@ -856,6 +1353,7 @@ struct ProbeSpillState {
     /// Separate temp file for probe-side spills.
     temp_file: TempFile,
     /// Same partitioning as build side, so partition indices correspond.
+    /// Buffers also cover the build side's re-partitioned sub-partitions.
     partitioning: Partitioning,
     /// Current memory used by probe buffers.
     mem_used: usize,
@@ -868,10 +1366,11 @@ impl ProbeSpillState {
         io: &Arc<dyn IO>,
         temp_store: crate::TempStore,
         partitioning: Partitioning,
+        num_partitions: usize,
         mem_budget: usize,
     ) -> Result<Self> {
         Ok(Self {
-            partition_buffers: (0..partitioning.count)
+            partition_buffers: (0..num_partitions)
                 .map(|_| PartitionBuffer::new())
                 .try_collect()?,
             partitions: vec![],
@@ -953,6 +1452,9 @@ pub struct HashTable {
     num_entries: usize,
     /// Current memory usage in bytes.
     mem_used: usize,
+    /// Bytes allocated for `buckets` and the entry vectors of its buckets, on top of
+    /// the entry sizes counted by `mem_used`.
+    bucket_alloc_bytes: usize,
     /// Memory budget in bytes.
     mem_budget: usize,
     /// Number of join keys.
@@ -999,6 +1501,31 @@ pub struct HashTable {
     probe_spill_state: Option<ProbeSpillState>,
     /// Grace processing state machine.
     grace_state: Option<GraceState>,
+    /// Load spilled partitions via `File::map_read`, cleared if the file can't be mapped.
+    mmap_spill_reads: bool,
+    /// Fan-out used when re-partitioning an oversized spilled partition.
+    repartition_fanout: usize,
+    /// Average bucket chain length above which the buckets are doubled.
+    max_load_factor: f64,
+    /// Hashes of inserted join keys, consulted before probing when enabled.
+    bloom_filter: Option<BloomFilter>,
+    /// Which matches probes produce.
+    join_mode: JoinMode,
+    /// Whether NULL keys match NULL.
+    null_safe: bool,
+    /// Seed of the join key hash.
+    seed: u64,
+    /// Collect join inserts in `deferred` until the build is finalized.
+    deferred_index: bool,
+    /// Rows inserted by a `deferred_index` build, not yet hashed into `buckets`.
+    /// Counted in `num_entries` and `mem_used`.
+    deferred: Vec<PendingHashInsert>,
+    /// Matches the probe cursor returns per probe key, if capped.
+    max_matches_per_key: Option<usize>,
+    /// Matches returned so far for the current probe keys.
+    probe_matches: usize,
+    /// Shrink the bucket vectors to fit when the build is finalized.
+    shrink_on_finalize: bool,
 }
 
 crate::assert::assert_send!(HashTable);
@@ -1017,11 +1544,16 @@ enum SpillAction {
         buffer_len: Arc<AtomicUsize>,
         read_buffer_ref: Arc<RwLock<Vec<u8>>>,
     },
+    MapChunk {
+        read_size: usize,
+        file_offset: u64,
+    },
     Restart,
     NotFound,
 }
 
-enum GraceProbeChunkAction {
+/// Next step when reading a spilled partition back one chunk at a time.
+enum ChunkReadAction {
     WaitingForIO,
     ParseChunk {
         partition_idx: usize,
@@ -1056,6 +1588,18 @@ impl HashTable {
                 "custom collations are not supported by hash tables".to_string(),
             ));
         }
+        turso_assert!(
+            config.repartition_fanout.is_power_of_two(),
+            "repartition fanout must be a power of two"
+        );
+        turso_assert!(
+            config.max_load_factor > 0.0,
+            "max load factor must be positive"
+        );
+        turso_assert!(
+            config.max_matches_per_key != Some(0),
+            "max matches per key must be positive"
+        );
         let num_buckets = config.initial_buckets;
         let buckets = (0..num_buckets).map(|_| HashBucket::new()).try_collect()?;
         let matched_bits = if config.track_matched {
@@ -1063,11 +1607,23 @@ impl HashTable {
         } else {
             vec![]
         };
+        let bloom_filter = config
+            .enable_bloom_filter
+            .then(|| match config.expected_entries {
+                Some(expected) => BloomFilter::with_capacity(
+                    u32::try_from(expected).unwrap_or(u32::MAX).max(1),
+                    DEFAULT_FALSE_POSITIVE_RATE,
+                ),
+                None => BloomFilter::new(),
+            });
+        // New buckets have no entries allocated yet.
+        let bucket_alloc_bytes = buckets.capacity() * std::mem::size_of::<HashBucket>();
         Ok(Self {
             initial_buckets: config.initial_buckets,
             buckets,
             num_entries: 0,
             mem_used: 0,
+            bucket_alloc_bytes,
             mem_budget: config.mem_budget,
             num_keys: config.num_keys,
             collations: config.collations,
@@ -1091,9 +1647,44 @@ impl HashTable {
             partition_count_override: config.partition_count,
             probe_spill_state: None,
             grace_state: None,
+            mmap_spill_reads: config.mmap_spill_reads,
+            repartition_fanout: config.repartition_fanout,
+            max_load_factor: config.max_load_factor,
+            bloom_filter,
+            join_mode: config.join_mode,
+            null_safe: config.null_safe,
+            seed: config.seed,
+            deferred_index: config.deferred_index,
+            deferred: vec![],
+            max_matches_per_key: config.max_matches_per_key,
+            probe_matches: 0,
+            shrink_on_finalize: config.shrink_on_finalize,
         })
     }
 
+    /// Create a hash table whose buckets are sized up front for `estimated` build rows,
+    /// e.g. from the cardinality of the index scan feeding the build. `initial_buckets`
+    /// is replaced by `estimated / max_load_factor` rounded up to a power of two, so a
+    /// build of up to `estimated` rows never rehashes. Overestimating wastes memory on
+    /// empty buckets, underestimating falls back to doubling the buckets as they fill.
+    pub fn with_estimated_rows(
+        config: HashTableConfig,
+        io: Arc<dyn IO>,
+        estimated: usize,
+    ) -> Result<Self> {
+        let config = if config.max_load_factor.is_finite() {
+            let buckets = (estimated as f64 / config.max_load_factor).ceil() as usize;
+            HashTableConfig {
+                initial_buckets: buckets.max(1).next_power_of_two(),
+                ..config
+            }
+        } else {
+            // The buckets never grow, so any count avoids rehashing.
+            config
+        };
+        Self::new(config, io)
+    }
+
     /// Get the current state of the hash table.
     pub fn get_state(&self) -> &HashTableState {
         &self.state
@@ -1127,7 +1718,7 @@ impl HashTable {
     /// SAFETY: only call this when spill_state is Some.
     fn partition_index(&self, hash: u64) -> usize {
         let spill_state = self.spill_state.as_ref().expect("spill state must exist");
-        spill_state.partitioning.index(hash)
+        spill_state.partition_for_hash(hash)
     }
 
     fn record_probe_call(&mut self, metrics: Option<&mut HashJoinMetrics>) {
@@ -1140,6 +1731,8 @@ impl HashTable {
     /// When memory budget is exceeded, triggers grace hash join by partitioning and spilling.
     /// Rows with NULL join keys are skipped since NULL != NULL in SQL.
     /// (This is specific to hash join semantics, not DISTINCT.)
+    /// Goes through the same per-row path as [HashTable::insert_batch] without
+    /// allocating a batch for the row.
     pub fn insert(
         &mut self,
         key_values: Vec<Value>,
@@ -1148,12 +1741,13 @@ impl HashTable {
         metrics: Option<&mut HashJoinMetrics>,
     ) -> Result<IOResult<()>> {
         let pending = PendingHashInsert {
-            key_values,
+            key_values: key_values.into(),
             rowid,
             payload_values,
         };
         match self.insert_pending(pending, metrics)? {
             HashInsertResult::Done => Ok(IOResult::Done(())),
+            // The caller retries with the same row once the I/O completes.
             HashInsertResult::IO { io, .. } => Ok(IOResult::IO(io)),
         }
     }
@@ -1161,7 +1755,7 @@ impl HashTable {
     pub(crate) fn insert_pending(
         &mut self,
         pending: PendingHashInsert,
-        metrics: Option<&mut HashJoinMetrics>,
+        mut metrics: Option<&mut HashJoinMetrics>,
     ) -> Result<HashInsertResult> {
         turso_assert!(
             matches!(
@@ -1175,25 +1769,39 @@ impl HashTable {
         // Skip rows with NULL join keys - they can never match anything since NULL != NULL in SQL.
         // However, when track_matched is enabled (outer joins), we must keep NULL-key entries
         // so they appear as unmatched in the unmatched scan.
-        if has_null_key(&pending.key_values) && !self.track_matched {
+        if has_null_key(&pending.key_values) && !self.track_matched && !self.null_safe {
             return Ok(HashInsertResult::Done);
         }
 
-        // Compute hash of the join keys using collations
-        let key_refs: Vec<ValueRef> = pending
-            .key_values
-            .iter()
-            .map(|value| value.as_ref())
-            .try_collect()?;
-        let hash = hash_join_key(&key_refs, &self.collations);
         let entry_size = HashEntry::size_from_values(&pending.key_values, &pending.payload_values);
+        if self.deferred_index && self.spill_state.is_none() {
+            let buffer_growth = if self.deferred.len() == self.deferred.capacity() {
+                self.deferred.capacity().max(4) * std::mem::size_of::<PendingHashInsert>()
+            } else {
+                0
+            };
+            if self.allocated_mem() + buffer_growth + entry_size <= self.mem_budget {
+                if let Some(metrics) = metrics {
+                    metrics.build_heap_keys += pending.key_values.is_heap_allocated() as u64;
+                }
+                self.deferred.try_push(pending)?;
+                self.num_entries += 1;
+                self.mem_used += entry_size;
+                return Ok(HashInsertResult::Done);
+            }
+            // The buffered rows must be in buckets before they can be partitioned.
+            self.index_deferred()?;
+        }
+
+        // Compute hash of the join keys using collations
+        let hash = pending.key_values.hash(&self.collations, self.seed)?;
 
         // Check if we would exceed memory budget
-        if self.mem_used + entry_size > self.mem_budget {
+        if self.allocated_mem() + entry_size > self.mem_budget {
             if self.spill_state.is_none() {
                 tracing::debug!(
                     "Hash table memory budget exceeded (used: {}, budget: {}), spilling to disk",
-                    self.mem_used,
+                    self.allocated_mem(),
                     self.mem_budget
                 );
                 // First time exceeding budget, trigger spill
@@ -1206,7 +1814,7 @@ impl HashTable {
             };
 
             // Spill whole partitions until the new entry fits
-            if let Some(c) = self.spill_partitions_for_entry(entry_size, metrics)? {
+            if let Some(c) = self.spill_partitions_for_entry(entry_size, metrics.as_deref_mut())? {
                 // I/O pending, caller will re-enter after completion and retry the insert.
                 if !c.finished() {
                     return Ok(HashInsertResult::IO {
@@ -1217,6 +1825,22 @@ impl HashTable {
             }
         }
 
+        self.insert_hashed(hash, entry_size, pending, metrics)?;
+        Ok(HashInsertResult::Done)
+    }
+
+    /// Store a row whose budget check has passed: into its bucket while the table is in
+    /// memory, or into its partition buffer once it has spilled.
+    fn insert_hashed(
+        &mut self,
+        hash: u64,
+        entry_size: usize,
+        pending: PendingHashInsert,
+        metrics: Option<&mut HashJoinMetrics>,
+    ) -> Result<()> {
+        if let Some(metrics) = metrics {
+            metrics.build_heap_keys += pending.key_values.is_heap_allocated() as u64;
+        }
         let PendingHashInsert {
             key_values,
             rowid,
@@ -1238,41 +1862,244 @@ impl HashTable {
             spill_state.partition_buffers[partition_idx].insert(entry)?;
         } else {
             // Normal mode, insert into hash bucket
+            self.grow_buckets_if_needed()?;
             let bucket_idx = (hash as usize) % self.buckets.len();
-            if self.buckets[bucket_idx].entries.is_empty() {
-                self.non_empty_buckets.try_push(bucket_idx)?;
-            }
-            self.buckets[bucket_idx].insert(entry)?;
-            if self.track_matched {
-                self.matched_bits[bucket_idx].try_push(false)?;
-            }
+            self.insert_into_bucket(bucket_idx, entry)?;
         }
 
+        if let Some(bloom_filter) = self.bloom_filter.as_mut() {
+            bloom_filter.insert_i64(hash as i64);
+        }
         self.num_entries += 1;
         self.mem_used += entry_size;
+        Ok(())
+    }
 
-        Ok(HashInsertResult::Done)
+    fn insert_into_bucket(&mut self, bucket_idx: usize, entry: HashEntry) -> Result<()> {
+        if self.buckets[bucket_idx].entries.is_empty() {
+            self.non_empty_buckets.try_push(bucket_idx)?;
+        }
+        let capacity = self.buckets[bucket_idx].entries.capacity();
+        self.buckets[bucket_idx].insert(entry)?;
+        self.bucket_alloc_bytes += (self.buckets[bucket_idx].entries.capacity() - capacity)
+            * std::mem::size_of::<HashEntry>();
+        if self.track_matched {
+            self.matched_bits[bucket_idx].try_push(false)?;
+        }
+        Ok(())
     }
 
-    /// Insert keys into the hash table if not already present.
-    /// Returns true if inserted, false if duplicate found.
-    /// Unlike hash join inserts, DISTINCT keeps NULLs and treats NULL==NULL.
-    pub fn insert_distinct(
+    /// Double the bucket count once the average chain is longer than `max_load_factor`,
+    /// so that a build larger than `initial_buckets` anticipated keeps short chains.
+    /// Entries are moved by their cached hash, keys are not hashed again.
+    fn grow_buckets_if_needed(&mut self) -> Result<()> {
+        if (self.num_entries as f64) <= self.buckets.len() as f64 * self.max_load_factor {
+            return Ok(());
+        }
+        self.rehash_buckets(self.buckets.len() * 2)
+    }
+
+    /// Grow the buckets up front so that `additional` more entries stay within
+    /// `max_load_factor`, rehashing once instead of doubling repeatedly while they are
+    /// inserted.
+    fn reserve_buckets(&mut self, additional: usize) -> Result<()> {
+        let bucket_count = self.bucket_count_for(self.num_entries + additional);
+        if bucket_count != self.buckets.len() {
+            self.rehash_buckets(bucket_count)?;
+        }
+        Ok(())
+    }
+
+    /// Bucket count that [Self::reserve_buckets] grows to for `entries` entries.
+    fn bucket_count_for(&self, entries: usize) -> usize {
+        let mut bucket_count = self.buckets.len().max(1);
+        while entries as f64 > bucket_count as f64 * self.max_load_factor {
+            bucket_count *= 2;
+        }
+        bucket_count
+    }
+
+    /// Estimate of [Self::allocated_mem] once `rows` more entries taking `size` bytes
+    /// are inserted in memory, including the bucket vector growing to fit them. Entry
+    /// vectors are assumed to end up at most twice as long as needed.
+    fn projected_allocated_mem(&self, rows: usize, size: usize) -> usize {
+        let entries = self.num_entries + rows;
+        let bucket_count = self.bucket_count_for(entries);
+        let bucket_mem = if bucket_count != self.buckets.len() {
+            // Rehashing rebuilds every entry vector.
+            bucket_count * std::mem::size_of::<HashBucket>()
+                + 2 * entries * std::mem::size_of::<HashEntry>()
+        } else {
+            self.bucket_alloc_bytes + 2 * rows * std::mem::size_of::<HashEntry>()
+        };
+        self.mem_used
+            + size
+            + bucket_mem
+            + self.deferred.capacity() * std::mem::size_of::<PendingHashInsert>()
+    }
+
+    /// Move every entry into `bucket_count` new buckets.
+    fn rehash_buckets(&mut self, bucket_count: usize) -> Result<()> {
+        let mut buckets: Vec<HashBucket> =
+            (0..bucket_count).map(|_| HashBucket::new()).try_collect()?;
+        for bucket in std::mem::take(&mut self.buckets) {
+            for entry in bucket.entries {
+                buckets[(entry.hash as usize) % bucket_count].insert(entry)?;
+            }
+        }
+        self.non_empty_buckets.clear();
+        for (idx, bucket) in buckets.iter().enumerate() {
+            if !bucket.entries.is_empty() {
+                self.non_empty_buckets.try_push(idx)?;
+            }
+        }
+        if self.track_matched {
+            // Nothing is matched before probing starts.
+            self.matched_bits = buckets
+                .iter()
+                .map(|b| vec![false; b.entries.len()])
+                .try_collect()?;
+        }
+        self.buckets = buckets;
+        self.update_bucket_alloc_bytes();
+        Ok(())
+    }
+
+    /// Recompute `bucket_alloc_bytes` after `buckets` was replaced or emptied.
+    fn update_bucket_alloc_bytes(&mut self) {
+        let entries: usize = self.buckets.iter().map(|b| b.entries.capacity()).sum();
+        self.bucket_alloc_bytes = self.buckets.capacity() * std::mem::size_of::<HashBucket>()
+            + entries * std::mem::size_of::<HashEntry>();
+    }
+
+    /// Memory counted against the budget while building: the entries in memory and
+    /// the bucket and deferred vectors holding them.
+    fn allocated_mem(&self) -> usize {
+        self.mem_used
+            + self.bucket_alloc_bytes
+            + self.deferred.capacity() * std::mem::size_of::<PendingHashInsert>()
+    }
+
+    /// Hash the rows collected by a `deferred_index` build into buckets, sizing the
+    /// buckets for all of them first so none is moved twice.
+    fn index_deferred(&mut self) -> Result<()> {
+        if self.deferred.is_empty() {
+            return Ok(());
+        }
+        // The deferred rows are already counted in `num_entries`.
+        self.reserve_buckets(0)?;
+        for pending in std::mem::take(&mut self.deferred) {
+            let hash = pending.key_values.hash(&self.collations, self.seed)?;
+            let PendingHashInsert {
+                key_values,
+                rowid,
+                payload_values,
+            } = pending;
+            let entry = if payload_values.is_empty() {
+                HashEntry::new(hash, key_values, rowid)
+            } else {
+                HashEntry::new_with_payload(hash, key_values, rowid, payload_values)
+            };
+            let bucket_idx = (hash as usize) % self.buckets.len();
+            self.insert_into_bucket(bucket_idx, entry)?;
+            if let Some(bloom_filter) = self.bloom_filter.as_mut() {
+                bloom_filter.insert_i64(hash as i64);
+            }
+        }
+        Ok(())
+    }
+
+    /// Insert a batch of rows, consuming them from the front of `batch`.
+    ///
+    /// While the table is in memory, the whole batch is hashed up front and the memory
+    /// budget is checked once, including the bucket and entry vectors growing to fit the
+    /// batch: if the batch fits, the buckets are grown once for the whole batch and
+    /// every row goes straight into its bucket. A batch that would
+    /// cross the budget falls back to inserting row by row through the spilling path,
+    /// as does a `deferred_index` build, which buffers rows instead of hashing them.
+    /// If a spill has to wait for I/O, the rows inserted so
+    /// far have been removed from `batch`, and the caller retries with the remainder
+    /// once the I/O completes.
+    ///
+    /// Rows are [PendingHashInsert]s rather than key values paired with an
+    /// `ImmutableRecord`: entries keep the rowid and payload as values, so a record
+    /// would only be decoded again here. The batch is a deque so that a batch cut
+    /// short by I/O resumes at its first row not yet inserted.
+    pub fn insert_batch(
         &mut self,
-        key_values: &[Value],
-        key_refs: &[ValueRef],
+        batch: &mut VecDeque<PendingHashInsert>,
         mut metrics: Option<&mut HashJoinMetrics>,
-    ) -> Result<IOResult<bool>> {
+    ) -> Result<IOResult<()>> {
         turso_assert!(
-            self.state == HashTableState::Building || self.state == HashTableState::Spilled,
-            "Cannot insert_distinct into hash table in unexpected state",
+            matches!(
+                self.state,
+                HashTableState::Building | HashTableState::Spilled
+            ),
+            "Cannot insert into hash table in unexpected state",
             { "state": format!("{:?}", self.state) }
         );
 
-        let hash = hash_join_key(key_refs, &self.collations);
+        if self.spill_state.is_none() && !self.deferred_index {
+            // (hash, entry size) per row, None for rows skipped because of NULL keys
+            let mut hashed: Vec<Option<(u64, usize)>> = Vec::try_with_capacity_ext(batch.len())?;
+            let mut batch_size = 0usize;
+            for pending in batch.iter() {
+                if has_null_key(&pending.key_values) && !self.track_matched && !self.null_safe {
+                    hashed.try_push(None)?;
+                    continue;
+                }
+                let hash = pending.key_values.hash(&self.collations, self.seed)?;
+                let entry_size =
+                    HashEntry::size_from_values(&pending.key_values, &pending.payload_values);
+                batch_size += entry_size;
+                hashed.try_push(Some((hash, entry_size)))?;
+            }
 
-        if self.spill_state.is_some() {
-            let partition_idx = self.partition_index(hash);
+            let rows = hashed.iter().flatten().count();
+            if self.projected_allocated_mem(rows, batch_size) <= self.mem_budget {
+                self.reserve_buckets(rows)?;
+                for (pending, hashed) in batch.drain(..).zip(hashed) {
+                    let Some((hash, entry_size)) = hashed else {
+                        continue;
+                    };
+                    // The buckets were reserved for the batch, so none of these grow them.
+                    self.insert_hashed(hash, entry_size, pending, metrics.as_deref_mut())?;
+                }
+                return Ok(IOResult::Done(()));
+            }
+        }
+
+        while let Some(pending) = batch.pop_front() {
+            match self.insert_pending(pending, metrics.as_deref_mut())? {
+                HashInsertResult::Done => {}
+                HashInsertResult::IO { io, pending } => {
+                    batch.try_push_front(pending)?;
+                    return Ok(IOResult::IO(io));
+                }
+            }
+        }
+        Ok(IOResult::Done(()))
+    }
+
+    /// Insert keys into the hash table if not already present.
+    /// Returns true if inserted, false if duplicate found.
+    /// Unlike hash join inserts, DISTINCT keeps NULLs and treats NULL==NULL.
+    pub fn insert_distinct(
+        &mut self,
+        key_values: &[Value],
+        key_refs: &[ValueRef],
+        mut metrics: Option<&mut HashJoinMetrics>,
+    ) -> Result<IOResult<bool>> {
+        turso_assert!(
+            self.state == HashTableState::Building || self.state == HashTableState::Spilled,
+            "Cannot insert_distinct into hash table in unexpected state",
+            { "state": format!("{:?}", self.state) }
+        );
+
+        let hash = hash_join_key(key_refs, &self.collations, self.seed);
+
+        if self.spill_state.is_some() {
+            let partition_idx = self.partition_index(hash);
             // Check partition buffer for duplicates
             let has_buffer_dup = {
                 let spill_state = self.spill_state.as_ref().expect("spill state exists");
@@ -1326,7 +2153,7 @@ impl HashTable {
                 let spill_state = self.spill_state.as_mut().expect("spill state exists");
                 spill_state.partition_buffers[partition_idx].insert(HashEntry::new(
                     hash,
-                    key_values.iter().cloned().try_collect()?,
+                    JoinKey::try_from_slice(key_values)?,
                     0,
                 ))?;
             }
@@ -1347,7 +2174,7 @@ impl HashTable {
         }
 
         let entry_size = HashEntry::size_from_values(key_values, &[]);
-        if self.mem_used + entry_size > self.mem_budget {
+        if self.allocated_mem() + entry_size > self.mem_budget {
             if self.spill_state.is_none() {
                 let partition_count = self.choose_partition_count(entry_size);
                 let partitioning = Partitioning::new(partition_count);
@@ -1358,59 +2185,62 @@ impl HashTable {
             return self.insert_distinct(key_values, key_refs, metrics);
         }
 
-        if self.buckets[bucket_idx].entries.is_empty() {
-            self.non_empty_buckets.try_push(bucket_idx)?;
-        }
-        self.buckets[bucket_idx].insert(HashEntry::new(
-            hash,
-            key_values.iter().cloned().try_collect()?,
-            0,
-        ))?;
+        self.insert_into_bucket(
+            bucket_idx,
+            HashEntry::new(hash, JoinKey::try_from_slice(key_values)?, 0),
+        )?;
         self.num_entries += 1;
         self.mem_used += entry_size;
         Ok(IOResult::Done(true))
     }
 
-    /// Clear all entries and reset spill state.
+    /// Clear all entries and reset spill state, going back to `Building` so the table
+    /// can be rebuilt, e.g. once per iteration of an outer loop.
+    ///
+    /// An in-memory table keeps its bucket count and the capacity of its buckets, so a
+    /// rebuild of a similar size does not reallocate them. A spilled table drops its
+    /// partitions and starts over with `initial_buckets` buckets. The seed is kept
+    /// either way.
     pub fn clear(&mut self) -> Result<()> {
-        if self.num_entries == 0 && self.spill_state.is_none() {
-            self.state = HashTableState::Building;
-            self.current_probe_keys = None;
-            self.current_probe_hash = None;
-            self.probe_bucket_idx = 0;
-            self.probe_entry_idx = 0;
-            self.current_spill_partition_idx = 0;
-            self.loaded_partitions_lru.borrow_mut().clear();
-            self.loaded_partitions_mem = 0;
-            self.non_empty_buckets.clear();
-            self.probe_spill_state = None;
-            self.grace_state = None;
-            return Ok(());
-        }
-
-        if self.spill_state.is_some() {
+        let had_entries = self.num_entries > 0 || self.spill_state.is_some();
+        if self.spill_state.take().is_some() {
             // Drop spilled partitions and reset buckets.
-            self.spill_state = None;
             let bucket_count = self.initial_buckets.max(1);
             self.buckets = (0..bucket_count).map(|_| HashBucket::new()).try_collect()?;
-            self.non_empty_buckets.clear();
+            if self.track_matched {
+                // Spilling moved the matched bits into the partitions.
+                self.matched_bits = (0..bucket_count).map(|_| vec![]).try_collect()?;
+            }
         } else {
             for &idx in &self.non_empty_buckets {
                 self.buckets[idx].entries.clear();
+                if self.track_matched {
+                    self.matched_bits[idx].clear();
+                }
             }
-            self.non_empty_buckets.clear();
         }
+        self.non_empty_buckets.clear();
+        self.deferred.clear();
 
         self.num_entries = 0;
         self.mem_used = 0;
+        self.update_bucket_alloc_bytes();
+        if had_entries {
+            if let Some(bloom_filter) = self.bloom_filter.as_mut() {
+                bloom_filter.clear();
+            }
+        }
         self.state = HashTableState::Building;
         self.current_probe_keys = None;
         self.current_probe_hash = None;
         self.probe_bucket_idx = 0;
         self.probe_entry_idx = 0;
         self.current_spill_partition_idx = 0;
+        self.begin_unmatched_scan();
         self.loaded_partitions_lru.borrow_mut().clear();
         self.loaded_partitions_mem = 0;
+        self.probe_spill_state = None;
+        self.grace_state = None;
         Ok(())
     }
 
@@ -1420,7 +2250,7 @@ impl HashTable {
             let spill_state = self.spill_state.as_ref().expect("spill state must exist");
             spill_state.partitioning
         };
-        for bucket in self.buckets.drain(..) {
+        for bucket in std::mem::take(&mut self.buckets) {
             for entry in bucket.entries {
                 let partition_idx = partitioning.index(entry.hash);
                 self.spill_state
@@ -1432,6 +2262,7 @@ impl HashTable {
         }
         // Clear in-memory matched bits; spilled partitions will have their own.
         self.matched_bits.clear();
+        self.update_bucket_alloc_bytes();
         Ok(())
     }
 
@@ -1724,7 +2555,7 @@ impl HashTable {
         entry_size: usize,
         metrics: Option<&mut HashJoinMetrics>,
     ) -> Result<Option<Completion>> {
-        if self.mem_used + entry_size <= self.mem_budget {
+        if self.allocated_mem() + entry_size <= self.mem_budget {
             return Ok(None);
         }
 
@@ -1800,12 +2631,309 @@ impl HashTable {
         Ok(())
     }
 
+    /// Re-partition spilled partitions that would exceed the memory budget once loaded
+    /// back, so that probing never has to hold one in memory. Each is streamed chunk by
+    /// chunk into `repartition_fanout` sub-partitions, recursively up to
+    /// [MAX_REPARTITION_DEPTH] times. A partition still over budget at that depth fails
+    /// the build.
+    fn repartition_oversized_partitions(
+        &mut self,
+        mut metrics: Option<&mut HashJoinMetrics>,
+    ) -> Result<IOResult<()>> {
+        if self.repartition_fanout == 1 {
+            return Ok(IOResult::Done(()));
+        }
+        loop {
+            let spill_state = self.spill_state.as_ref().expect("spill state must exist");
+            if spill_state.repartition.is_some() {
+                return_if_io!(self.stream_repartition(metrics.as_deref_mut()));
+                continue;
+            }
+            if spill_state
+                .partitions
+                .iter()
+                .any(|p| matches!(p.io_state.get(), SpillIOState::WaitingForWrite))
+            {
+                io_yield_one!(Completion::new_yield());
+            }
+            let oversized = spill_state
+                .partitions
+                .iter()
+                .find(|p| !p.chunks.is_empty() && p.total_size_bytes() > self.mem_budget)
+                .map(|p| p.partition_idx);
+            let Some(partition_idx) = oversized else {
+                return Ok(IOResult::Done(()));
+            };
+            self.start_repartition(partition_idx)?;
+        }
+    }
+
+    /// Detach an oversized spilled partition and set up the `repartition_fanout`
+    /// sub-partitions its entries are routed to by a re-seeded hash. Fails if the
+    /// partition was already re-partitioned [MAX_REPARTITION_DEPTH] times.
+    fn start_repartition(&mut self, partition_idx: usize) -> Result<()> {
+        let spill_state = self.spill_state.as_mut().expect("spill state must exist");
+        let pos = spill_state
+            .partitions
+            .iter()
+            .position(|p| p.partition_idx == partition_idx)
+            .expect("partition to split must exist");
+        let depth = spill_state.split_depth(partition_idx);
+        if depth >= MAX_REPARTITION_DEPTH {
+            let parent = &spill_state.partitions[pos];
+            return Err(LimboError::InternalError(format!(
+                "hash join build side partition has {} rows, {} bytes on disk, which still \
+                 exceeds the memory budget of {} bytes after {depth} re-partitions and cannot \
+                 be partitioned further",
+                parent.total_num_entries(),
+                parent.total_size_bytes(),
+                self.mem_budget
+            )));
+        }
+
+        let mut parent = spill_state.partitions.remove(pos);
+        self.loaded_partitions_mem = self
+            .loaded_partitions_mem
+            .saturating_sub(parent.resident_mem);
+        {
+            let mut lru = self.loaded_partitions_lru.borrow_mut();
+            if let Some(lru_pos) = lru.iter().position(|p| *p == partition_idx) {
+                lru.remove(lru_pos);
+            }
+        }
+        parent.buckets = vec![];
+        parent.matched_bits = vec![];
+        parent.resident_mem = 0;
+        parent.current_chunk_idx = 0;
+        parent.partial_entry.clear();
+        parent.io_state.set(SpillIOState::None);
+
+        let split = PartitionSplit {
+            parent: partition_idx,
+            depth: depth + 1,
+            first_child: spill_state.partition_buffers.len(),
+            partitioning: Partitioning::new(self.repartition_fanout),
+        };
+        tracing::debug!(
+            "re-partitioning spilled partition {partition_idx} ({} bytes) into {} sub-partitions at depth {}",
+            parent.total_size_bytes(),
+            self.repartition_fanout,
+            split.depth
+        );
+        for _ in 0..self.repartition_fanout {
+            spill_state
+                .partition_buffers
+                .try_push(PartitionBuffer::new())?;
+        }
+        spill_state.splits.try_push(split)?;
+        spill_state.repartition = Some(Repartition {
+            parent,
+            split,
+            first_hash: None,
+            single_hash: true,
+        });
+        Ok(())
+    }
+
+    /// Read back the chunks of the partition being re-partitioned and route their
+    /// entries to its sub-partitions, spilling those whenever the budget is exceeded.
+    /// Fails if all of its entries have the same hash: those are duplicates of a single
+    /// key and no split can make them smaller.
+    fn stream_repartition(
+        &mut self,
+        mut metrics: Option<&mut HashJoinMetrics>,
+    ) -> Result<IOResult<()>> {
+        loop {
+            let action = {
+                let spill_state = self.spill_state.as_mut().expect("spill state must exist");
+                let repartition = spill_state
+                    .repartition
+                    .as_mut()
+                    .expect("repartition must be in progress");
+                let parent = &mut repartition.parent;
+                let io_state = parent.io_state.get();
+
+                if unlikely(matches!(io_state, SpillIOState::Error)) {
+                    return Err(LimboError::InternalError(
+                        "hash join spill I/O failure".into(),
+                    ));
+                }
+
+                if matches!(io_state, SpillIOState::WaitingForRead) {
+                    ChunkReadAction::WaitingForIO
+                } else if matches!(io_state, SpillIOState::ReadComplete) {
+                    ChunkReadAction::ParseChunk {
+                        partition_idx: parent.partition_idx,
+                    }
+                } else {
+                    match parent.current_chunk() {
+                        Some(chunk) if chunk.size_bytes == 0 => {
+                            parent.current_chunk_idx += 1;
+                            ChunkReadAction::Restart
+                        }
+                        Some(chunk) => {
+                            parent.io_state.set(SpillIOState::WaitingForRead);
+                            ChunkReadAction::LoadChunk {
+                                read_size: chunk.size_bytes,
+                                file_offset: chunk.file_offset,
+                                io_state: parent.io_state.clone(),
+                                buffer_len: parent.buffer_len.clone(),
+                                read_buffer_ref: parent.read_buffer.clone(),
+                            }
+                        }
+                        None => ChunkReadAction::NoMoreChunks,
+                    }
+                }
+            };
+
+            match action {
+                ChunkReadAction::WaitingForIO => {
+                    io_yield_one!(Completion::new_yield());
+                }
+                ChunkReadAction::ParseChunk { .. } => {
+                    let SpillState {
+                        partition_buffers,
+                        repartition,
+                        ..
+                    } = self.spill_state.as_mut().expect("spill state must exist");
+                    let Repartition {
+                        parent,
+                        split,
+                        first_hash,
+                        single_hash,
+                    } = repartition
+                        .as_mut()
+                        .expect("repartition must be in progress");
+
+                    let split = *split;
+                    let data_len = parent.buffer_len();
+                    if let Some(metrics) = metrics.as_mut() {
+                        metrics.load_bytes_read =
+                            metrics.load_bytes_read.saturating_add(data_len as u64);
+                    }
+                    let mut data = std::mem::take(&mut *parent.read_buffer.write());
+                    data.truncate(data_len);
+                    parent.buffer_len.store(0, atomic::Ordering::Release);
+                    parent.io_state.set(SpillIOState::None);
+                    parent.current_chunk_idx += 1;
+                    if !parent.partial_entry.is_empty() {
+                        let mut combined = std::mem::take(&mut parent.partial_entry);
+                        combined.try_reserve(data.len())?;
+                        combined.extend_from_slice(&data);
+                        data = combined;
+                    }
+
+                    let mut added_mem = 0;
+                    parse_spilled_entries(&data, &mut parent.partial_entry, |entry| {
+                        match *first_hash {
+                            None => *first_hash = Some(entry.hash),
+                            Some(hash) => *single_hash &= hash == entry.hash,
+                        }
+                        // Released again when the sub-partitions are spilled.
+                        added_mem += entry.size_bytes();
+                        partition_buffers[split.child(entry.hash)].insert(entry)
+                    })?;
+                    self.mem_used += added_mem;
+
+                    if self.allocated_mem() > self.mem_budget {
+                        if let Some(c) =
+                            self.spill_repartition_children(split, metrics.as_deref_mut())?
+                        {
+                            if !c.finished() {
+                                io_yield_one!(c);
+                            }
+                        }
+                    }
+                }
+                ChunkReadAction::LoadChunk {
+                    read_size,
+                    file_offset,
+                    io_state,
+                    buffer_len,
+                    read_buffer_ref,
+                } => {
+                    let read_buffer = Arc::new(Buffer::new_temporary(read_size));
+                    let read_complete = Box::new(
+                        move |res: Result<(Arc<Buffer>, i32), CompletionError>| match res {
+                            Ok((buf, bytes_read)) => {
+                                let mut persistent_buf = read_buffer_ref.write();
+                                persistent_buf.clear();
+                                persistent_buf
+                                    .extend_from_slice(&buf.as_slice()[..bytes_read as usize]);
+                                buffer_len.store(bytes_read as usize, atomic::Ordering::Release);
+                                io_state.set(SpillIOState::ReadComplete);
+                                None
+                            }
+                            Err(e) => {
+                                mark_unlikely();
+                                tracing::error!("Error reading partition chunk to split: {e:?}");
+                                io_state.set(SpillIOState::Error);
+                                None
+                            }
+                        },
+                    );
+                    let completion = Completion::new_read(read_buffer, read_complete);
+                    if let Some(metrics) = metrics.as_mut() {
+                        metrics.load_read_calls = metrics.load_read_calls.saturating_add(1);
+                    }
+                    let spill_state = self.spill_state.as_ref().expect("spill state must exist");
+                    let c = spill_state.temp_file.file.pread(file_offset, completion)?;
+                    if !c.finished() {
+                        io_yield_one!(c);
+                    }
+                }
+                ChunkReadAction::Restart => continue,
+                ChunkReadAction::NoMoreChunks => {
+                    let spill_state = self.spill_state.as_mut().expect("spill state must exist");
+                    let repartition = spill_state
+                        .repartition
+                        .take()
+                        .expect("repartition must be in progress");
+                    let parent = repartition.parent;
+                    if unlikely(!parent.partial_entry.is_empty()) {
+                        return Err(LimboError::Corrupt("HashEntry: truncated entry".into()));
+                    }
+                    if repartition.first_hash.is_some() && repartition.single_hash {
+                        return Err(LimboError::InternalError(format!(
+                            "hash join build side has {} rows with the same join key, {} bytes on disk, \
+                             which exceeds the memory budget of {} bytes and cannot be partitioned further",
+                            parent.total_num_entries(),
+                            parent.total_size_bytes(),
+                            self.mem_budget
+                        )));
+                    }
+                    // Write out what is still buffered before the table is probed.
+                    if let Some(c) =
+                        self.spill_repartition_children(repartition.split, metrics.as_deref_mut())?
+                    {
+                        if !c.finished() {
+                            io_yield_one!(c);
+                        }
+                    }
+                    return Ok(IOResult::Done(()));
+                }
+            }
+        }
+    }
+
+    /// Spill the buffered entries of the sub-partitions of `split`.
+    fn spill_repartition_children(
+        &mut self,
+        split: PartitionSplit,
+        metrics: Option<&mut HashJoinMetrics>,
+    ) -> Result<Option<Completion>> {
+        let children: Vec<usize> =
+            (split.first_child..split.first_child + split.partitioning.count).try_collect()?;
+        self.spill_multiple_partitions(&children, metrics)
+    }
+
     /// Finalize the build phase and prepare for probing.
+    /// Rows buffered by a `deferred_index` build are hashed into their buckets here.
     /// If spilled, flushes remaining in-memory partition entries to disk.
     pub fn finalize_build(
         &mut self,
         metrics: Option<&mut HashJoinMetrics>,
-    ) -> Result<IOResult<()>> {
+    ) -> Result<IOResult<BuildSummary>> {
         let mut metrics = metrics;
         turso_assert!(
             self.state == HashTableState::Building || self.state == HashTableState::Spilled,
@@ -1813,6 +2941,7 @@ impl HashTable {
             { "state": format!("{:?}", self.state) }
         );
 
+        self.index_deferred()?;
         if self.spill_state.is_some() {
             {
                 // Check for pending writes from previous call
@@ -1854,10 +2983,51 @@ impl HashTable {
             for partition_idx in materialize_targets {
                 self.materialize_partition_in_memory(partition_idx)?;
             }
+            return_if_io!(self.repartition_oversized_partitions(metrics));
+        }
+        if self.shrink_on_finalize {
+            self.shrink_allocations();
         }
         self.current_spill_partition_idx = 0;
         self.state = HashTableState::Probing;
-        Ok(IOResult::Done(()))
+        Ok(IOResult::Done(self.build_summary()?))
+    }
+
+    /// Summarize the table once the build is finalized, see [BuildSummary].
+    fn build_summary(&self) -> Result<BuildSummary> {
+        let stats = self.stats();
+        // Partitions kept in memory at finalize have buckets but no chunks.
+        let in_memory = self.spill_state.iter().flat_map(|spill_state| {
+            spill_state
+                .partitions
+                .iter()
+                .filter(|p| p.chunks.is_empty())
+                .map(|p| &p.buckets)
+        });
+        let mut distinct_keys = stats.spilled_entries;
+        let mut max_chain_length = 0;
+        let mut hashes = vec![];
+        for bucket in std::iter::once(&self.buckets)
+            .chain(in_memory)
+            .flat_map(|buckets| buckets.iter())
+        {
+            max_chain_length = max_chain_length.max(bucket.entries.len());
+            // Equal hashes always share a bucket, so distinct hashes can be counted
+            // one bucket at a time.
+            hashes.clear();
+            for entry in &bucket.entries {
+                hashes.try_push(entry.hash)?;
+            }
+            hashes.sort_unstable();
+            hashes.dedup();
+            distinct_keys += hashes.len();
+        }
+        Ok(BuildSummary {
+            num_entries: stats.num_entries,
+            estimated_distinct_keys: distinct_keys,
+            max_chain_length,
+            mem_used: stats.mem_used,
+        })
     }
 
     /// Probe the hash table with the given keys, returns the first matching entry if found.
@@ -1868,124 +3038,130 @@ impl HashTable {
         probe_keys: Vec<Value>,
         metrics: Option<&mut HashJoinMetrics>,
     ) -> Result<Option<&HashEntry>> {
+        self.begin_probe(probe_keys, metrics)?;
+        self.advance_probe()
+    }
+
+    /// Iterate over the entries whose join keys equal `probe_keys`. Unlike
+    /// [HashTable::probe] and [HashTable::next_match] this does not move the probe
+    /// cursor, so [HashTable::mark_current_matched] does not apply to its entries, nor
+    /// does [HashTableConfig::max_matches_per_key].
+    /// NOTE: On a spilled table the partition for `probe_keys` must be loaded.
+    pub fn matches(&mut self, probe_keys: Vec<Value>) -> Result<MatchIter<'_>> {
+        self.begin_probe(probe_keys, None)?;
+        Ok(self.probe_cursor())
+    }
+
+    /// Point the probe cursor at the start of the bucket `probe_keys` hash to.
+    fn begin_probe(
+        &mut self,
+        probe_keys: Vec<Value>,
+        metrics: Option<&mut HashJoinMetrics>,
+    ) -> Result<()> {
         turso_assert!(
             self.state == HashTableState::Probing,
             "Cannot probe hash table in unexpected state",
             { "state": format!("{:?}", self.state) }
         );
+        self.probe_entry_idx = 0;
+        self.probe_matches = 0;
 
         // Skip probing if any key is NULL - NULL can never match anything in SQL
-        if has_null_key(&probe_keys) {
+        if !self.null_safe && has_null_key(&probe_keys) {
             self.current_probe_keys = Some(probe_keys);
             self.current_probe_hash = None;
-            return Ok(None);
+            return Ok(());
         }
 
         // Compute hash of probe keys using collations
-        let hash = {
-            let key_refs: Vec<ValueRef> = probe_keys
-                .iter()
-                .map(|value| value.as_ref())
-                .try_collect()?;
-            hash_join_key(&key_refs, &self.collations)
-        };
+        let hash = hash_join_key_owned(&probe_keys, &self.collations, self.seed);
         self.current_probe_keys = Some(probe_keys);
-        self.current_probe_hash = Some(hash);
 
-        // Reset probe state
-        self.probe_entry_idx = 0;
+        // No entry was inserted with this hash, so no entry can match.
+        if let Some(bloom_filter) = self.bloom_filter.as_ref() {
+            if !bloom_filter.contains_i64(hash as i64) {
+                self.current_probe_hash = None;
+                return Ok(());
+            }
+        }
+        self.current_probe_hash = Some(hash);
 
         if self.spill_state.is_some() {
             // In spilled mode, search through loaded entries from spilled partitions
             // that match this probe key's partition
-            let partitioning = {
-                let spill_state = self.spill_state.as_ref().expect("spill state must exist");
-                spill_state.partitioning
-            };
-            let target_partition = partitioning.index(hash);
+            let target_partition = self.partition_index(hash);
             self.record_probe_call(metrics);
             self.touch_partition_lru(target_partition);
-
-            let bucket_idx = {
-                let spill_state = self.spill_state.as_ref().expect("spill state must exist");
-                let Some(partition) = spill_state.find_partition(target_partition) else {
-                    return Ok(None);
-                };
-                if partition.buckets.is_empty() {
-                    return Ok(None);
-                }
-                (hash as usize) % partition.buckets.len()
-            };
-
-            self.probe_bucket_idx = bucket_idx;
+            let spill_state = self.spill_state.as_ref().expect("spill state must exist");
             self.current_spill_partition_idx = target_partition;
+            self.probe_bucket_idx = spill_state
+                .find_partition(target_partition)
+                .filter(|partition| !partition.buckets.is_empty())
+                .map_or(0, |partition| (hash as usize) % partition.buckets.len());
+        } else {
+            self.probe_bucket_idx = (hash as usize) % self.buckets.len();
+        }
+        Ok(())
+    }
 
-            let match_idx = {
-                let key_refs: Vec<ValueRef> = self
-                    .current_probe_keys
-                    .as_ref()
-                    .expect("probe keys were set")
-                    .iter()
-                    .map(|v| v.as_ref())
-                    .try_collect()?;
-                let spill_state = self.spill_state.as_ref().expect("spill state must exist");
-                let Some(partition) = spill_state.find_partition(target_partition) else {
-                    return Ok(None);
-                };
-                let bucket = &partition.buckets[bucket_idx];
-                let mut found = None;
-                for (idx, entry) in bucket.entries.iter().enumerate() {
-                    if entry.hash == hash
-                        && keys_equal(&entry.key_values, &key_refs, &self.collations)
-                    {
-                        found = Some(idx);
-                        break;
-                    }
-                }
-                found
-            };
+    /// Entries of the bucket the probe cursor is in. Empty if the bucket's partition
+    /// is not loaded.
+    fn probe_bucket_entries(&self) -> &[HashEntry] {
+        let bucket = match self.spill_state.as_ref() {
+            Some(spill_state) => spill_state
+                .find_partition(self.current_spill_partition_idx)
+                .and_then(|partition| partition.buckets.get(self.probe_bucket_idx)),
+            None => self.buckets.get(self.probe_bucket_idx),
+        };
+        bucket.map_or(&[][..], |bucket| &bucket.entries[..])
+    }
 
-            if let Some(idx) = match_idx {
-                self.probe_entry_idx = idx + 1;
-                let spill_state = self.spill_state.as_ref().expect("spill state must exist");
-                let Some(partition) = spill_state.find_partition(target_partition) else {
-                    return Ok(None);
-                };
-                let bucket = &partition.buckets[bucket_idx];
-                return Ok(bucket.entries.get(idx));
-            }
-            Ok(None)
-        } else {
-            // Normal mode - search in hash buckets
-            let bucket_idx = (hash as usize) % self.buckets.len();
-            self.probe_bucket_idx = bucket_idx;
-            let match_idx = {
-                let key_refs: Vec<ValueRef> = self
-                    .current_probe_keys
-                    .as_ref()
-                    .expect("probe keys were set")
-                    .iter()
-                    .map(|v| v.as_ref())
-                    .try_collect()?;
-                let bucket = &self.buckets[bucket_idx];
-                let mut found = None;
-                for (idx, entry) in bucket.entries.iter().enumerate() {
-                    if entry.hash == hash
-                        && keys_equal(&entry.key_values, &key_refs, &self.collations)
-                    {
-                        found = Some(idx);
-                        break;
-                    }
-                }
-                found
-            };
+    /// Matches of the current probe keys from the probe cursor onwards.
+    fn probe_cursor(&self) -> MatchIter<'_> {
+        MatchIter {
+            // Without a hash, from a NULL key or a Bloom filter miss, nothing matches.
+            entries: match self.current_probe_hash {
+                Some(_) => self.probe_bucket_entries(),
+                None => &[],
+            },
+            next_idx: self.probe_entry_idx,
+            hash: self.current_probe_hash.unwrap_or_default(),
+            keys: self.current_probe_keys.as_deref().unwrap_or_default(),
+            collations: &self.collations,
+            null_safe: self.null_safe,
+            first_only: self.join_mode != JoinMode::Inner,
+        }
+    }
 
-            if let Some(idx) = match_idx {
-                self.probe_entry_idx = idx + 1;
-                return Ok(self.buckets[bucket_idx].entries.get(idx));
-            }
-            Ok(None)
+    /// Move the probe cursor past the next match and return it, unless
+    /// `max_matches_per_key` matches were already returned for the current keys.
+    fn advance_probe(&mut self) -> Result<Option<&HashEntry>> {
+        if self
+            .max_matches_per_key
+            .is_some_and(|max| self.probe_matches >= max)
+        {
+            return Ok(None);
         }
+        let next_idx = {
+            let mut cursor = self.probe_cursor();
+            cursor.next().map(|_| cursor.next_idx)
+        };
+        let Some(next_idx) = next_idx else {
+            return Ok(None);
+        };
+        self.probe_entry_idx = next_idx;
+        self.probe_matches += 1;
+        Ok(self.probe_bucket_entries().get(next_idx - 1))
+    }
+
+    /// Whether any build entry has join keys equal to `probe_keys`. Meant for
+    /// [JoinMode::Anti] tables, where the caller only emits probe rows without a match.
+    pub fn probe_exists(
+        &mut self,
+        probe_keys: Vec<Value>,
+        metrics: Option<&mut HashJoinMetrics>,
+    ) -> Result<bool> {
+        Ok(self.probe(probe_keys, metrics)?.is_some())
     }
 
     /// Get the next matching entry for the current probe keys.
@@ -1995,57 +3171,86 @@ impl HashTable {
             "Cannot get next match in unexpected state",
             { "state": format!("{:?}", self.state) }
         );
+        if self.join_mode != JoinMode::Inner {
+            // Semi and anti joins only need the first match, returned by probe.
+            return Ok(None);
+        }
 
         turso_assert!(self.current_probe_keys.is_some(), "probe keys must be set");
-        let Some(probe_keys) = self.current_probe_keys.as_ref() else {
-            return Ok(None);
-        };
-        let key_refs: Vec<ValueRef> = probe_keys.iter().map(|v| v.as_ref()).try_collect()?;
-        let hash = match self.current_probe_hash {
-            Some(h) => h,
-            None => {
-                let h = hash_join_key(&key_refs, &self.collations);
-                self.current_probe_hash = Some(h);
-                h
-            }
-        };
+        if let (Some(hash), Some(_)) = (self.current_probe_hash, self.spill_state.as_ref()) {
+            turso_assert_eq!(self.current_spill_partition_idx, self.partition_index(hash));
+        }
+        self.advance_probe()
+    }
 
-        if let Some(spill_state) = self.spill_state.as_ref() {
-            let partition_idx = self.current_spill_partition_idx;
+    /// Call `f` on each entry matching `keys`, in bucket order, until it returns
+    /// `ControlFlow::Break`. Lets callers evaluate a residual predicate per match
+    /// without collecting all of them first. Does not move the probe cursor.
+    /// NOTE: On a spilled table the partition for `keys` must be loaded.
+    pub fn visit_matches<F>(&self, keys: &[ValueRef], mut f: F) -> ControlFlow<()>
+    where
+        F: FnMut(&HashEntry) -> ControlFlow<()>,
+    {
+        turso_assert!(
+            self.state == HashTableState::Probing || self.state == HashTableState::GraceProcessing,
+            "Cannot visit matches in unexpected state",
+            { "state": format!("{:?}", self.state) }
+        );
+        if !self.null_safe && has_null_key_ref(keys) {
+            return ControlFlow::Continue(());
+        }
 
-            turso_assert_eq!(partition_idx, self.partition_index(hash));
-            let Some(partition) = spill_state.find_partition(partition_idx) else {
-                return Ok(None);
-            };
-            if partition.buckets.is_empty() {
-                return Ok(None);
+        let hash = hash_join_key(keys, &self.collations, self.seed);
+        let buckets = match self.spill_state.as_ref() {
+            Some(spill_state) => match spill_state.find_partition(self.partition_index(hash)) {
+                Some(partition) => &partition.buckets,
+                None => return ControlFlow::Continue(()),
+            },
+            None => &self.buckets,
+        };
+        if buckets.is_empty() {
+            return ControlFlow::Continue(());
+        }
+        let bucket = &buckets[(hash as usize) % buckets.len()];
+        for entry in &bucket.entries {
+            if entry.hash == hash
+                && keys_equal(&entry.key_values, keys, &self.collations, self.null_safe)
+            {
+                f(entry)?;
             }
+        }
+        ControlFlow::Continue(())
+    }
 
-            let bucket = &partition.buckets[self.probe_bucket_idx];
-            // Continue from where we left off
-            for idx in self.probe_entry_idx..bucket.entries.len() {
-                let entry = &bucket.entries[idx];
-                if entry.hash == hash && keys_equal(&entry.key_values, &key_refs, &self.collations)
-                {
-                    self.probe_entry_idx = idx + 1;
-                    return Ok(Some(entry));
-                }
-            }
-            Ok(None)
-        } else {
-            // non-spilled case, seach in main buckets
-            let bucket = &self.buckets[self.probe_bucket_idx];
-            for idx in self.probe_entry_idx..bucket.entries.len() {
-                let entry = &bucket.entries[idx];
-                if entry.hash == hash && keys_equal(&entry.key_values, &key_refs, &self.collations)
-                {
-                    // update probe entry index for next call
-                    self.probe_entry_idx = idx + 1;
-                    return Ok(Some(entry));
-                }
+    /// Positions `(bucket_idx, entry_idx)` of the entries whose join keys equal `keys`,
+    /// in insertion order, for callers that fetch the matching rows later with
+    /// [Self::entry_at]. Positions index the in-memory buckets and stay valid until
+    /// the table is cleared or closed, so the table must be probing and not spilled.
+    pub fn match_positions(&self, keys: &[ValueRef]) -> SmallVec<[(usize, usize); 4]> {
+        turso_assert!(
+            self.state == HashTableState::Probing && self.spill_state.is_none(),
+            "match_positions requires an in-memory table after finalize_build",
+            { "state": format!("{:?}", self.state) }
+        );
+        let mut positions = SmallVec::new();
+        if (!self.null_safe && has_null_key_ref(keys)) || self.buckets.is_empty() {
+            return positions;
+        }
+        let hash = hash_join_key(keys, &self.collations, self.seed);
+        let bucket_idx = (hash as usize) % self.buckets.len();
+        for (entry_idx, entry) in self.buckets[bucket_idx].entries.iter().enumerate() {
+            if entry.hash == hash
+                && keys_equal(&entry.key_values, keys, &self.collations, self.null_safe)
+            {
+                positions.push((bucket_idx, entry_idx));
             }
-            Ok(None)
         }
+        positions
+    }
+
+    /// The entry at a position returned by [Self::match_positions].
+    pub fn entry_at(&self, bucket_idx: usize, entry_idx: usize) -> &HashEntry {
+        &self.buckets[bucket_idx].entries[entry_idx]
     }
 
     /// Mark the current matched entry as "matched" for outer join tracking.
@@ -2117,6 +3322,33 @@ impl HashTable {
         self.next_unmatched_main_buckets()
     }
 
+    /// Entries no probe marked as matched, for emitting the NULL-extended rows of an
+    /// outer join after the probe phase. The table must be `Probing`, built with
+    /// `track_matched` and never spilled: unlike [Self::next_unmatched] this borrows
+    /// the table instead of keeping a scan cursor, so it cannot load partitions.
+    pub fn unmatched_entries(&self) -> impl Iterator<Item = &HashEntry> {
+        turso_assert!(
+            self.track_matched,
+            "unmatched_entries requires a table built with track_matched"
+        );
+        turso_assert!(
+            self.state == HashTableState::Probing && self.spill_state.is_none(),
+            "unmatched_entries requires an in-memory table after finalize_build",
+            { "state": format!("{:?}", self.state) }
+        );
+        self.buckets
+            .iter()
+            .zip(&self.matched_bits)
+            .flat_map(|(bucket, matched)| {
+                bucket
+                    .entries
+                    .iter()
+                    .zip(matched)
+                    .filter(|(_, matched)| !**matched)
+                    .map(|(entry, _)| entry)
+            })
+    }
+
     fn next_unmatched_main_buckets(&mut self) -> Option<&HashEntry> {
         while self.unmatched_scan_bucket < self.buckets.len() {
             let bucket = &self.buckets[self.unmatched_scan_bucket];
@@ -2274,6 +3506,7 @@ impl HashTable {
         mut metrics: Option<&mut HashJoinMetrics>,
     ) -> Result<IOResult<()>> {
         loop {
+            let mmap_spill_reads = self.mmap_spill_reads;
             // to avoid holding mut borrows, split this into two phases.
             let action = {
                 let spill_state = match &mut self.spill_state {
@@ -2326,6 +3559,13 @@ impl HashTable {
                                     spilled.state = PartitionState::Loaded;
                                     SpillAction::NoChunks
                                 }
+                            } else if mmap_spill_reads {
+                                // Non-empty chunk, parse it in place from a mapping.
+                                spilled.state = PartitionState::Loading;
+                                SpillAction::MapChunk {
+                                    read_size,
+                                    file_offset,
+                                }
                             } else {
                                 // Non-empty chunk, schedule a read for it.
                                 let buffer_len = spilled.buffer_len.clone();
@@ -2378,6 +3618,29 @@ impl HashTable {
                 SpillAction::WaitingForIO => {
                     io_yield_one!(Completion::new_yield());
                 }
+                SpillAction::MapChunk {
+                    read_size,
+                    file_offset,
+                } => {
+                    match self.parse_mapped_partition_chunk(
+                        partition_idx,
+                        file_offset,
+                        read_size,
+                        metrics.as_deref_mut(),
+                    )? {
+                        Some(ParseChunkResult::MoreChunks) => continue,
+                        Some(ParseChunkResult::Done { resident_mem }) => {
+                            self.evict_partitions_to_fit(resident_mem, partition_idx);
+                            self.record_partition_resident(partition_idx, resident_mem);
+                            return Ok(IOResult::Done(()));
+                        }
+                        None => {
+                            // The file can't be mapped, use pread for this and later loads.
+                            self.mmap_spill_reads = false;
+                            continue;
+                        }
+                    }
+                }
                 SpillAction::Restart => {
                     // We advanced state (e.g., moved past an empty chunk or completed a chunk),
                     // so just loop again and recompute the next action.
@@ -2414,6 +3677,9 @@ impl HashTable {
                         },
                     );
                     let completion = Completion::new_read(read_buffer, read_complete);
+                    if let Some(metrics) = metrics.as_mut() {
+                        metrics.load_read_calls = metrics.load_read_calls.saturating_add(1);
+                    }
                     let spill_state = self.spill_state.as_ref().expect("spill state must exist");
                     let c = spill_state.temp_file.file.pread(file_offset, completion)?;
                     if !c.finished() {
@@ -2430,110 +3696,76 @@ impl HashTable {
         partition_idx: usize,
         mut metrics: Option<&mut HashJoinMetrics>,
     ) -> Result<ParseChunkResult> {
-        let (has_more_chunks, resident_mem) = {
-            let spill_state = self.spill_state.as_mut().expect("spill state must exist");
-            let partition = spill_state
-                .find_partition_mut(partition_idx)
-                .expect("partition must exist for parsing");
-
-            let data_len = partition.buffer_len();
-            if let Some(metrics) = metrics.as_mut() {
-                metrics.load_bytes_read = metrics.load_bytes_read.saturating_add(data_len as u64);
-            }
-
-            let data_guard = partition.read_buffer.read();
-            let data = &data_guard[..data_len];
-            let parse_buf = if partition.partial_entry.is_empty() {
-                data.iter().copied().try_collect()?
-            } else {
-                let mut combined =
-                    Vec::try_with_capacity_ext(partition.partial_entry.len() + data.len())?;
-                combined.extend_from_slice(&partition.partial_entry);
-                combined.extend_from_slice(data);
-                combined
-            };
-            drop(data_guard);
-
-            partition.partial_entry.clear();
-            partition.buffer_len.store(0, atomic::Ordering::Release);
-            partition.read_buffer.write().clear();
-            partition.io_state.set(SpillIOState::None);
-
-            let mut offset = 0;
-            while offset < parse_buf.len() {
-                let Some((entry_len, varint_size)) = read_varint_partial(&parse_buf[offset..])?
-                else {
-                    partition
-                        .partial_entry
-                        .try_reserve(parse_buf.len() - offset)?;
-                    partition
-                        .partial_entry
-                        .extend_from_slice(&parse_buf[offset..]);
-                    break;
-                };
+        let spill_state = self.spill_state.as_mut().expect("spill state must exist");
+        let partition = spill_state
+            .find_partition_mut(partition_idx)
+            .expect("partition must exist for parsing");
 
-                let total_needed = varint_size + entry_len as usize;
-                if offset + total_needed > parse_buf.len() {
-                    partition
-                        .partial_entry
-                        .try_reserve(parse_buf.len() - offset)?;
-                    partition
-                        .partial_entry
-                        .extend_from_slice(&parse_buf[offset..]);
-                    break;
-                }
+        let data_len = partition.buffer_len();
+        if let Some(metrics) = metrics.as_mut() {
+            metrics.load_bytes_read = metrics.load_bytes_read.saturating_add(data_len as u64);
+        }
 
-                let start = offset + varint_size;
-                let end = start + entry_len as usize;
-                let (entry, consumed) = HashEntry::deserialize(&parse_buf[start..end])?;
-                turso_assert!(
-                    consumed == entry_len as usize,
-                    "expected to consume entire entry"
-                );
+        let data_guard = partition.read_buffer.read();
+        let data = &data_guard[..data_len];
+        let parse_buf = if partition.partial_entry.is_empty() {
+            data.iter().copied().try_collect()?
+        } else {
+            let mut combined =
+                Vec::try_with_capacity_ext(partition.partial_entry.len() + data.len())?;
+            combined.extend_from_slice(&partition.partial_entry);
+            combined.extend_from_slice(data);
+            combined
+        };
+        drop(data_guard);
 
-                let bucket_idx = (entry.hash as usize) % partition.buckets.len();
-                partition.buckets[bucket_idx].insert(entry)?;
-                partition.parsed_entries += 1;
-                offset += total_needed;
-            }
+        partition.partial_entry.clear();
+        partition.buffer_len.store(0, atomic::Ordering::Release);
+        partition.read_buffer.write().clear();
+        partition.io_state.set(SpillIOState::None);
 
-            partition.current_chunk_idx += 1;
+        partition.parse_entries(&parse_buf)?;
+        partition.finish_chunk(self.track_matched)
+    }
 
-            if partition.has_more_chunks() {
-                (true, 0)
-            } else {
-                if unlikely(!partition.partial_entry.is_empty()) {
-                    return Err(LimboError::Corrupt("HashEntry: truncated entry".into()));
-                }
-                let total_num_entries = partition.total_num_entries();
-                turso_assert!(
-                    partition.parsed_entries == total_num_entries,
-                    "parsed entry count mismatch"
-                );
-                if self.track_matched && partition.matched_bits.is_empty() {
-                    // Only initialize matched_bits on the first load. On subsequent
-                    // reloads (after eviction), the existing bits are preserved so that
-                    // probe marks set during earlier passes are not lost.
-                    partition.matched_bits = partition
-                        .buckets
-                        .iter()
-                        .map(|b| vec![false; b.entries.len()])
-                        .try_collect()?;
-                }
-                partition.state = PartitionState::Loaded;
-                partition.resident_mem = Self::partition_bucket_mem(&partition.buckets);
-                // Release staging buffer to free memory now that buckets are built.
-                partition.buffer_len.store(0, atomic::Ordering::SeqCst);
-                partition.read_buffer.write().clear();
-                (false, partition.resident_mem)
-            }
+    /// Parse the current chunk of a partition directly out of a read-only mapping of
+    /// the spill file, without copying it into the staging buffer first.
+    /// Returns `None` if the spill file cannot be mapped; the caller falls back to `pread`.
+    fn parse_mapped_partition_chunk(
+        &mut self,
+        partition_idx: usize,
+        file_offset: u64,
+        read_size: usize,
+        mut metrics: Option<&mut HashJoinMetrics>,
+    ) -> Result<Option<ParseChunkResult>> {
+        let spill_state = self.spill_state.as_mut().expect("spill state must exist");
+        let Some(mapping) = spill_state
+            .temp_file
+            .file
+            .map_read(file_offset, read_size)?
+        else {
+            return Ok(None);
         };
+        let partition = spill_state
+            .find_partition_mut(partition_idx)
+            .expect("partition must exist for parsing");
+        if let Some(metrics) = metrics.as_mut() {
+            metrics.load_bytes_read = metrics.load_bytes_read.saturating_add(read_size as u64);
+            metrics.load_mmap_chunks = metrics.load_mmap_chunks.saturating_add(1);
+        }
 
-        if has_more_chunks {
-            Ok(ParseChunkResult::MoreChunks)
+        let data = mapping.as_slice();
+        if partition.partial_entry.is_empty() {
+            partition.parse_entries(data)?;
         } else {
-            Ok(ParseChunkResult::Done { resident_mem })
+            let mut combined =
+                Vec::try_with_capacity_ext(partition.partial_entry.len() + data.len())?;
+            combined.extend_from_slice(&partition.partial_entry);
+            combined.extend_from_slice(data);
+            partition.partial_entry.clear();
+            partition.parse_entries(&combined)?;
         }
+        partition.finish_chunk(self.track_matched).map(Some)
     }
 
     /// Probe a specific partition with the given keys. The partition must be loaded first via `load_spilled_partition`.
@@ -2545,15 +3777,15 @@ impl HashTable {
         probe_keys: &[Value],
         metrics: Option<&mut HashJoinMetrics>,
     ) -> Result<Option<&HashEntry>> {
+        self.probe_matches = 0;
         // Skip probing if any key is NULL - NULL can never match anything in SQL
-        if has_null_key(probe_keys) {
+        if !self.null_safe && has_null_key(probe_keys) {
             self.current_probe_keys = Some(probe_keys.iter().cloned().try_collect()?);
             self.current_probe_hash = None;
             return Ok(None);
         }
 
-        let key_refs: Vec<ValueRef> = probe_keys.iter().map(|v| v.as_ref()).try_collect()?;
-        let hash = hash_join_key(&key_refs, &self.collations);
+        let hash = hash_join_key_owned(probe_keys, &self.collations, self.seed);
 
         // Store probe keys for subsequent next_match calls
         self.current_probe_keys = Some(probe_keys.iter().cloned().try_collect()?);
@@ -2579,8 +3811,16 @@ impl HashTable {
         self.current_spill_partition_idx = partition_idx;
 
         for (idx, entry) in bucket.entries.iter().enumerate() {
-            if entry.hash == hash && keys_equal(&entry.key_values, &key_refs, &self.collations) {
+            if entry.hash == hash
+                && keys_equal(
+                    &entry.key_values,
+                    probe_keys,
+                    &self.collations,
+                    self.null_safe,
+                )
+            {
                 self.probe_entry_idx = idx + 1;
+                self.probe_matches = 1;
                 return Ok(Some(entry));
             }
         }
@@ -2593,8 +3833,7 @@ impl HashTable {
             self.spill_state.is_some(),
             "partition_for_keys requires spill state"
         );
-        let key_refs: Vec<ValueRef> = probe_keys.iter().map(|v| v.as_ref()).try_collect()?;
-        let hash = hash_join_key(&key_refs, &self.collations);
+        let hash = hash_join_key_owned(probe_keys, &self.collations, self.seed);
         Ok(self.partition_index(hash))
     }
 
@@ -2603,6 +3842,178 @@ impl HashTable {
         self.spill_state.is_some()
     }
 
+    /// Entry counts and memory usage. Spilled entries are counted from the chunk
+    /// metadata of each partition, so they don't need to be loaded. A loaded
+    /// partition is counted once, as spilled. The chain length histogram covers the
+    /// buckets held in memory.
+    pub fn stats(&self) -> HashTableStats {
+        let bloom_filter_false_positive_rate = self
+            .bloom_filter
+            .as_ref()
+            .map(BloomFilter::estimated_false_positive_rate);
+        // Partitions kept in memory at finalize have buckets but no chunks.
+        let materialized = self.spill_state.iter().flat_map(|spill_state| {
+            spill_state
+                .partitions
+                .iter()
+                .filter(|p| p.chunks.is_empty())
+                .flat_map(|p| &p.buckets)
+        });
+        let mut in_memory = 0;
+        let mut chain_length_histogram = vec![0; CHAIN_LENGTH_HISTOGRAM_LEN];
+        for bucket in self.buckets.iter().chain(materialized) {
+            let len = bucket.entries.len();
+            in_memory += len;
+            chain_length_histogram[len.min(CHAIN_LENGTH_HISTOGRAM_LEN - 1)] += 1;
+        }
+        let Some(spill_state) = self.spill_state.as_ref() else {
+            return HashTableStats {
+                num_entries: in_memory + self.deferred.len(),
+                spilled_entries: 0,
+                mem_used: self.mem_used,
+                allocated_mem: self.allocated_mem(),
+                bloom_filter_false_positive_rate,
+                chain_length_histogram,
+            };
+        };
+        let buffered: usize = spill_state
+            .partition_buffers
+            .iter()
+            .map(|b| b.entries.len())
+            .sum();
+        let spilled_entries: usize = spill_state
+            .partitions
+            .iter()
+            .map(|p| p.total_num_entries())
+            .sum();
+        HashTableStats {
+            num_entries: buffered + in_memory + spilled_entries,
+            spilled_entries,
+            mem_used: self.mem_used + self.loaded_partitions_mem,
+            allocated_mem: self.allocated_mem() + self.loaded_partitions_mem,
+            bloom_filter_false_positive_rate,
+            chain_length_histogram,
+        }
+    }
+
+    /// Every entry of an in-memory table, in bucket order and in insertion order within
+    /// a bucket, whatever its key. Works while `Building` and `Probing`, but not once
+    /// the table has spilled, as spilled entries are on disk, nor before a
+    /// `deferred_index` build is finalized.
+    pub fn iter_entries(&self) -> impl Iterator<Item = &HashEntry> {
+        turso_assert!(
+            matches!(
+                self.state,
+                HashTableState::Building | HashTableState::Probing
+            ) && !self.has_spilled()
+                && self.deferred.is_empty(),
+            "iter_entries requires an in-memory table",
+            { "state": format!("{:?}", self.state) }
+        );
+        self.buckets.iter().flat_map(|bucket| bucket.entries.iter())
+    }
+
+    /// Consume an in-memory table into its entries, in the order of
+    /// [HashTable::iter_entries].
+    pub fn into_entries(mut self) -> Result<Vec<HashEntry>> {
+        turso_assert!(
+            matches!(
+                self.state,
+                HashTableState::Building | HashTableState::Probing
+            ) && !self.has_spilled()
+                && self.deferred.is_empty(),
+            "into_entries requires an in-memory table",
+            { "state": format!("{:?}", self.state) }
+        );
+        let mut entries = Vec::try_with_capacity_ext(self.num_entries)?;
+        for bucket in std::mem::take(&mut self.buckets) {
+            for entry in bucket.entries {
+                entries.try_push(entry)?;
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Release the slack capacity of an in-memory table, such as after most of its
+    /// entries were dropped. While more than three quarters of the buckets are empty,
+    /// the bucket count is halved, down to `initial_buckets`, and then every bucket's
+    /// entries are shrunk to fit. Returns the number of bytes reclaimed.
+    ///
+    /// Halving rehashes the entries, which resets the probe cursor, so it is skipped
+    /// while a table tracking matched entries is probed, as the matched bits would be lost.
+    pub fn shrink_to_fit(&mut self) -> Result<usize> {
+        turso_assert!(
+            matches!(
+                self.state,
+                HashTableState::Building | HashTableState::Probing
+            ) && !self.has_spilled(),
+            "shrink_to_fit requires an in-memory table",
+            { "state": format!("{:?}", self.state) }
+        );
+        let before = self.bucket_alloc_bytes;
+        let can_rehash = !(self.track_matched && self.state == HashTableState::Probing);
+        let floor = self.initial_buckets.max(1);
+        let mut bucket_count = self.buckets.len();
+        // Buckets that stay non-empty when halving is at most the current count, so
+        // this estimate is conservative.
+        let non_empty = self
+            .buckets
+            .iter()
+            .filter(|b| !b.entries.is_empty())
+            .count();
+        while can_rehash
+            && bucket_count / 2 >= floor
+            && non_empty * 4 < bucket_count
+            && self.num_entries as f64 <= (bucket_count / 2) as f64 * self.max_load_factor
+        {
+            bucket_count /= 2;
+        }
+        if bucket_count != self.buckets.len() {
+            self.rehash_buckets(bucket_count)?;
+            self.current_probe_keys = None;
+            self.current_probe_hash = None;
+            self.probe_bucket_idx = 0;
+            self.probe_entry_idx = 0;
+        }
+        self.shrink_allocations();
+        Ok(before.saturating_sub(self.bucket_alloc_bytes))
+    }
+
+    /// Shrink the bucket, entry and matched bit vectors to their lengths.
+    fn shrink_allocations(&mut self) {
+        self.buckets.shrink_to_fit();
+        for bucket in self.buckets.iter_mut() {
+            bucket.entries.shrink_to_fit();
+        }
+        for bits in self.matched_bits.iter_mut() {
+            bits.shrink_to_fit();
+        }
+        self.update_bucket_alloc_bytes();
+    }
+
+    /// Freeze a built table into a [FrozenHashTable] for probing from many threads at
+    /// once. Only in-memory tables without matched-entry tracking can be frozen, as
+    /// spilled partitions and matched bits need `&mut self` to load and update.
+    pub fn freeze(mut self) -> FrozenHashTable {
+        turso_assert!(
+            self.state == HashTableState::Probing,
+            "Cannot freeze hash table before finalize_build",
+            { "state": format!("{:?}", self.state) }
+        );
+        turso_assert!(!self.has_spilled(), "Cannot freeze a spilled hash table");
+        turso_assert!(
+            !self.track_matched,
+            "Cannot freeze a hash table that tracks matched entries"
+        );
+        FrozenHashTable {
+            buckets: std::mem::take(&mut self.buckets),
+            num_entries: self.num_entries,
+            collations: std::mem::take(&mut self.collations),
+            null_safe: self.null_safe,
+            seed: self.seed,
+        }
+    }
+
     /// Approximate memory used by a partition's buckets.
     fn partition_bucket_mem(buckets: &[HashBucket]) -> usize {
         buckets.iter().map(|b| b.size_bytes()).sum()
@@ -2634,7 +4045,7 @@ impl HashTable {
     }
 
     fn evict_partitions_to_fit(&mut self, incoming_mem: usize, protect_idx: usize) {
-        while self.mem_used + self.loaded_partitions_mem + incoming_mem > self.mem_budget {
+        while self.allocated_mem() + self.loaded_partitions_mem + incoming_mem > self.mem_budget {
             let Some(victim_idx) = self.next_evictable(protect_idx) else {
                 break;
             };
@@ -2698,23 +4109,22 @@ impl HashTable {
             .spill_state
             .as_ref()
             .expect("buffer_probe_row requires build-side spill state");
-        let partitioning = spill_state.partitioning;
 
         // Lazily initialize probe spill state on first call
         if self.probe_spill_state.is_none() {
             self.probe_spill_state = Some(ProbeSpillState::new(
                 &self.io,
                 self.temp_store,
-                partitioning,
+                spill_state.partitioning,
+                spill_state.partition_buffers.len(),
                 self.mem_budget / 2,
             )?);
         }
 
-        let key_refs: Vec<ValueRef> = key_values.iter().map(|v| v.as_ref()).try_collect()?;
-        let hash = hash_join_key(&key_refs, &self.collations);
-        let partition_idx = partitioning.index(hash);
+        let hash = hash_join_key_owned(&key_values, &self.collations, self.seed);
+        let partition_idx = spill_state.partition_for_hash(hash);
 
-        let entry = HashEntry::new(hash, key_values, probe_rowid);
+        let entry = HashEntry::new(hash, key_values.into(), probe_rowid);
         let entry_size = entry.size_bytes();
 
         let probe_state = self
@@ -2862,7 +4272,7 @@ impl HashTable {
         }
 
         // Collect partition indices that need flushing
-        let partition_count = probe_state.partitioning.count;
+        let partition_count = probe_state.partition_buffers.len();
         let mut flush_targets = vec![];
         for partition_idx in 0..partition_count {
             if probe_state.partition_buffers[partition_idx].is_empty() {
@@ -3055,6 +4465,7 @@ impl HashTable {
         }
         // Also free the main buckets
         self.buckets.clear();
+        self.update_bucket_alloc_bytes();
         self.loaded_partitions_lru.borrow_mut().clear();
         self.loaded_partitions_mem = 0;
     }
@@ -3131,18 +4542,18 @@ impl HashTable {
                 }
 
                 if matches!(io_state, SpillIOState::WaitingForRead) {
-                    GraceProbeChunkAction::WaitingForIO
+                    ChunkReadAction::WaitingForIO
                 } else if matches!(io_state, SpillIOState::ReadComplete) {
-                    GraceProbeChunkAction::ParseChunk { partition_idx }
+                    ChunkReadAction::ParseChunk { partition_idx }
                 } else {
                     match spilled.current_chunk() {
                         Some(chunk) if chunk.size_bytes == 0 => {
                             spilled.current_chunk_idx += 1;
-                            GraceProbeChunkAction::Restart
+                            ChunkReadAction::Restart
                         }
                         Some(chunk) => {
                             spilled.io_state.set(SpillIOState::WaitingForRead);
-                            GraceProbeChunkAction::LoadChunk {
+                            ChunkReadAction::LoadChunk {
                                 read_size: chunk.size_bytes,
                                 file_offset: chunk.file_offset,
                                 io_state: spilled.io_state.clone(),
@@ -3150,19 +4561,19 @@ impl HashTable {
                                 read_buffer_ref: spilled.read_buffer.clone(),
                             }
                         }
-                        None => GraceProbeChunkAction::NoMoreChunks,
+                        None => ChunkReadAction::NoMoreChunks,
                     }
                 }
             };
 
             match action {
-                GraceProbeChunkAction::WaitingForIO => {
+                ChunkReadAction::WaitingForIO => {
                     io_yield_one!(Completion::new_yield());
                 }
-                GraceProbeChunkAction::ParseChunk { partition_idx } => {
+                ChunkReadAction::ParseChunk { partition_idx } => {
                     return Ok(IOResult::Done(self.parse_grace_probe_chunk(partition_idx)?));
                 }
-                GraceProbeChunkAction::LoadChunk {
+                ChunkReadAction::LoadChunk {
                     read_size,
                     file_offset,
                     io_state,
@@ -3197,8 +4608,8 @@ impl HashTable {
                         io_yield_one!(c);
                     }
                 }
-                GraceProbeChunkAction::Restart => continue,
-                GraceProbeChunkAction::NoMoreChunks => return Ok(IOResult::Done(false)),
+                ChunkReadAction::Restart => continue,
+                ChunkReadAction::NoMoreChunks => return Ok(IOResult::Done(false)),
             }
         }
     }
@@ -3268,108 +4679,431 @@ impl HashTable {
     pub fn close(&mut self) {
         self.state = HashTableState::Closed;
         self.buckets.clear();
+        self.deferred = vec![];
         self.num_entries = 0;
         self.mem_used = 0;
+        self.update_bucket_alloc_bytes();
         self.loaded_partitions_lru.borrow_mut().clear();
         self.loaded_partitions_mem = 0;
+        if let Some(bloom_filter) = self.bloom_filter.as_mut() {
+            bloom_filter.clear();
+        }
+        // Dropping the spill state drops the temp file, which removes its temp dir.
         let _ = self.spill_state.take();
         self.probe_spill_state = None;
         self.grace_state = None;
     }
 }
 
-#[cfg(test)]
-mod hashtests {
-    use super::*;
-    use crate::alloc::vec;
-    use crate::io::Buffer;
-    use crate::MemoryIO;
-
-    #[test]
-    fn test_hash_table_rejects_custom_collations() {
-        let io = Arc::new(MemoryIO::new());
-        let config = HashTableConfig {
-            collations: vec![CollationSeq::custom("hash_table_custom")],
-            ..Default::default()
-        };
-        let err = match HashTable::new(config, io) {
-            Ok(_) => panic!("custom-collated hash table should be rejected"),
-            Err(err) => err,
-        };
-        assert!(err
-            .to_string()
-            .contains("custom collations are not supported by hash tables"));
+impl Drop for HashTable {
+    fn drop(&mut self) {
+        // close() is idempotent, so a table closed before being dropped is fine.
+        self.close();
     }
+}
 
-    #[test]
-    fn test_hash_function_consistency() {
-        // Test that the same keys produce the same hash
-        let keys1 = vec![
-            ValueRef::from_i64(42),
-            ValueRef::Text(crate::types::TextRef::new(
-                "hello",
-                crate::types::TextSubtype::Text,
-            )),
-        ];
-        let keys2 = vec![
-            ValueRef::from_i64(42),
-            ValueRef::Text(crate::types::TextRef::new(
-                "hello",
-                crate::types::TextSubtype::Text,
-            )),
-        ];
-        let keys3 = vec![
-            ValueRef::from_i64(43),
-            ValueRef::Text(crate::types::TextRef::new(
-                "hello",
-                crate::types::TextSubtype::Text,
-            )),
-        ];
+/// Iterator over the entries matching a set of probe keys, see [HashTable::matches].
+pub struct MatchIter<'a> {
+    /// Entries of the bucket the probe keys hash to.
+    entries: &'a [HashEntry],
+    /// Index in `entries` of the next entry to check.
+    next_idx: usize,
+    hash: u64,
+    keys: &'a [Value],
+    collations: &'a [CollationSeq],
+    null_safe: bool,
+    /// Stop after the first match, for [JoinMode::Semi] and [JoinMode::Anti].
+    first_only: bool,
+}
 
-        let collations = vec![CollationSeq::Binary, CollationSeq::Binary];
-        let hash1 = hash_join_key(&keys1, &collations);
-        let hash2 = hash_join_key(&keys2, &collations);
-        let hash3 = hash_join_key(&keys3, &collations);
+impl<'a> Iterator for MatchIter<'a> {
+    type Item = &'a HashEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(entry) = self.entries.get(self.next_idx) {
+            self.next_idx += 1;
+            if entry.hash == self.hash
+                && keys_equal(
+                    &entry.key_values,
+                    self.keys,
+                    self.collations,
+                    self.null_safe,
+                )
+            {
+                if self.first_only {
+                    self.entries = &[];
+                }
+                return Some(entry);
+            }
+        }
+        None
+    }
+}
 
-        assert_eq!(hash1, hash2);
-        assert_ne!(hash1, hash3);
+/// A built [HashTable] frozen by [HashTable::freeze]. It has no probe cursor, so
+/// lookups take `&self` and may run from many threads concurrently.
+pub struct FrozenHashTable {
+    buckets: Vec<HashBucket>,
+    num_entries: usize,
+    collations: Vec<CollationSeq>,
+    /// Whether NULL keys match NULL, as in [HashTableConfig::null_safe].
+    null_safe: bool,
+    /// Seed of the join key hash, as in [HashTableConfig::seed].
+    seed: u64,
+}
+
+impl FrozenHashTable {
+    /// Number of entries in the table.
+    pub fn num_entries(&self) -> usize {
+        self.num_entries
+    }
+
+    /// Entries whose join keys equal `keys`, in insertion order. NULL keys match nothing
+    /// unless the table is null-safe.
+    pub fn matches<'a>(
+        &'a self,
+        keys: &'a [ValueRef<'a>],
+    ) -> impl Iterator<Item = &'a HashEntry> + 'a {
+        let hash = hash_join_key(keys, &self.collations, self.seed);
+        let bucket = ((self.null_safe || !has_null_key_ref(keys)) && !self.buckets.is_empty())
+            .then(|| &self.buckets[(hash as usize) % self.buckets.len()]);
+        bucket
+            .into_iter()
+            .flat_map(|bucket| bucket.entries.iter())
+            .filter(move |entry| {
+                entry.hash == hash
+                    && keys_equal(&entry.key_values, keys, &self.collations, self.null_safe)
+            })
     }
 
-    #[test]
-    fn test_hash_function_numeric_equivalence() {
-        let collations = vec![CollationSeq::Binary];
+    /// A probe cursor over the table. The table can be shared, e.g. behind an `Arc`,
+    /// by threads each probing through their own cursor.
+    pub fn cursor(&self) -> ProbeCursor<'_> {
+        ProbeCursor {
+            table: self,
+            keys: vec![],
+            hash: 0,
+            entries: &[],
+            next_idx: 0,
+        }
+    }
+}
 
-        // Zero variants should hash identically
-        let h_zero = hash_join_key(&[ValueRef::from_f64(0.0)], &collations);
-        let h_neg_zero = hash_join_key(&[ValueRef::from_f64(-0.0)], &collations);
-        let h_int_zero = hash_join_key(&[ValueRef::from_i64(0)], &collations);
-        assert_eq!(h_zero, h_neg_zero);
-        assert_eq!(h_zero, h_int_zero);
+/// Probe cursor over a [FrozenHashTable], from [FrozenHashTable::cursor]. It steps
+/// through matches like [HashTable::probe] and [HashTable::next_match], but keeps its
+/// position itself rather than in the table.
+pub struct ProbeCursor<'a> {
+    table: &'a FrozenHashTable,
+    /// Keys of the current probe.
+    keys: Vec<Value>,
+    hash: u64,
+    /// Entries of the bucket the current keys hash to, empty if nothing can match.
+    entries: &'a [HashEntry],
+    /// Index in `entries` of the next entry to check.
+    next_idx: usize,
+}
 
-        // Integer/float representations of the same numeric value should match
-        let h_ten_int = hash_join_key(&[ValueRef::from_i64(10)], &collations);
-        let h_ten_float = hash_join_key(&[ValueRef::from_f64(10.0)], &collations);
-        assert_eq!(h_ten_int, h_ten_float);
+impl<'a> ProbeCursor<'a> {
+    /// Start probing for `keys` and return the first match. NULL keys match nothing
+    /// unless the table is null-safe.
+    pub fn probe(&mut self, keys: Vec<Value>) -> Option<&'a HashEntry> {
+        let table = self.table;
+        self.hash = hash_join_key_owned(&keys, &table.collations, table.seed);
+        self.entries = if (table.null_safe || !has_null_key(&keys)) && !table.buckets.is_empty() {
+            &table.buckets[(self.hash as usize) % table.buckets.len()].entries
+        } else {
+            &[]
+        };
+        self.keys = keys;
+        self.next_idx = 0;
+        self.next_match()
+    }
+
+    /// Next match for the keys of the last [ProbeCursor::probe].
+    pub fn next_match(&mut self) -> Option<&'a HashEntry> {
+        while let Some(entry) = self.entries.get(self.next_idx) {
+            self.next_idx += 1;
+            if entry.hash == self.hash
+                && keys_equal(
+                    &entry.key_values,
+                    &self.keys,
+                    &self.table.collations,
+                    self.table.null_safe,
+                )
+            {
+                return Some(entry);
+            }
+        }
+        None
+    }
+}
 
-        let h_neg_ten_int = hash_join_key(&[ValueRef::from_i64(-10)], &collations);
-        let h_neg_ten_float = hash_join_key(&[ValueRef::from_f64(-10.0)], &collations);
-        assert_eq!(h_neg_ten_int, h_neg_ten_float);
+/// A row produced by [GraceHashJoin].
+#[derive(Debug, Clone, PartialEq)]
+pub struct JoinedRow {
+    pub build_rowid: i64,
+    pub probe_rowid: i64,
+    /// Payload stored with the build row.
+    pub build_payload: Vec<Value>,
+}
 
-        // Positive/negative values should still differ
-        assert_ne!(h_ten_int, h_neg_ten_int);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GraceJoinPhase {
+    Build,
+    Probe,
+    /// Loading the next spilled build partition and its buffered probe rows.
+    LoadPartition,
+    /// Joining the buffered probe rows of the loaded partition.
+    JoinPartition,
+    Done,
+}
+
+/// Streaming driver for an inner equi-join that may not fit in memory, independent
+/// of the VDBE. Build rows go into a [HashTable], which partitions them and spills
+/// partitions to temp files once `mem_budget` is exceeded. Probe rows whose build
+/// partition is in memory are joined right away, the others are written to the
+/// matching probe partition. Once the probe input is exhausted, spilled partitions
+/// are joined one at a time, see [GraceHashJoin::current_partition].
+///
+/// Methods returning [IOResult::IO] must be called again with the same arguments
+/// once the IO has completed.
+pub struct GraceHashJoin {
+    table: HashTable,
+    phase: GraceJoinPhase,
+    /// Matches not yet returned by [GraceHashJoin::next_output].
+    output: VecDeque<JoinedRow>,
+    /// The last probe row was buffered and is waiting for a spill write.
+    probe_row_buffered: bool,
+}
+
+impl GraceHashJoin {
+    pub fn new(config: HashTableConfig, io: Arc<dyn IO>) -> Result<Self> {
+        turso_assert!(
+            config.join_mode == JoinMode::Inner && !config.track_matched,
+            "GraceHashJoin only drives inner joins"
+        );
+        Ok(Self {
+            table: HashTable::new(config, io)?,
+            phase: GraceJoinPhase::Build,
+            output: VecDeque::new(),
+            probe_row_buffered: false,
+        })
+    }
+
+    /// Add a row of the build input.
+    pub fn add_build_row(
+        &mut self,
+        key_values: Vec<Value>,
+        rowid: i64,
+        payload_values: Vec<Value>,
+    ) -> Result<IOResult<()>> {
+        turso_assert!(
+            self.phase == GraceJoinPhase::Build,
+            "build input already finished"
+        );
+        let pending = PendingHashInsert {
+            key_values: key_values.into(),
+            rowid,
+            payload_values,
+        };
+        match self.table.insert_pending(pending, None)? {
+            HashInsertResult::Done => Ok(IOResult::Done(())),
+            // The row was not inserted and is retried by the next call.
+            HashInsertResult::IO { io, .. } => Ok(IOResult::IO(io)),
+        }
+    }
+
+    /// End the build input, flushing the partitions that spilled.
+    pub fn finish_build(&mut self) -> Result<IOResult<()>> {
+        turso_assert!(
+            self.phase == GraceJoinPhase::Build,
+            "build input already finished"
+        );
+        return_if_io!(self.table.finalize_build(None));
+        self.phase = GraceJoinPhase::Probe;
+        Ok(IOResult::Done(()))
+    }
+
+    /// Add a row of the probe input. Its matches against in-memory build partitions
+    /// are available from [GraceHashJoin::next_output] right away, draining them
+    /// between probe rows keeps the output queue short.
+    pub fn add_probe_row(&mut self, key_values: Vec<Value>, rowid: i64) -> Result<IOResult<()>> {
+        turso_assert!(
+            self.phase == GraceJoinPhase::Probe,
+            "not accepting probe rows"
+        );
+        if std::mem::take(&mut self.probe_row_buffered) {
+            return Ok(IOResult::Done(()));
+        }
+        if !self.table.has_spilled() {
+            return self
+                .queue_matches(None, key_values, rowid)
+                .map(IOResult::Done);
+        }
+        let partition_idx = self.table.partition_for_keys(&key_values)?;
+        if self.table.is_partition_loaded(partition_idx) {
+            return self
+                .queue_matches(Some(partition_idx), key_values, rowid)
+                .map(IOResult::Done);
+        }
+        match self.table.buffer_probe_row(key_values, rowid, None)? {
+            IOResult::Done(()) => Ok(IOResult::Done(())),
+            IOResult::IO(io) => {
+                self.probe_row_buffered = true;
+                Ok(IOResult::IO(io))
+            }
+        }
+    }
+
+    /// End the probe input. Rows buffered for spilled partitions are joined by
+    /// [GraceHashJoin::next_output].
+    pub fn finish_probe(&mut self) -> Result<IOResult<()>> {
+        turso_assert!(
+            self.phase == GraceJoinPhase::Probe,
+            "not accepting probe rows"
+        );
+        return_if_io!(self.table.finalize_probe_spill(None));
+        self.phase = if self.table.grace_begin()? {
+            GraceJoinPhase::LoadPartition
+        } else {
+            GraceJoinPhase::Done
+        };
+        Ok(IOResult::Done(()))
+    }
+
+    /// Next joined row. Before [GraceHashJoin::finish_probe] this only returns the
+    /// matches of probe rows added so far, afterwards it joins the spilled partitions
+    /// and `None` means the join is complete.
+    pub fn next_output(&mut self) -> Result<IOResult<Option<JoinedRow>>> {
+        loop {
+            if let Some(row) = self.output.pop_front() {
+                return Ok(IOResult::Done(Some(row)));
+            }
+            match self.phase {
+                GraceJoinPhase::Build | GraceJoinPhase::Probe | GraceJoinPhase::Done => {
+                    return Ok(IOResult::Done(None));
+                }
+                GraceJoinPhase::LoadPartition => {
+                    self.phase = if return_if_io!(self.table.grace_load_current_partition(None)) {
+                        GraceJoinPhase::JoinPartition
+                    } else {
+                        GraceJoinPhase::Done
+                    };
+                }
+                GraceJoinPhase::JoinPartition => {
+                    match return_if_io!(self.table.grace_next_probe_entry()) {
+                        Some(entry) => {
+                            let key_values: Vec<Value> =
+                                entry.key_values.into_iter().try_collect()?;
+                            let partition_idx = self.table.partition_for_keys(&key_values)?;
+                            self.queue_matches(Some(partition_idx), key_values, entry.probe_rowid)?;
+                        }
+                        None => {
+                            self.phase = if self.table.grace_advance_partition() {
+                                GraceJoinPhase::LoadPartition
+                            } else {
+                                GraceJoinPhase::Done
+                            };
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// The spilled partition whose rows [GraceHashJoin::next_output] is joining, or
+    /// `None` outside of partition-by-partition processing. Rows of one partition are
+    /// all returned before the next partition is loaded.
+    pub fn current_partition(&self) -> Option<usize> {
+        match self.phase {
+            GraceJoinPhase::LoadPartition | GraceJoinPhase::JoinPartition => {
+                self.table.grace_state.as_ref()?.current_partition_idx()
+            }
+            _ => None,
+        }
+    }
+
+    /// Number of build partitions, 0 if the build input fit in memory.
+    pub fn num_partitions(&self) -> usize {
+        self.table.num_partitions()
+    }
+
+    pub fn stats(&self) -> HashTableStats {
+        self.table.stats()
+    }
+
+    /// Queue every build row matching `key_values`, probing `partition_idx` if the
+    /// table has spilled.
+    fn queue_matches(
+        &mut self,
+        partition_idx: Option<usize>,
+        key_values: Vec<Value>,
+        probe_rowid: i64,
+    ) -> Result<()> {
+        let to_row = |entry: &HashEntry| JoinedRow {
+            build_rowid: entry.rowid,
+            probe_rowid,
+            build_payload: entry.payload_values.clone(),
+        };
+        let first = match partition_idx {
+            Some(partition_idx) => self
+                .table
+                .probe_partition(partition_idx, &key_values, None)?
+                .map(to_row),
+            None => self.table.probe(key_values, None)?.map(to_row),
+        };
+        if let Some(row) = first {
+            self.output.push_back(row);
+            while let Some(entry) = self.table.next_match()? {
+                self.output.push_back(to_row(entry));
+            }
+        }
+        Ok(())
     }
+}
+
+#[cfg(test)]
+mod hashtests {
+    use super::*;
+    use crate::alloc::vec;
+    use crate::io::Buffer;
+    use crate::MemoryIO;
+    use quickcheck_macros::quickcheck;
 
     #[test]
-    fn test_keys_equal() {
-        let key1 = vec![Value::from_i64(42), Value::Text("hello".to_string().into())];
-        let key2 = vec![
+    fn test_hash_table_rejects_custom_collations() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            collations: vec![CollationSeq::custom("hash_table_custom")],
+            ..Default::default()
+        };
+        let err = match HashTable::new(config, io) {
+            Ok(_) => panic!("custom-collated hash table should be rejected"),
+            Err(err) => err,
+        };
+        assert!(err
+            .to_string()
+            .contains("custom collations are not supported by hash tables"));
+    }
+
+    #[test]
+    fn test_hash_function_consistency() {
+        // Test that the same keys produce the same hash
+        let keys1 = vec![
             ValueRef::from_i64(42),
             ValueRef::Text(crate::types::TextRef::new(
                 "hello",
                 crate::types::TextSubtype::Text,
             )),
         ];
-        let key3 = vec![
+        let keys2 = vec![
+            ValueRef::from_i64(42),
+            ValueRef::Text(crate::types::TextRef::new(
+                "hello",
+                crate::types::TextSubtype::Text,
+            )),
+        ];
+        let keys3 = vec![
             ValueRef::from_i64(43),
             ValueRef::Text(crate::types::TextRef::new(
                 "hello",
@@ -3377,124 +5111,1594 @@ mod hashtests {
             )),
         ];
 
-        let collations = vec![CollationSeq::Binary, CollationSeq::Binary];
-        assert!(keys_equal(&key1, &key2, &collations));
-        assert!(!keys_equal(&key1, &key3, &collations));
+        let collations = vec![CollationSeq::Binary, CollationSeq::Binary];
+        let hash1 = hash_join_key(&keys1, &collations, DEFAULT_SEED);
+        let hash2 = hash_join_key(&keys2, &collations, DEFAULT_SEED);
+        let hash3 = hash_join_key(&keys3, &collations, DEFAULT_SEED);
+
+        assert_eq!(hash1, hash2);
+        assert_ne!(hash1, hash3);
+    }
+
+    #[test]
+    fn test_hash_function_numeric_equivalence() {
+        let collations = vec![CollationSeq::Binary];
+
+        // Zero variants should hash identically
+        let h_zero = hash_join_key(&[ValueRef::from_f64(0.0)], &collations, DEFAULT_SEED);
+        let h_neg_zero = hash_join_key(&[ValueRef::from_f64(-0.0)], &collations, DEFAULT_SEED);
+        let h_int_zero = hash_join_key(&[ValueRef::from_i64(0)], &collations, DEFAULT_SEED);
+        assert_eq!(h_zero, h_neg_zero);
+        assert_eq!(h_zero, h_int_zero);
+
+        // Integer/float representations of the same numeric value should match
+        let h_ten_int = hash_join_key(&[ValueRef::from_i64(10)], &collations, DEFAULT_SEED);
+        let h_ten_float = hash_join_key(&[ValueRef::from_f64(10.0)], &collations, DEFAULT_SEED);
+        assert_eq!(h_ten_int, h_ten_float);
+
+        let h_neg_ten_int = hash_join_key(&[ValueRef::from_i64(-10)], &collations, DEFAULT_SEED);
+        let h_neg_ten_float =
+            hash_join_key(&[ValueRef::from_f64(-10.0)], &collations, DEFAULT_SEED);
+        assert_eq!(h_neg_ten_int, h_neg_ten_float);
+
+        // Positive/negative values should still differ
+        assert_ne!(h_ten_int, h_neg_ten_int);
+    }
+
+    #[test]
+    fn test_recommend_strategy() {
+        const MB: usize = 1024 * 1024;
+        let budget = 64 * MB;
+
+        // 100k rows of 100 bytes is ~10MB, well within budget
+        assert_eq!(
+            recommend_strategy(100_000, 100, budget),
+            JoinStrategy::HashInMemory
+        );
+        // Exactly filling the budget still fits
+        assert_eq!(
+            recommend_strategy(64, MB, budget),
+            JoinStrategy::HashInMemory
+        );
+        // An empty build side always fits
+        assert_eq!(
+            recommend_strategy(0, 100, budget),
+            JoinStrategy::HashInMemory
+        );
+
+        // One byte over the budget spills
+        assert_eq!(
+            recommend_strategy(64 * MB + 1, 1, budget),
+            JoinStrategy::GraceHash
+        );
+        // 10M rows of 100 bytes is ~1GB, ~8MB per partition
+        assert_eq!(
+            recommend_strategy(10_000_000, 100, budget),
+            JoinStrategy::GraceHash
+        );
+        // Largest build whose partitions each still fit the budget
+        assert_eq!(
+            recommend_strategy(MAX_PARTITIONS * 64, MB, budget),
+            JoinStrategy::GraceHash
+        );
+
+        // One row more and a partition overflows the budget
+        assert_eq!(
+            recommend_strategy(MAX_PARTITIONS * 64 + 1, MB, budget),
+            JoinStrategy::SortMerge
+        );
+        // 1B rows of 1KB is ~1TB
+        assert_eq!(
+            recommend_strategy(1_000_000_000, 1024, budget),
+            JoinStrategy::SortMerge
+        );
+        // Overflowing the size estimate must not wrap around to "fits"
+        assert_eq!(
+            recommend_strategy(usize::MAX, usize::MAX, budget),
+            JoinStrategy::SortMerge
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_numeric() {
+        let canon = |v: f64| canonicalize_numeric(ValueRef::from_f64(v));
+
+        // Integral REALs become INTEGERs, including negative zero
+        assert!(matches!(canon(5.0), ValueRef::Numeric(Numeric::Integer(5))));
+        assert!(matches!(
+            canon(-5.0),
+            ValueRef::Numeric(Numeric::Integer(-5))
+        ));
+        assert!(matches!(
+            canon(-0.0),
+            ValueRef::Numeric(Numeric::Integer(0))
+        ));
+
+        // Fractional REALs stay REAL
+        assert!(matches!(canon(5.5), ValueRef::Numeric(Numeric::Float(_))));
+
+        // -2^63 is i64::MIN, 2^63 and beyond are outside the i64 range
+        assert!(matches!(
+            canon(-9223372036854775808.0),
+            ValueRef::Numeric(Numeric::Integer(i64::MIN))
+        ));
+        assert!(matches!(
+            canon(9223372036854775808.0),
+            ValueRef::Numeric(Numeric::Float(_))
+        ));
+        assert!(matches!(canon(1e300), ValueRef::Numeric(Numeric::Float(_))));
+        assert!(matches!(
+            canon(f64::INFINITY),
+            ValueRef::Numeric(Numeric::Float(_))
+        ));
+
+        // INTEGERs are never touched, even those not exactly representable as f64
+        let big = (1i64 << 53) + 1;
+        assert!(matches!(
+            canonicalize_numeric(ValueRef::from_i64(big)),
+            ValueRef::Numeric(Numeric::Integer(i)) if i == big
+        ));
+        assert!(matches!(
+            canonicalize_numeric(ValueRef::from_i64(i64::MAX)),
+            ValueRef::Numeric(Numeric::Integer(i64::MAX))
+        ));
+
+        // 2^53 + 1 rounds to 2^53 as a REAL, but must not equal the REAL 2^53
+        let collations = vec![CollationSeq::Binary];
+        let h_big = hash_join_key(&[ValueRef::from_i64(big)], &collations, DEFAULT_SEED);
+        let h_big_float =
+            hash_join_key(&[ValueRef::from_f64(big as f64)], &collations, DEFAULT_SEED);
+        assert_ne!(h_big, h_big_float);
+        assert!(!values_equal(
+            ValueRef::from_i64(big),
+            ValueRef::from_f64(big as f64),
+            CollationSeq::Binary
+        ));
+        assert!(values_equal(
+            ValueRef::from_i64(big - 1),
+            ValueRef::from_f64(big as f64),
+            CollationSeq::Binary
+        ));
+    }
+
+    #[test]
+    fn test_hash_table_cross_type_numeric_keys() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 1024 * 1024,
+            num_keys: 1,
+            collations: vec![CollationSeq::Binary],
+            temp_store: crate::TempStore::Default,
+            track_matched: false,
+            partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+
+        // Build side has INTEGER keys and one integral REAL key
+        let _ = ht
+            .insert(vec![Value::from_i64(5)], 1, vec![], None)
+            .unwrap();
+        let _ = ht
+            .insert(vec![Value::from_f64(0.0)], 2, vec![], None)
+            .unwrap();
+        let _ = ht
+            .insert(vec![Value::from_f64(7.5)], 3, vec![], None)
+            .unwrap();
+        let _ = ht.finalize_build(None);
+
+        // Probe side uses the other numeric type
+        let entry = ht.probe(vec![Value::from_f64(5.0)], None).unwrap();
+        assert_eq!(entry.map(|e| e.rowid), Some(1));
+        let entry = ht.probe(vec![Value::from_f64(-0.0)], None).unwrap();
+        assert_eq!(entry.map(|e| e.rowid), Some(2));
+        let entry = ht.probe(vec![Value::from_i64(0)], None).unwrap();
+        assert_eq!(entry.map(|e| e.rowid), Some(2));
+        let entry = ht.probe(vec![Value::from_f64(7.5)], None).unwrap();
+        assert_eq!(entry.map(|e| e.rowid), Some(3));
+        assert!(ht.probe(vec![Value::from_i64(7)], None).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_integer_and_real_keys_compare_numerically() {
+        let int_one = ValueRef::from_i64(1);
+        for (real, equal) in [(1.0, true), (1.5, false)] {
+            let real = ValueRef::from_f64(real);
+            assert_eq!(values_equal(int_one, real, CollationSeq::Binary), equal);
+            assert_eq!(values_equal(real, int_one, CollationSeq::Binary), equal);
+            assert_eq!(
+                hash_join_key(&[int_one], &[], DEFAULT_SEED)
+                    == hash_join_key(&[real], &[], DEFAULT_SEED),
+                equal
+            );
+        }
+    }
+
+    #[quickcheck]
+    /// Numeric keys that compare equal must hash alike, or a probe would look for its
+    /// match in the wrong bucket.
+    fn prop_equal_numeric_keys_hash_alike(i: i64, f: f64) -> bool {
+        let values = [
+            ValueRef::from_i64(i),
+            ValueRef::from_f64(i as f64),
+            ValueRef::from_f64(f),
+            ValueRef::from_f64(-f),
+            ValueRef::from_f64(f.trunc()),
+            ValueRef::from_i64(f as i64),
+            ValueRef::from_i64(0),
+            ValueRef::from_f64(0.0),
+            ValueRef::from_f64(-0.0),
+            ValueRef::from_i64(i64::MIN),
+            ValueRef::from_i64(i64::MAX),
+            ValueRef::from_f64(-9223372036854775808.0),
+            ValueRef::from_f64(9223372036854775808.0),
+        ];
+        values.iter().all(|&a| {
+            values.iter().all(|&b| {
+                !values_equal(a, b, CollationSeq::Binary)
+                    || hash_join_key(&[a], &[], DEFAULT_SEED)
+                        == hash_join_key(&[b], &[], DEFAULT_SEED)
+            })
+        })
+    }
+
+    #[test]
+    fn test_float_join_keys_are_exact() {
+        let eq = |a: f64, b: f64| {
+            values_equal(
+                ValueRef::from_f64(a),
+                ValueRef::from_f64(b),
+                CollationSeq::Binary,
+            )
+        };
+        assert!(eq(0.0, -0.0));
+        assert!(eq(0.5, 0.5));
+        assert!(!eq(0.1 + 0.2, 0.3));
+        assert!(!eq(1.0, 1.0 + f64::EPSILON));
+        assert!(!eq(f64::MIN_POSITIVE, 0.0));
+        // NaN becomes NULL, and NULL = NULL is not true
+        assert!(!eq(f64::NAN, f64::NAN));
+        assert!(matches!(Value::from_f64(f64::NAN), Value::Null));
+
+        let io = Arc::new(MemoryIO::new());
+        let mut ht = HashTable::new(HashTableConfig::default(), io).unwrap();
+        let _ = ht
+            .insert(vec![Value::from_f64(-0.0)], 1, vec![], None)
+            .unwrap();
+        let _ = ht
+            .insert(vec![Value::from_f64(f64::NAN)], 2, vec![], None)
+            .unwrap();
+        let _ = ht
+            .insert(vec![Value::from_f64(0.1 + 0.2)], 3, vec![], None)
+            .unwrap();
+        let _ = ht.finalize_build(None).unwrap();
+
+        // The NaN key is never stored
+        assert_eq!(ht.stats().num_entries, 2);
+        let entry = ht.probe(vec![Value::from_f64(0.0)], None).unwrap();
+        assert_eq!(entry.map(|e| e.rowid), Some(1));
+        assert!(ht
+            .probe(vec![Value::from_f64(f64::NAN)], None)
+            .unwrap()
+            .is_none());
+        assert!(ht
+            .probe(vec![Value::from_f64(0.3)], None)
+            .unwrap()
+            .is_none());
+        let entry = ht.probe(vec![Value::from_f64(0.1 + 0.2)], None).unwrap();
+        assert_eq!(entry.map(|e| e.rowid), Some(3));
+    }
+
+    #[test]
+    fn test_mem_accounting_includes_bucket_allocations() {
+        let io = Arc::new(MemoryIO::new());
+        let bucket_vec_bytes = 1024 * std::mem::size_of::<HashBucket>();
+        let config = |mem_budget| HashTableConfig {
+            initial_buckets: 1024,
+            mem_budget,
+            ..Default::default()
+        };
+
+        // The bucket vector is allocated before any entry is inserted
+        let mut ht = HashTable::new(config(usize::MAX), io.clone()).unwrap();
+        assert_eq!(ht.stats().mem_used, 0);
+        assert!(ht.stats().allocated_mem >= bucket_vec_bytes);
+        for i in 0..100 {
+            let _ = ht
+                .insert(vec![Value::from_i64(i)], i, vec![], None)
+                .unwrap();
+        }
+        let stats = ht.stats();
+        assert_eq!(stats.mem_used, 100 * 24);
+        assert!(
+            stats.allocated_mem
+                >= stats.mem_used + bucket_vec_bytes + 100 * std::mem::size_of::<HashEntry>()
+        );
+
+        // Rows that fit the budget by their logical size alone spill once the
+        // allocations holding them are counted
+        let mut ht = HashTable::new(config(bucket_vec_bytes + 200 * 24), io).unwrap();
+        for i in 0..100 {
+            let _ = ht
+                .insert(vec![Value::from_i64(i)], i, vec![], None)
+                .unwrap();
+        }
+        assert!(ht.has_spilled());
+    }
+
+    #[test]
+    fn test_default_seed_is_per_table() {
+        let build = || {
+            let io = Arc::new(MemoryIO::new());
+            let config = HashTableConfig {
+                mem_budget: 1024 * 1024,
+                ..Default::default()
+            };
+            let mut ht = HashTable::new(config, io).unwrap();
+            for i in 0..64 {
+                let _ = ht
+                    .insert(vec![Value::from_i64(i)], i, vec![], None)
+                    .unwrap();
+            }
+            let _ = ht.finalize_build(None).unwrap();
+            ht
+        };
+        let (mut ht1, mut ht2) = (build(), build());
+        assert_ne!(ht1.seed, ht2.seed);
+
+        let buckets = |ht: &HashTable| -> Vec<usize> {
+            (0..64)
+                .map(|i| {
+                    let positions = ht.match_positions(&[ValueRef::from_i64(i)]);
+                    assert_eq!(positions.len(), 1);
+                    positions[0].0
+                })
+                .collect()
+        };
+        // A key may share its bucket by chance, but not all 64 of them
+        assert_ne!(buckets(&ht1), buckets(&ht2));
+
+        // Build and probe of each table still agree
+        for ht in [&mut ht1, &mut ht2] {
+            let entry = ht.probe(vec![Value::from_i64(42)], None).unwrap();
+            assert_eq!(entry.map(|e| e.rowid), Some(42));
+        }
+    }
+
+    #[test]
+    fn test_keys_equal() {
+        let key1 = vec![Value::from_i64(42), Value::Text("hello".to_string().into())];
+        let key2 = vec![
+            ValueRef::from_i64(42),
+            ValueRef::Text(crate::types::TextRef::new(
+                "hello",
+                crate::types::TextSubtype::Text,
+            )),
+        ];
+        let key3 = vec![
+            ValueRef::from_i64(43),
+            ValueRef::Text(crate::types::TextRef::new(
+                "hello",
+                crate::types::TextSubtype::Text,
+            )),
+        ];
+
+        let collations = vec![CollationSeq::Binary, CollationSeq::Binary];
+        assert!(keys_equal(&key1, &key2, &collations, false));
+        assert!(!keys_equal(&key1, &key3, &collations, false));
+    }
+
+    #[test]
+    fn test_hash_table_basic() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 1024 * 1024,
+            num_keys: 1,
+            collations: vec![CollationSeq::Binary],
+            temp_store: crate::TempStore::Default,
+            track_matched: false,
+            partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+
+        // Insert some entries (late materialization - only store rowids)
+        let key1 = vec![Value::from_i64(1)];
+        let _ = ht.insert(key1.clone(), 100, vec![], None).unwrap();
+
+        let key2 = vec![Value::from_i64(2)];
+        let _ = ht.insert(key2.clone(), 200, vec![], None).unwrap();
+
+        let _ = ht.finalize_build(None);
+
+        // Probe for key1
+        let result = ht.probe(key1, None).unwrap();
+        assert!(result.is_some());
+        let entry1 = result.unwrap();
+        assert_eq!(entry1.key_values[0].as_ref(), ValueRef::from_i64(1));
+        assert_eq!(entry1.rowid, 100);
+
+        // Probe for key2
+        let result = ht.probe(key2, None).unwrap();
+        assert!(result.is_some());
+        let entry2 = result.unwrap();
+        assert_eq!(entry2.key_values[0].as_ref(), ValueRef::from_i64(2));
+        assert_eq!(entry2.rowid, 200);
+
+        // Probe for non-existent key
+        let result = ht.probe(vec![Value::from_i64(999)], None).unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_hash_table_insert_batch() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 64,
+            mem_budget: 1024 * 1024,
+            num_keys: 1,
+            collations: vec![CollationSeq::Binary],
+            temp_store: crate::TempStore::Default,
+            track_matched: false,
+            partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+
+        let mut batch: VecDeque<PendingHashInsert> = (0..1000)
+            .map(|i| PendingHashInsert {
+                key_values: vec![Value::from_i64(i)].into(),
+                rowid: i * 10,
+                payload_values: vec![],
+            })
+            .collect();
+        batch.push_back(PendingHashInsert {
+            key_values: vec![Value::Null].into(),
+            rowid: -1,
+            payload_values: vec![],
+        });
+        match ht.insert_batch(&mut batch, None).unwrap() {
+            IOResult::Done(()) => {}
+            IOResult::IO(_) => panic!("batch within budget must not spill"),
+        }
+        assert!(batch.is_empty());
+        assert!(!ht.has_spilled());
+
+        let _ = ht.finalize_build(None).unwrap();
+        for i in 0..1000 {
+            let entry = ht.probe(vec![Value::from_i64(i)], None).unwrap();
+            assert_eq!(entry.map(|e| e.rowid), Some(i * 10), "key {i}");
+        }
+        assert!(ht
+            .probe(vec![Value::from_i64(1000)], None)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_iter_and_into_entries() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+        for rowid in 0..100 {
+            let _ = ht
+                .insert(vec![Value::from_i64(rowid % 7)], rowid, vec![], None)
+                .unwrap();
+        }
+        let mut building: Vec<i64> = ht.iter_entries().map(|e| e.rowid).collect();
+        let _ = ht.finalize_build(None).unwrap();
+        let probing: Vec<i64> = ht.iter_entries().map(|e| e.rowid).collect();
+        assert_eq!(building, probing);
+        // Rows sharing a key keep their insertion order
+        let key_three: Vec<i64> = ht
+            .iter_entries()
+            .filter(|e| e.key_values[0] == Value::from_i64(3))
+            .map(|e| e.rowid)
+            .collect();
+        assert_eq!(key_three, (3..100).step_by(7).collect::<Vec<_>>());
+
+        let owned: Vec<i64> = ht.into_entries().unwrap().iter().map(|e| e.rowid).collect();
+        assert_eq!(owned, probing);
+        building.sort_unstable();
+        assert_eq!(building, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_shrink_to_fit_after_dropping_entries() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            max_load_factor: 1.0,
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+        for i in 0..1000 {
+            let _ = ht
+                .insert(vec![Value::from_i64(i)], i, vec![], None)
+                .unwrap();
+        }
+        let _ = ht.finalize_build(None).unwrap();
+        let bucket_count = ht.buckets.len();
+
+        // Drop 900 entries in place, leaving the buckets' capacity behind
+        for bucket in ht.buckets.iter_mut() {
+            bucket.entries.retain(|entry| entry.rowid < 100);
+        }
+        ht.num_entries = 100;
+        ht.mem_used = ht.buckets.iter().map(|b| b.size_bytes()).sum();
+
+        let before = ht.stats().allocated_mem;
+        let reclaimed = ht.shrink_to_fit().unwrap();
+        assert!(reclaimed > 0);
+        assert_eq!(ht.stats().allocated_mem, before - reclaimed);
+        assert!(ht.buckets.len() < bucket_count);
+        assert!(ht.buckets.len() >= 100);
+        for i in 0..1000 {
+            let entry = ht.probe(vec![Value::from_i64(i)], None).unwrap();
+            let expected = (i < 100).then_some(i);
+            assert_eq!(entry.map(|e| e.rowid), expected, "key {i}");
+        }
+    }
+
+    #[test]
+    fn test_clear_keeps_bucket_capacity() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            track_matched: true,
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+        let seed = ht.seed;
+        let build = |ht: &mut HashTable, offset: i64| {
+            for i in 0..1000 {
+                let _ = ht
+                    .insert(vec![Value::from_i64(i)], offset + i, vec![], None)
+                    .unwrap();
+            }
+            let _ = ht.finalize_build(None).unwrap();
+        };
+
+        build(&mut ht, 0);
+        let entry = ht.probe(vec![Value::from_i64(7)], None).unwrap();
+        assert_eq!(entry.map(|e| e.rowid), Some(7));
+        ht.mark_current_matched();
+        let buckets = ht.buckets.as_ptr();
+        let capacities: Vec<usize> = ht.buckets.iter().map(|b| b.entries.capacity()).collect();
+
+        ht.clear().unwrap();
+        assert_eq!(*ht.get_state(), HashTableState::Building);
+        assert_eq!(ht.num_entries, 0);
+        assert_eq!(ht.seed, seed);
+
+        // The same rows land in the same buckets, which already have room for them
+        build(&mut ht, 1000);
+        assert!(std::ptr::eq(buckets, ht.buckets.as_ptr()));
+        let rebuilt: Vec<usize> = ht.buckets.iter().map(|b| b.entries.capacity()).collect();
+        assert_eq!(capacities, rebuilt);
+
+        // Entries and matched bits are those of the second build only
+        let entry = ht.probe(vec![Value::from_i64(7)], None).unwrap();
+        assert_eq!(entry.map(|e| e.rowid), Some(1007));
+        assert!(ht.next_match().unwrap().is_none());
+        ht.begin_unmatched_scan();
+        let mut unmatched = 0;
+        while ht.next_unmatched().is_some() {
+            unmatched += 1;
+        }
+        assert_eq!(unmatched, 1000);
+    }
+
+    #[test]
+    fn test_hash_table_insert_batch_grows_buckets_once() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 1024 * 1024,
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config.clone(), io.clone()).unwrap();
+        let batch = |rows: std::ops::Range<i64>| -> VecDeque<PendingHashInsert> {
+            rows.map(|i| PendingHashInsert {
+                key_values: vec![Value::from_i64(i)].into(),
+                rowid: i,
+                payload_values: vec![],
+            })
+            .collect()
+        };
+
+        // Growing for the batch up front leaves nothing to grow while inserting it
+        ht.reserve_buckets(1000).unwrap();
+        let buckets = ht.buckets.as_ptr();
+        let bucket_count = ht.buckets.len();
+        let _ = ht.insert_batch(&mut batch(0..1000), None).unwrap();
+        assert!(std::ptr::eq(buckets, ht.buckets.as_ptr()));
+        assert!(ht.num_entries as f64 <= bucket_count as f64 * config.max_load_factor);
+
+        // Row by row ends up with the same bucket count
+        let mut row_by_row = HashTable::new(config, io).unwrap();
+        for i in 0..1000 {
+            let _ = row_by_row
+                .insert(vec![Value::from_i64(i)], i, vec![], None)
+                .unwrap();
+        }
+        assert_eq!(row_by_row.buckets.len(), bucket_count);
+
+        let _ = ht.finalize_build(None).unwrap();
+        for i in 0..1000 {
+            let entry = ht.probe(vec![Value::from_i64(i)], None).unwrap();
+            assert_eq!(entry.map(|e| e.rowid), Some(i), "key {i}");
+        }
+    }
+
+    #[test]
+    fn test_insert_batch_budget_counts_bucket_growth() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 1024 * 1024,
+            ..Default::default()
+        };
+        let ht = HashTable::new(config, io).unwrap();
+        // 1000 rows need 256 buckets at the default load factor, plus their entry slots.
+        let growth =
+            256 * std::mem::size_of::<HashBucket>() + 1000 * std::mem::size_of::<HashEntry>();
+        assert!(ht.projected_allocated_mem(1000, 0) >= ht.allocated_mem() + growth);
+        assert_eq!(ht.projected_allocated_mem(0, 0), ht.allocated_mem());
+    }
+
+    #[test]
+    fn test_shrink_on_finalize() {
+        let io = Arc::new(MemoryIO::new());
+        let build = |shrink_on_finalize| {
+            let config = HashTableConfig {
+                initial_buckets: 4,
+                shrink_on_finalize,
+                ..Default::default()
+            };
+            let mut ht = HashTable::new(config, io.clone()).unwrap();
+            for i in 0..1000 {
+                let _ = ht
+                    .insert(vec![Value::from_i64(i)], i, vec![], None)
+                    .unwrap();
+            }
+            let _ = ht.finalize_build(None).unwrap();
+            ht
+        };
+
+        let mut ht = build(true);
+        assert_eq!(ht.buckets.capacity(), ht.buckets.len());
+        for bucket in &ht.buckets {
+            assert_eq!(bucket.entries.capacity(), bucket.entries.len());
+        }
+        assert_eq!(
+            ht.stats().allocated_mem,
+            ht.stats().mem_used
+                + ht.buckets.len() * std::mem::size_of::<HashBucket>()
+                + 1000 * std::mem::size_of::<HashEntry>()
+        );
+        for i in 0..1000 {
+            let entry = ht.probe(vec![Value::from_i64(i)], None).unwrap();
+            assert_eq!(entry.map(|e| e.rowid), Some(i), "key {i}");
+        }
+
+        // Opting out keeps the capacity the entries grew into
+        let ht = build(false);
+        assert!(ht
+            .buckets
+            .iter()
+            .any(|b| b.entries.capacity() > b.entries.len()));
+    }
+
+    #[test]
+    fn test_hash_table_with_estimated_rows() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 1024 * 1024,
+            ..Default::default()
+        };
+        let mut ht = HashTable::with_estimated_rows(config.clone(), io.clone(), 1000).unwrap();
+        // 1000 / 4 rounded up to a power of two
+        assert_eq!(ht.buckets.len(), 256);
+        let buckets = ht.buckets.as_ptr();
+        for i in 0..1000 {
+            let _ = ht
+                .insert(vec![Value::from_i64(i)], i, vec![], None)
+                .unwrap();
+        }
+        assert!(std::ptr::eq(buckets, ht.buckets.as_ptr()));
+        assert_eq!(ht.buckets.len(), 256);
+        let _ = ht.finalize_build(None).unwrap();
+        for i in 0..1000 {
+            let entry = ht.probe(vec![Value::from_i64(i)], None).unwrap();
+            assert_eq!(entry.map(|e| e.rowid), Some(i), "key {i}");
+        }
+
+        // An underestimate still grows the buckets
+        let mut ht = HashTable::with_estimated_rows(config, io, 10).unwrap();
+        assert_eq!(ht.buckets.len(), 4);
+        for i in 0..1000 {
+            let _ = ht
+                .insert(vec![Value::from_i64(i)], i, vec![], None)
+                .unwrap();
+        }
+        assert_eq!(ht.buckets.len(), 256);
+    }
+
+    #[test]
+    fn test_hash_table_deferred_index() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 1024 * 1024,
+            deferred_index: true,
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config.clone(), io).unwrap();
+        let buckets = ht.buckets.as_ptr();
+        for i in 0..1000 {
+            let _ = ht
+                .insert(
+                    vec![Value::from_i64(i)],
+                    i,
+                    vec![Value::from_i64(i * 2)],
+                    None,
+                )
+                .unwrap();
+        }
+
+        // Nothing was hashed into the buckets, so they were never resized
+        assert!(std::ptr::eq(buckets, ht.buckets.as_ptr()));
+        assert_eq!(ht.buckets.len(), 4);
+        assert!(ht.non_empty_buckets.is_empty());
+        assert_eq!(ht.deferred.len(), 1000);
+        assert_eq!(ht.stats().num_entries, 1000);
+
+        // Finalize sizes the buckets once for the final count
+        let _ = ht.finalize_build(None).unwrap();
+        let bucket_count = ht.buckets.len();
+        assert!(1000.0 <= bucket_count as f64 * config.max_load_factor);
+        assert!(1000.0 > (bucket_count / 2) as f64 * config.max_load_factor);
+        assert!(ht.deferred.is_empty());
+        for i in 0..1000 {
+            let entry = ht.probe(vec![Value::from_i64(i)], None).unwrap();
+            let entry = entry.unwrap_or_else(|| panic!("key {i}"));
+            assert_eq!(entry.rowid, i);
+            assert_eq!(entry.payload_values, vec![Value::from_i64(i * 2)]);
+        }
+    }
+
+    #[test]
+    fn test_hash_table_insert_batch_spills_mid_batch() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            // very small budget so the batch crosses it
+            mem_budget: 1024,
+            num_keys: 1,
+            collations: vec![CollationSeq::Binary],
+            temp_store: crate::TempStore::Default,
+            track_matched: false,
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+
+        let mut batch: VecDeque<PendingHashInsert> = (0..1000)
+            .map(|i| PendingHashInsert {
+                key_values: vec![Value::from_i64(i)].into(),
+                rowid: i,
+                payload_values: vec![],
+            })
+            .collect();
+        // Retry with whatever is left until the whole batch has been consumed
+        while let IOResult::IO(_) = ht.insert_batch(&mut batch, None).unwrap() {}
+        assert!(batch.is_empty());
+
+        let _ = ht.finalize_build(None).unwrap();
+        assert!(ht.has_spilled(), "hash table should have spilled");
+        for i in 0..1000 {
+            let key = vec![Value::from_i64(i)];
+            let partition_idx = ht.partition_for_keys(&key).unwrap();
+            match ht.load_spilled_partition(partition_idx, None).unwrap() {
+                IOResult::Done(()) => {}
+                IOResult::IO(_) => panic!("test harness must drive IO completions here"),
+            }
+            let entry = ht.probe_partition(partition_idx, &key, None).unwrap();
+            assert_eq!(entry.map(|e| e.rowid), Some(i), "key {i}");
+        }
+    }
+
+    /// With completions deferred until `step()`, a spill stops the batch part way: the
+    /// rows before it stay inserted and the rest are inserted by the retry.
+    #[cfg(feature = "io_memory_yield")]
+    #[test]
+    fn test_hash_table_insert_batch_resumes_after_spill_io() {
+        let io: Arc<dyn IO> = Arc::new(crate::MemoryYieldIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 1024,
+            partition_count: Some(4),
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config, io.clone()).unwrap();
+
+        let mut batch: VecDeque<PendingHashInsert> = (0..1000)
+            .map(|i| PendingHashInsert {
+                key_values: vec![Value::from_i64(i)].into(),
+                rowid: i,
+                payload_values: vec![],
+            })
+            .collect();
+        let IOResult::IO(_) = ht.insert_batch(&mut batch, None).unwrap() else {
+            panic!("batch over budget must wait for the spill");
+        };
+        let remaining = batch.len();
+        assert!(remaining > 0 && remaining < 1000, "remaining {remaining}");
+        assert_eq!(ht.num_entries, 1000 - remaining);
+
+        loop {
+            io.step().unwrap();
+            match ht.insert_batch(&mut batch, None).unwrap() {
+                IOResult::Done(()) => break,
+                IOResult::IO(_) => {}
+            }
+        }
+        assert!(batch.is_empty());
+        assert_eq!(ht.num_entries, 1000);
+
+        while let IOResult::IO(_) = ht.finalize_build(None).unwrap() {
+            io.step().unwrap();
+        }
+        for i in 0..1000 {
+            let key = vec![Value::from_i64(i)];
+            let partition_idx = ht.partition_for_keys(&key).unwrap();
+            while let IOResult::IO(_) = ht.load_spilled_partition(partition_idx, None).unwrap() {
+                io.step().unwrap();
+            }
+            let entry = ht.probe_partition(partition_idx, &key, None).unwrap();
+            assert_eq!(entry.map(|e| e.rowid), Some(i), "key {i}");
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "fs", not(target_family = "wasm")))]
+    fn test_hash_table_drop_removes_spill_temp_dir() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 2048,
+            temp_store: crate::TempStore::File,
+            partition_count: Some(4),
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+        for i in 0..300 {
+            let _ = ht.insert(vec![Value::from_i64(i)], i, vec![], None);
+        }
+        assert!(ht.has_spilled());
+
+        let dir = ht
+            .spill_state
+            .as_ref()
+            .unwrap()
+            .temp_file
+            .dir()
+            .expect("file temp store should use a temp dir")
+            .to_path_buf();
+        assert!(dir.exists());
+        // Dropped without close()
+        drop(ht);
+        assert!(!dir.exists(), "drop should remove the spill temp dir");
+    }
+
+    #[test]
+    fn test_hash_table_collisions() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 2, // Small number to force collisions
+            mem_budget: 1024 * 1024,
+            num_keys: 1,
+            collations: vec![CollationSeq::Binary],
+            temp_store: crate::TempStore::Default,
+            track_matched: false,
+            partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+
+        // Insert multiple entries (late materialization - only store rowids)
+        for i in 0..10 {
+            let key = vec![Value::from_i64(i)];
+            let _ = ht.insert(key, i * 100, vec![], None).unwrap();
+        }
+
+        let _ = ht.finalize_build(None);
+
+        // Verify all entries can be found
+        for i in 0..10 {
+            let result = ht.probe(vec![Value::from_i64(i)], None).unwrap();
+            assert!(result.is_some());
+            let entry = result.unwrap();
+            assert_eq!(entry.key_values[0].as_ref(), ValueRef::from_i64(i));
+            assert_eq!(entry.rowid, i * 100);
+        }
+    }
+
+    #[test]
+    fn test_hash_table_duplicate_keys() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 1024 * 1024,
+            num_keys: 1,
+            collations: vec![CollationSeq::Binary],
+            temp_store: crate::TempStore::Default,
+            track_matched: false,
+            partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+
+        // Insert multiple entries with the same key
+        let key = vec![Value::from_i64(42)];
+        for i in 0..3 {
+            let _ = ht.insert(key.clone(), 1000 + i, vec![], None).unwrap();
+        }
+
+        let _ = ht.finalize_build(None);
+
+        // Probe should return first match
+        let result = ht.probe(key, None).unwrap();
+        assert!(result.is_some());
+        assert_eq!(result.unwrap().rowid, 1000);
+
+        // next_match should return additional matches
+        let result2 = ht.next_match().unwrap();
+        assert!(result2.is_some());
+        assert_eq!(result2.unwrap().rowid, 1001);
+
+        let result3 = ht.next_match().unwrap();
+        assert!(result3.is_some());
+        assert_eq!(result3.unwrap().rowid, 1002);
+
+        // No more matches
+        let result4 = ht.next_match().unwrap();
+        assert!(result4.is_none());
+    }
+
+    #[test]
+    fn test_max_matches_per_key() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 1024 * 1024,
+            max_matches_per_key: Some(3),
+            shrink_on_finalize: true,
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+        for i in 0..1000 {
+            let _ = ht
+                .insert(vec![Value::from_i64(42)], i, vec![], None)
+                .unwrap();
+        }
+        let _ = ht.finalize_build(None).unwrap();
+
+        let rowids = |ht: &mut HashTable| {
+            let mut rowids = vec![
+                ht.probe(vec![Value::from_i64(42)], None)
+                    .unwrap()
+                    .unwrap()
+                    .rowid,
+            ];
+            while let Some(entry) = ht.next_match().unwrap() {
+                rowids.push(entry.rowid);
+            }
+            rowids
+        };
+        assert_eq!(rowids(&mut ht), vec![0, 1, 2]);
+        // The rest of the chain was not scanned
+        assert_eq!(ht.probe_entry_idx, 3);
+        assert!(ht.next_match().unwrap().is_none());
+
+        // A new probe starts counting again
+        assert_eq!(rowids(&mut ht), vec![0, 1, 2]);
+
+        // matches() is not capped
+        assert_eq!(ht.matches(vec![Value::from_i64(42)]).unwrap().count(), 1000);
+    }
+
+    #[test]
+    fn test_visit_matches_stops_on_break() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 1024 * 1024,
+            num_keys: 1,
+            collations: vec![CollationSeq::Binary],
+            temp_store: crate::TempStore::Default,
+            track_matched: false,
+            partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+
+        let key = vec![Value::from_i64(42)];
+        for i in 0..5 {
+            let _ = ht.insert(key.clone(), 1000 + i, vec![], None).unwrap();
+        }
+        let _ = ht
+            .insert(vec![Value::from_i64(7)], 1, vec![], None)
+            .unwrap();
+        let _ = ht.finalize_build(None);
+
+        let key_refs: Vec<ValueRef> = key.iter().map(|v| v.as_ref()).try_collect().unwrap();
+        let mut visited = vec![];
+        // Residual predicate: stop at the first match with an odd rowid
+        let flow = ht.visit_matches(&key_refs, |entry| {
+            visited.push(entry.rowid);
+            if entry.rowid % 2 == 1 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert_eq!(flow, ControlFlow::Break(()));
+        assert_eq!(visited, vec![1000, 1001]);
+
+        // Without a break every match is visited, and only matches
+        let mut visited = vec![];
+        let flow = ht.visit_matches(&key_refs, |entry| {
+            visited.push(entry.rowid);
+            ControlFlow::Continue(())
+        });
+        assert_eq!(flow, ControlFlow::Continue(()));
+        assert_eq!(visited, vec![1000, 1001, 1002, 1003, 1004]);
     }
 
     #[test]
-    fn test_hash_table_basic() {
+    fn test_single_integer_keys_are_inline() {
         let io = Arc::new(MemoryIO::new());
         let config = HashTableConfig {
-            initial_buckets: 4,
+            initial_buckets: 16,
             mem_budget: 1024 * 1024,
             num_keys: 1,
             collations: vec![CollationSeq::Binary],
             temp_store: crate::TempStore::Default,
             track_matched: false,
             partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
         let mut ht = HashTable::new(config, io).unwrap();
 
-        // Insert some entries (late materialization - only store rowids)
-        let key1 = vec![Value::from_i64(1)];
-        let _ = ht.insert(key1.clone(), 100, vec![], None).unwrap();
-
-        let key2 = vec![Value::from_i64(2)];
-        let _ = ht.insert(key2.clone(), 200, vec![], None).unwrap();
-
+        let mut metrics = HashJoinMetrics::default();
+        for i in 0..100 {
+            let _ = ht
+                .insert(vec![Value::from_i64(i)], i, vec![], Some(&mut metrics))
+                .unwrap();
+        }
+        // A text key can't be stored inline
+        let _ = ht
+            .insert(
+                vec![Value::Text("abc".into())],
+                1000,
+                vec![],
+                Some(&mut metrics),
+            )
+            .unwrap();
         let _ = ht.finalize_build(None);
+        assert_eq!(metrics.build_heap_keys, 1);
 
-        // Probe for key1
-        let result = ht.probe(key1, None).unwrap();
-        assert!(result.is_some());
-        let entry1 = result.unwrap();
-        assert_eq!(entry1.key_values[0].as_ref(), ValueRef::from_i64(1));
-        assert_eq!(entry1.rowid, 100);
+        let inline = ht
+            .buckets
+            .iter()
+            .flat_map(|bucket| &bucket.entries)
+            .filter(|entry| matches!(entry.key_values, JoinKey::Integer(_)))
+            .count();
+        assert_eq!(inline, 100);
 
-        // Probe for key2
-        let result = ht.probe(key2, None).unwrap();
-        assert!(result.is_some());
-        let entry2 = result.unwrap();
-        assert_eq!(entry2.key_values[0].as_ref(), ValueRef::from_i64(2));
-        assert_eq!(entry2.rowid, 200);
+        for i in 0..100 {
+            let entry = ht.probe(vec![Value::from_i64(i)], None).unwrap().unwrap();
+            assert_eq!(entry.rowid, i);
+            assert_eq!(&*entry.key_values, &[Value::from_i64(i)]);
+        }
+        let entry = ht
+            .probe(vec![Value::Text("abc".into())], None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.rowid, 1000);
+        // Keys compare numerically, whatever their representation
+        assert!(ht
+            .probe(vec![Value::from_f64(5.0)], None)
+            .unwrap()
+            .is_some());
+    }
 
-        // Probe for non-existent key
-        let result = ht.probe(vec![Value::from_i64(999)], None).unwrap();
-        assert!(result.is_none());
+    #[test]
+    fn test_join_key_push() {
+        let mut key = JoinKey::with_capacity(1).unwrap();
+        key.push(Value::from_i64(1)).unwrap();
+        assert!(matches!(key, JoinKey::Integer(_)));
+
+        let mut key = JoinKey::with_capacity(1).unwrap();
+        key.push(Value::Null).unwrap();
+        assert!(matches!(key, JoinKey::Values(_)));
+        assert_eq!(&*key, &[Value::Null]);
+
+        let mut key = JoinKey::with_capacity(2).unwrap();
+        key.push(Value::from_i64(1)).unwrap();
+        key.push(Value::from_i64(2)).unwrap();
+        assert!(matches!(key, JoinKey::Values(_)));
+        assert_eq!(&*key, &[Value::from_i64(1), Value::from_i64(2)]);
+
+        let key: Vec<Value> = JoinKey::from(vec![Value::from_i64(7)])
+            .into_iter()
+            .try_collect()
+            .unwrap();
+        assert_eq!(key, vec![Value::from_i64(7)]);
     }
 
     #[test]
-    fn test_hash_table_collisions() {
+    fn test_frozen_table_concurrent_probe() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FrozenHashTable>();
+
         let io = Arc::new(MemoryIO::new());
         let config = HashTableConfig {
-            initial_buckets: 2, // Small number to force collisions
+            initial_buckets: 4,
             mem_budget: 1024 * 1024,
             num_keys: 1,
             collations: vec![CollationSeq::Binary],
             temp_store: crate::TempStore::Default,
             track_matched: false,
             partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
         let mut ht = HashTable::new(config, io).unwrap();
+        for i in 0..400 {
+            // Two entries per key
+            let _ = ht
+                .insert(vec![Value::from_i64(i % 200)], i, vec![], None)
+                .unwrap();
+        }
+        let _ = ht.finalize_build(None).unwrap();
+        let frozen = ht.freeze();
+        assert_eq!(frozen.num_entries(), 400);
+
+        std::thread::scope(|scope| {
+            for t in 0..4i64 {
+                let frozen = &frozen;
+                scope.spawn(move || {
+                    for k in (t * 50)..((t + 1) * 50) {
+                        let key = Value::from_i64(k);
+                        let key_refs = [key.as_ref()];
+                        let rowids: Vec<i64> = frozen
+                            .matches(&key_refs)
+                            .map(|e| e.rowid)
+                            .try_collect()
+                            .unwrap();
+                        assert_eq!(rowids, vec![k, k + 200]);
+                    }
+                    let missing = Value::from_i64(1000);
+                    assert_eq!(frozen.matches(&[missing.as_ref()]).count(), 0);
+                });
+            }
+        });
+    }
 
-        // Insert multiple entries (late materialization - only store rowids)
-        for i in 0..10 {
-            let key = vec![Value::from_i64(i)];
-            let _ = ht.insert(key, i * 100, vec![], None).unwrap();
+    #[test]
+    fn test_frozen_table_probe_cursors() {
+        let io = Arc::new(MemoryIO::new());
+        // A budget the whole build fits in, a spilled table cannot be frozen.
+        let config = HashTableConfig {
+            mem_budget: 1024 * 1024,
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+        for i in 0..4000 {
+            // Two entries per key
+            let _ = ht
+                .insert(vec![Value::from_i64(i % 2000)], i, vec![], None)
+                .unwrap();
         }
+        let _ = ht.finalize_build(None).unwrap();
+        let frozen = Arc::new(ht.freeze());
+
+        let threads: Vec<_> = (0..4i64)
+            .map(|t| {
+                let frozen = frozen.clone();
+                std::thread::spawn(move || {
+                    let mut cursor = frozen.cursor();
+                    for k in (t * 500)..((t + 1) * 500) {
+                        let first = cursor.probe(vec![Value::from_i64(k)]);
+                        assert_eq!(first.map(|e| e.rowid), Some(k));
+                        let second = cursor.next_match();
+                        assert_eq!(second.map(|e| e.rowid), Some(k + 2000));
+                        assert!(cursor.next_match().is_none());
+                    }
+                    assert!(cursor.probe(vec![Value::from_i64(-1)]).is_none());
+                    assert!(cursor.probe(vec![Value::Null]).is_none());
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    }
 
-        let _ = ht.finalize_build(None);
+    #[test]
+    fn test_stats_count_spilled_entries() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 2048,
+            num_keys: 1,
+            collations: vec![CollationSeq::Binary],
+            temp_store: crate::TempStore::Default,
+            track_matched: false,
+            partition_count: Some(4),
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
 
-        // Verify all entries can be found
         for i in 0..10 {
-            let result = ht.probe(vec![Value::from_i64(i)], None).unwrap();
-            assert!(result.is_some());
-            let entry = result.unwrap();
-            assert_eq!(entry.key_values[0].as_ref(), ValueRef::from_i64(i));
-            assert_eq!(entry.rowid, i * 100);
+            let _ = ht.insert(vec![Value::from_i64(i)], i, vec![], None);
+        }
+        let stats = ht.stats();
+        assert_eq!(stats.num_entries, 10);
+        assert_eq!(stats.spilled_entries, 0);
+
+        // NULL keys are never stored
+        let _ = ht.insert(vec![Value::Null], -1, vec![], None);
+        for i in 10..300 {
+            let _ = ht.insert(vec![Value::from_i64(i)], i, vec![], None);
         }
+        let _ = ht.finalize_build(None).unwrap();
+        assert!(ht.has_spilled());
+
+        let stats = ht.stats();
+        assert_eq!(stats.num_entries, 300);
+        let on_disk: usize = ht
+            .spill_state
+            .as_ref()
+            .unwrap()
+            .partitions
+            .iter()
+            .flat_map(|p| &p.chunks)
+            .map(|c| c.num_entries)
+            .sum();
+        assert!(on_disk > 0);
+        assert_eq!(stats.spilled_entries, on_disk);
+
+        // Loading a partition back doesn't count its entries twice
+        let partition_idx = ht.partition_for_keys(&[Value::from_i64(0)]).unwrap();
+        while let IOResult::IO(_) = ht.load_spilled_partition(partition_idx, None).unwrap() {}
+        assert_eq!(ht.stats().num_entries, 300);
     }
 
     #[test]
-    fn test_hash_table_duplicate_keys() {
+    fn test_stats_chain_length_histogram() {
         let io = Arc::new(MemoryIO::new());
         let config = HashTableConfig {
             initial_buckets: 4,
             mem_budget: 1024 * 1024,
+            max_load_factor: f64::INFINITY,
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+        let stats = ht.stats();
+        assert_eq!(
+            stats.chain_length_histogram.len(),
+            CHAIN_LENGTH_HISTOGRAM_LEN
+        );
+        assert_eq!(stats.chain_length_histogram[0], 4);
+
+        // One skewed key lands in the overflow slot, a second key gets a short chain
+        for i in 0..40 {
+            let _ = ht
+                .insert(vec![Value::from_i64(7)], i, vec![], None)
+                .unwrap();
+        }
+        let bucket_of = |key: i64| {
+            let hash = hash_join_key_owned(&[Value::from_i64(key)], &ht.collations, ht.seed);
+            (hash as usize) % ht.buckets.len()
+        };
+        let other = (0..).find(|&key| bucket_of(key) != bucket_of(7)).unwrap();
+        for i in 0..3 {
+            let _ = ht
+                .insert(vec![Value::from_i64(other)], i, vec![], None)
+                .unwrap();
+        }
+
+        let stats = ht.stats();
+        let mut expected = vec![0; CHAIN_LENGTH_HISTOGRAM_LEN];
+        expected[0] = 2;
+        expected[3] = 1;
+        expected[CHAIN_LENGTH_HISTOGRAM_LEN - 1] = 1;
+        assert_eq!(stats.chain_length_histogram, expected);
+        assert_eq!(stats.chain_length_histogram.iter().sum::<usize>(), 4);
+
+        let rendered = stats.to_string();
+        assert!(rendered.contains("entries: 43 (0 spilled)"), "{rendered}");
+        assert!(rendered.contains("           3 | 1"), "{rendered}");
+        assert!(rendered.contains("         15+ | 1"), "{rendered}");
+        // Empty chain lengths are left out
+        assert!(!rendered.contains("           1 |"), "{rendered}");
+    }
+
+    #[test]
+    fn test_bloom_filter_skips_probe_misses() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 64,
+            mem_budget: 1024 * 1024,
             num_keys: 1,
             collations: vec![CollationSeq::Binary],
             temp_store: crate::TempStore::Default,
             track_matched: false,
             partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: true,
+            expected_entries: Some(1000),
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
-        let mut ht = HashTable::new(config, io).unwrap();
+        let mut ht = HashTable::new(config.clone(), io.clone()).unwrap();
+        for i in 0..1000 {
+            let _ = ht.insert(vec![Value::from_i64(i)], i, vec![], None);
+        }
+        let _ = ht.finalize_build(None).unwrap();
 
-        // Insert multiple entries with the same key
-        let key = vec![Value::from_i64(42)];
-        for i in 0..3 {
-            let _ = ht.insert(key.clone(), 1000 + i, vec![], None).unwrap();
+        // No false negatives
+        for i in 0..1000 {
+            let entry = ht.probe(vec![Value::from_i64(i)], None).unwrap();
+            assert_eq!(entry.map(|e| e.rowid), Some(i));
         }
+        for i in 1000..2000 {
+            assert!(ht.probe(vec![Value::from_i64(i)], None).unwrap().is_none());
+        }
+        let rate = ht.stats().bloom_filter_false_positive_rate.unwrap();
+        assert!(
+            rate > 0.0 && rate < 0.05,
+            "estimated false positive rate {rate}"
+        );
 
-        let _ = ht.finalize_build(None);
+        ht.close();
+        assert_eq!(ht.stats().bloom_filter_false_positive_rate, Some(0.0));
 
-        // Probe should return first match
-        let result = ht.probe(key, None).unwrap();
-        assert!(result.is_some());
-        assert_eq!(result.unwrap().rowid, 1000);
+        let config = HashTableConfig {
+            enable_bloom_filter: false,
+            ..config
+        };
+        let ht = HashTable::new(config, io).unwrap();
+        assert_eq!(ht.stats().bloom_filter_false_positive_rate, None);
+    }
 
-        // next_match should return additional matches
-        let result2 = ht.next_match().unwrap();
-        assert!(result2.is_some());
-        assert_eq!(result2.unwrap().rowid, 1001);
+    #[test]
+    fn test_build_summary_matches_stats() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 1024 * 1024,
+            num_keys: 1,
+            collations: vec![CollationSeq::Binary],
+            temp_store: crate::TempStore::Default,
+            track_matched: false,
+            partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
+        };
+        let mut ht = HashTable::new(config.clone(), io.clone()).unwrap();
+        // 50 distinct keys, one of them repeated 20 times
+        for i in 0..50 {
+            let _ = ht.insert(vec![Value::from_i64(i)], i, vec![], None);
+        }
+        for i in 0..19 {
+            let _ = ht.insert(vec![Value::from_i64(7)], 100 + i, vec![], None);
+        }
+        let IOResult::Done(summary) = ht.finalize_build(None).unwrap() else {
+            panic!("memory IO");
+        };
+        let stats = ht.stats();
+        assert_eq!(summary.num_entries, stats.num_entries);
+        assert_eq!(summary.num_entries, 69);
+        assert_eq!(summary.mem_used, stats.mem_used);
+        assert_eq!(summary.estimated_distinct_keys, 50);
+        assert!(summary.max_chain_length >= 20);
+
+        // Spilled entries count as distinct keys
+        let config = HashTableConfig {
+            mem_budget: 2048,
+            partition_count: Some(4),
+            ..config
+        };
+        let mut ht = HashTable::new(config, io.clone()).unwrap();
+        for i in 0..300 {
+            let _ = ht.insert(vec![Value::from_i64(i)], i, vec![], None);
+        }
+        let summary = loop {
+            match ht.finalize_build(None).unwrap() {
+                IOResult::Done(summary) => break summary,
+                IOResult::IO(_) => io.step().unwrap(),
+            }
+        };
+        assert!(ht.has_spilled());
+        let stats = ht.stats();
+        assert_eq!(summary.num_entries, stats.num_entries);
+        assert_eq!(summary.mem_used, stats.mem_used);
+        assert_eq!(summary.estimated_distinct_keys, 300);
+    }
 
-        let result3 = ht.next_match().unwrap();
-        assert!(result3.is_some());
-        assert_eq!(result3.unwrap().rowid, 1002);
+    #[test]
+    fn test_buckets_grow_with_load_factor() {
+        let build = |max_load_factor: f64| {
+            let io = Arc::new(MemoryIO::new());
+            let config = HashTableConfig {
+                initial_buckets: 16,
+                mem_budget: 256 * 1024 * 1024,
+                num_keys: 1,
+                collations: vec![CollationSeq::Binary],
+                temp_store: crate::TempStore::Default,
+                track_matched: false,
+                partition_count: None,
+                mmap_spill_reads: false,
+                repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+                max_load_factor,
+                enable_bloom_filter: false,
+                expected_entries: None,
+                join_mode: JoinMode::Inner,
+                null_safe: false,
+                seed: DEFAULT_SEED,
+                deferred_index: false,
+                max_matches_per_key: None,
+                shrink_on_finalize: true,
+            };
+            let mut ht = HashTable::new(config, io).unwrap();
+            for i in 0..100_000 {
+                let _ = ht
+                    .insert(vec![Value::from_i64(i)], i, vec![], None)
+                    .unwrap();
+            }
+            let IOResult::Done(summary) = ht.finalize_build(None).unwrap() else {
+                panic!("memory IO");
+            };
+            assert!(!ht.has_spilled());
+            assert_eq!(summary.num_entries, 100_000);
+            (ht, summary)
+        };
 
-        // No more matches
-        let result4 = ht.next_match().unwrap();
-        assert!(result4.is_none());
+        let (ht, summary) = build(DEFAULT_MAX_LOAD_FACTOR);
+        assert!(ht.buckets.len() >= 100_000 / 4);
+        assert!(
+            summary.max_chain_length <= 32,
+            "max chain length {}",
+            summary.max_chain_length
+        );
+        // Every key is still found after the buckets were redistributed
+        for i in (0..100_000).step_by(997) {
+            let key = Value::from_i64(i);
+            let mut rowids = vec![];
+            let _ = ht.visit_matches(&[key.as_ref()], |entry| {
+                rowids.push(entry.rowid);
+                ControlFlow::Continue(())
+            });
+            assert_eq!(rowids, vec![i]);
+        }
+
+        let (ht, summary) = build(f64::INFINITY);
+        assert_eq!(ht.buckets.len(), 16);
+        assert!(summary.max_chain_length > 1000);
     }
 
     #[test]
@@ -3507,7 +6711,8 @@ mod hashtests {
                 Value::Text("hello".to_string().into()),
                 Value::Null,
                 Value::from_f64(std::f64::consts::PI),
-            ],
+            ]
+            .into(),
             100,
         );
 
@@ -3545,7 +6750,8 @@ mod hashtests {
                 Value::Text("hello world".to_string().into()),
                 Value::Null,
                 Value::from_f64(std::f64::consts::PI),
-            ],
+            ]
+            .into(),
             100,
             vec![Value::Blob(std::vec![1, 2, 3, 4, 5]), Value::from_i64(-999)],
         );
@@ -3584,6 +6790,89 @@ mod hashtests {
         );
     }
 
+    #[test]
+    fn test_hash_entry_serialization_round_trips_random_batch() {
+        use rand::{Rng, SeedableRng};
+        use rand_chacha::ChaCha8Rng;
+
+        let mut rng = ChaCha8Rng::seed_from_u64(0x5EED_0521);
+        let random_value = |rng: &mut ChaCha8Rng| match rng.random_range(0..5) {
+            0 => Value::Null,
+            1 => Value::from_i64(match rng.random_range(0..4) {
+                0 => i64::MIN,
+                1 => i64::MAX,
+                2 => -rng.random_range(0..1000),
+                _ => rng.random(),
+            }),
+            2 => Value::from_f64(rng.random_range(-1e9..1e9)),
+            3 => {
+                let len = rng.random_range(0..20);
+                Value::Text(
+                    (0..len)
+                        .map(|_| rng.random_range('a'..='z'))
+                        .collect::<String>()
+                        .into(),
+                )
+            }
+            _ => {
+                let len = rng.random_range(0..20);
+                Value::Blob((0..len).map(|_| rng.random()).collect())
+            }
+        };
+        let mut entries: Vec<HashEntry> = (0..500)
+            .map(|_| {
+                let key_values: Vec<Value> = (0..rng.random_range(1..4))
+                    .map(|_| random_value(&mut rng))
+                    .collect();
+                let payload_values = (0..rng.random_range(0..4))
+                    .map(|_| random_value(&mut rng))
+                    .collect();
+                HashEntry::new_with_payload(
+                    rng.random(),
+                    key_values.into(),
+                    rng.random(),
+                    payload_values,
+                )
+            })
+            .collect();
+        // Empty text and blob values must survive too
+        entries.push(HashEntry::new_with_payload(
+            0,
+            vec![Value::Text(String::new().into()), Value::Blob(vec![])].into(),
+            -1,
+            vec![Value::Blob(vec![]), Value::Text(String::new().into())],
+        ));
+
+        let mut buf = vec![];
+        for entry in &entries {
+            entry.serialize(&mut buf).unwrap();
+        }
+
+        let mut offset = 0;
+        for entry in &entries {
+            let (read, consumed) = HashEntry::deserialize(&buf[offset..]).unwrap();
+            assert_eq!(consumed, entry.serialized_size());
+            offset += consumed;
+            assert_eq!(read.hash, entry.hash);
+            assert_eq!(read.rowid, entry.rowid);
+            for (values, expected) in [
+                (&read.key_values[..], &entry.key_values[..]),
+                (&read.payload_values[..], &entry.payload_values[..]),
+            ] {
+                assert_eq!(values.len(), expected.len());
+                for (value, expected) in values.iter().zip(expected) {
+                    assert_eq!(value.value_type(), expected.value_type());
+                    assert_eq!(value, expected);
+                }
+            }
+            // The encoding is deterministic: the entry read back encodes to the same bytes
+            let mut reencoded = vec![];
+            read.serialize(&mut reencoded).unwrap();
+            assert_eq!(reencoded[..], buf[offset - consumed..offset]);
+        }
+        assert_eq!(offset, buf.len());
+    }
+
     #[test]
     fn test_partition_from_hash() {
         // Test partition distribution
@@ -3641,6 +6930,17 @@ mod hashtests {
             temp_store: crate::TempStore::Default,
             track_matched: false,
             partition_count: Some(64),
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
         let mut ht = HashTable::new(config, io).unwrap();
         insert_many_force_spill(&mut ht, 0, 1024);
@@ -3662,6 +6962,17 @@ mod hashtests {
             temp_store: crate::TempStore::Default,
             track_matched: false,
             partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
         let mut ht = HashTable::new(config, io).unwrap();
         insert_many_force_spill(&mut ht, 0, 1024);
@@ -3686,6 +6997,18 @@ mod hashtests {
             temp_store: crate::TempStore::Default,
             track_matched: false,
             partition_count: Some(16),
+            mmap_spill_reads: false,
+            // A single key can't be re-partitioned, keep it in one oversized partition.
+            repartition_fanout: 1,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
         let mut ht = HashTable::new(config, io).unwrap();
 
@@ -3698,7 +7021,7 @@ mod hashtests {
         }
 
         match ht.finalize_build(None).unwrap() {
-            IOResult::Done(()) => {}
+            IOResult::Done(_) => {}
             IOResult::IO(_) => panic!("memory IO"),
         }
         assert!(ht.has_spilled());
@@ -3739,6 +7062,17 @@ mod hashtests {
             temp_store: crate::TempStore::Default,
             track_matched: false,
             partition_count: Some(16),
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
         let mut ht = HashTable::new(config, io.clone()).unwrap();
         let partitioning = Partitioning::new(16);
@@ -3756,6 +7090,7 @@ mod hashtests {
             next_spill_offset: 0,
             temp_file,
             partitioning,
+            splits: vec![],
         };
         ht.spill_state = Some(spill_state);
         ht.state = HashTableState::Probing;
@@ -3775,10 +7110,21 @@ mod hashtests {
             temp_store: crate::TempStore::Default,
             track_matched: false,
             partition_count: Some(16),
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
         let mut ht = HashTable::new(config, io.clone()).unwrap();
 
-        let entry = HashEntry::new(1, vec![Value::from_i64(1)], 7);
+        let entry = HashEntry::new(1, vec![Value::from_i64(1)].into(), 7);
         let mut buf = vec![];
         entry.serialize(&mut buf).unwrap();
         let truncated = &buf[..buf.len() - 1];
@@ -3806,6 +7152,7 @@ mod hashtests {
             next_spill_offset: truncated.len() as u64,
             temp_file,
             partitioning,
+            splits: vec![],
         };
         ht.spill_state = Some(spill_state);
         ht.state = HashTableState::Probing;
@@ -3834,14 +7181,14 @@ mod hashtests {
 
         // Under BINARY: hashes must differ
         let bin_coll = vec![CollationSeq::Binary];
-        let h1_bin = hash_join_key(&keys1, &bin_coll);
-        let h2_bin = hash_join_key(&keys2, &bin_coll);
+        let h1_bin = hash_join_key(&keys1, &bin_coll, DEFAULT_SEED);
+        let h2_bin = hash_join_key(&keys2, &bin_coll, DEFAULT_SEED);
         assert_ne!(h1_bin, h2_bin);
 
         // Under NOCASE: hashes should be equal
         let nocase_coll = vec![CollationSeq::NoCase];
-        let h1_nc = hash_join_key(&keys1, &nocase_coll);
-        let h2_nc = hash_join_key(&keys2, &nocase_coll);
+        let h1_nc = hash_join_key(&keys1, &nocase_coll, DEFAULT_SEED);
+        let h2_nc = hash_join_key(&keys2, &nocase_coll, DEFAULT_SEED);
         assert_eq!(h1_nc, h2_nc);
     }
 
@@ -3857,8 +7204,8 @@ mod hashtests {
         // Under NOCASE: ASCII portion differs (b/B), so hashes should differ
         // (because SQLite NOCASE doesn't handle Unicode case folding)
         let nocase_coll = vec![CollationSeq::NoCase];
-        let h1 = hash_join_key(&keys1, &nocase_coll);
-        let h2 = hash_join_key(&keys2, &nocase_coll);
+        let h1 = hash_join_key(&keys1, &nocase_coll, DEFAULT_SEED);
+        let h2 = hash_join_key(&keys2, &nocase_coll, DEFAULT_SEED);
 
         // The 'b' and 'B' will be lowercased to 'b', but the 'ü' and 'Ü' are not
         // ASCII so they remain as-is. Since ü != Ü at byte level, hashes will differ.
@@ -3880,14 +7227,14 @@ mod hashtests {
 
         assert!(values_equal(keys1[0], keys2[0], CollationSeq::NoCase));
         assert_eq!(
-            hash_join_key(&keys1, &nocase_coll),
-            hash_join_key(&keys2, &nocase_coll)
+            hash_join_key(&keys1, &nocase_coll, DEFAULT_SEED),
+            hash_join_key(&keys2, &nocase_coll, DEFAULT_SEED)
         );
 
         assert!(!values_equal(keys1[0], keys3[0], CollationSeq::NoCase));
         assert_ne!(
-            hash_join_key(&keys1, &nocase_coll),
-            hash_join_key(&keys3, &nocase_coll)
+            hash_join_key(&keys1, &nocase_coll, DEFAULT_SEED),
+            hash_join_key(&keys3, &nocase_coll, DEFAULT_SEED)
         );
     }
 
@@ -3917,15 +7264,50 @@ mod hashtests {
         let key2 = vec![ValueRef::Text(TextRef::new("hello", TextSubtype::Text))];
 
         // Binary: not equal
-        assert!(!keys_equal(&key1, &key2, &[CollationSeq::Binary]));
+        assert!(!keys_equal(&key1, &key2, &[CollationSeq::Binary], false));
 
         // NOCASE: equal
-        assert!(keys_equal(&key1, &key2, &[CollationSeq::NoCase]));
+        assert!(keys_equal(&key1, &key2, &[CollationSeq::NoCase], false));
+    }
+
+    #[test]
+    fn test_nocase_join_matches_mixed_case() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            num_keys: 2,
+            collations: vec![CollationSeq::NoCase, CollationSeq::Binary],
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+        let key = |a: &str, b: &str| vec![Value::Text(a.into()), Value::Text(b.into())];
+        let _ = ht.insert(key("ABC", "x"), 1, vec![], None).unwrap();
+        let _ = ht.insert(key("abc", "x"), 2, vec![], None).unwrap();
+        let _ = ht.insert(key("abc", "X"), 3, vec![], None).unwrap();
+        let _ = ht.finalize_build(None).unwrap();
+
+        // The NOCASE column matches in any case, the BINARY one only exactly
+        let mut rowids = vec![];
+        let mut entry = ht.probe(key("aBc", "x"), None).unwrap().map(|e| e.rowid);
+        while let Some(rowid) = entry {
+            rowids.push(rowid);
+            entry = ht.next_match().unwrap().map(|e| e.rowid);
+        }
+        rowids.sort();
+        assert_eq!(rowids, vec![1, 2]);
+
+        let entry = ht.probe(key("ABC", "X"), None).unwrap();
+        assert_eq!(entry.map(|e| e.rowid), Some(3));
+        assert!(ht.next_match().unwrap().is_none());
+        assert!(ht.probe(key("abcd", "x"), None).unwrap().is_none());
     }
 
     #[test]
     fn test_hash_entry_deserialization_truncated() {
-        let entry = HashEntry::new(123, vec![Value::from_i64(1), Value::Text("abc".into())], 42);
+        let entry = HashEntry::new(
+            123,
+            vec![Value::from_i64(1), Value::Text("abc".into())].into(),
+            42,
+        );
 
         let mut buf = vec![];
         entry.serialize(&mut buf).unwrap();
@@ -3942,7 +7324,7 @@ mod hashtests {
 
     #[test]
     fn test_hash_entry_deserialization_garbage_type_tag() {
-        let entry = HashEntry::new(1, vec![Value::from_i64(10)], 7);
+        let entry = HashEntry::new(1, vec![Value::from_i64(10)].into(), 7);
         let mut buf = vec![];
         entry.serialize(&mut buf).unwrap();
 
@@ -3970,6 +7352,52 @@ mod hashtests {
         }
     }
 
+    #[test]
+    #[cfg(all(feature = "fs", not(target_family = "wasm")))]
+    fn test_close_removes_spill_temp_dir() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 2048,
+            num_keys: 1,
+            collations: vec![CollationSeq::Binary],
+            temp_store: crate::TempStore::File,
+            track_matched: false,
+            partition_count: Some(4),
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
+        };
+        let mut ht = HashTable::new(config, io.clone()).unwrap();
+        for i in 0..300 {
+            let _ = ht.insert(vec![Value::from_i64(i)], i, vec![], None);
+        }
+        while let IOResult::IO(_) = ht.finalize_build(None).unwrap() {
+            io.step().unwrap();
+        }
+        assert!(ht.has_spilled());
+
+        let dir = ht
+            .spill_state
+            .as_ref()
+            .unwrap()
+            .temp_file
+            .dir()
+            .expect("file temp store should use a temp dir")
+            .to_path_buf();
+        assert!(dir.exists());
+        ht.close();
+        assert!(!dir.exists(), "close should remove the spill temp dir");
+    }
+
     #[test]
     fn test_hash_table_spill_and_load_partition_round_trip() {
         let io = Arc::new(MemoryIO::new());
@@ -4012,6 +7440,212 @@ mod hashtests {
         assert_eq!(entry.unwrap().rowid, 10);
     }
 
+    #[test]
+    fn test_skewed_partition_is_repartitioned() {
+        let io: Arc<dyn IO> = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 2048,
+            partition_count: Some(16),
+            repartition_fanout: 8,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config, io.clone()).unwrap();
+
+        // Only build keys that land in top-level partition 0, so that it alone
+        // holds the whole build side.
+        let partitioning = Partitioning::new(16);
+        let keys: Vec<i64> = (0..)
+            .filter(|&k| {
+                let hash = hash_join_key(
+                    &[ValueRef::from_i64(k)],
+                    &[CollationSeq::Binary],
+                    DEFAULT_SEED,
+                );
+                partitioning.index(hash) == 0
+            })
+            .take(300)
+            .collect();
+        for &k in &keys {
+            let _ = ht.insert(vec![Value::from_i64(k)], k, vec![], None);
+        }
+        while let IOResult::IO(_) = ht.finalize_build(None).unwrap() {
+            io.step().unwrap();
+        }
+        assert!(ht.has_spilled(), "hash table should have spilled");
+
+        let spill_state = ht.spill_state.as_ref().unwrap();
+        assert_eq!(
+            spill_state.splits.len(),
+            1,
+            "expected a single re-partition"
+        );
+        assert!(spill_state.find_partition(0).is_none());
+        assert!(spill_state
+            .partitions
+            .iter()
+            .all(|p| p.total_size_bytes() <= 2048));
+
+        for &k in &keys {
+            let key = vec![Value::from_i64(k)];
+            let partition_idx = ht.partition_for_keys(&key).unwrap();
+            assert!(
+                partition_idx >= 16,
+                "key {k} should route to a sub-partition"
+            );
+            while let IOResult::IO(_) = ht.load_spilled_partition(partition_idx, None).unwrap() {
+                io.step().unwrap();
+            }
+            let entry = ht
+                .probe_partition(partition_idx, &key, None)
+                .unwrap()
+                .unwrap_or_else(|| panic!("key {k} not found after re-partitioning"));
+            assert_eq!(entry.rowid, k);
+        }
+    }
+
+    #[test]
+    fn test_single_key_partition_is_unsplittable() {
+        let io: Arc<dyn IO> = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 2048,
+            partition_count: Some(16),
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config, io.clone()).unwrap();
+        for rowid in 0..300 {
+            let _ = ht.insert(vec![Value::from_i64(7)], rowid, vec![], None);
+        }
+        let err = loop {
+            match ht.finalize_build(None) {
+                Ok(IOResult::IO(_)) => io.step().unwrap(),
+                Ok(IOResult::Done(_)) => panic!("duplicates of one key cannot fit the budget"),
+                Err(err) => break err,
+            }
+        };
+        assert!(err.to_string().contains("cannot be partitioned further"));
+    }
+
+    #[test]
+    fn test_mixed_hot_partition_fails_at_depth_cap() {
+        let io: Arc<dyn IO> = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 2048,
+            partition_count: Some(16),
+            seed: DEFAULT_SEED,
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config, io.clone()).unwrap();
+
+        // A hot key plus one other key that follows it into the same sub-partition at
+        // every depth, so no split ever gets the partition under the budget.
+        let route = |k: i64| {
+            let hash = hash_join_key(
+                &[ValueRef::from_i64(k)],
+                &[CollationSeq::Binary],
+                DEFAULT_SEED,
+            );
+            let mut path = vec![Partitioning::new(16).index(hash)];
+            for depth in 1..=MAX_REPARTITION_DEPTH {
+                let split = PartitionSplit {
+                    parent: 0,
+                    depth,
+                    first_child: 0,
+                    partitioning: Partitioning::new(DEFAULT_REPARTITION_FANOUT),
+                };
+                path.push(split.child(hash));
+            }
+            path
+        };
+        let hot_path = route(7);
+        let other = (8..).find(|&k| route(k) == hot_path).unwrap();
+        for rowid in 0..300 {
+            let _ = ht.insert(vec![Value::from_i64(7)], rowid, vec![], None);
+        }
+        let _ = ht.insert(vec![Value::from_i64(other)], 300, vec![], None);
+        let err = loop {
+            match ht.finalize_build(None) {
+                Ok(IOResult::IO(_)) => io.step().unwrap(),
+                Ok(IOResult::Done(_)) => panic!("the hot partition cannot fit the budget"),
+                Err(err) => break err,
+            }
+        };
+        assert!(err
+            .to_string()
+            .contains(&format!("after {MAX_REPARTITION_DEPTH} re-partitions")));
+    }
+
+    fn load_and_probe_all(ht: &mut HashTable, io: &Arc<dyn IO>, count: i64) -> HashJoinMetrics {
+        let mut metrics = HashJoinMetrics::default();
+        for rowid in 0..count {
+            let key = vec![Value::from_i64(rowid)];
+            let partition_idx = ht.partition_for_keys(&key).unwrap();
+            while let IOResult::IO(_) = ht
+                .load_spilled_partition(partition_idx, Some(&mut metrics))
+                .unwrap()
+            {
+                io.step().unwrap();
+            }
+            let entry = ht
+                .probe_partition(partition_idx, &key, None)
+                .unwrap()
+                .unwrap_or_else(|| panic!("rowid {rowid} not found in partition"));
+            assert_eq!(entry.rowid, rowid);
+        }
+        metrics
+    }
+
+    #[cfg(all(unix, not(miri)))]
+    #[test]
+    fn test_mmap_spill_reads_probe_without_reads() {
+        let io: Arc<dyn IO> = Arc::new(crate::io::PlatformIO::new().unwrap());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 1024,
+            mmap_spill_reads: true,
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config, io.clone()).unwrap();
+        insert_many_force_spill(&mut ht, 0, 1024);
+        let _ = ht.finalize_build(None).unwrap();
+        assert!(ht.has_spilled(), "hash table should have spilled");
+
+        let metrics = load_and_probe_all(&mut ht, &io, 1024);
+        assert!(metrics.load_mmap_chunks > 0);
+        assert_eq!(
+            metrics.load_read_calls, 0,
+            "spilled partitions should be parsed from the mapping"
+        );
+    }
+
+    #[test]
+    fn test_mmap_spill_reads_falls_back_to_pread() {
+        // MemoryIO files can't be mapped.
+        let io: Arc<dyn IO> = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 1024,
+            mmap_spill_reads: true,
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config, io.clone()).unwrap();
+        insert_many_force_spill(&mut ht, 0, 1024);
+        let _ = ht.finalize_build(None).unwrap();
+        assert!(ht.has_spilled(), "hash table should have spilled");
+
+        let metrics = load_and_probe_all(&mut ht, &io, 1024);
+        assert_eq!(metrics.load_mmap_chunks, 0);
+        assert!(metrics.load_read_calls > 0);
+    }
+
     #[test]
     fn test_partition_lru_eviction() {
         let io = Arc::new(MemoryIO::new());
@@ -4024,6 +7658,17 @@ mod hashtests {
             temp_store: crate::TempStore::Default,
             track_matched: false,
             partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
         let mut ht = HashTable::new(config, io).unwrap();
 
@@ -4068,6 +7713,18 @@ mod hashtests {
             temp_store: crate::TempStore::Default,
             track_matched: false,
             partition_count: None,
+            mmap_spill_reads: false,
+            // A single key can't be re-partitioned, keep it in one oversized partition.
+            repartition_fanout: 1,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
         let mut ht = HashTable::new(config, io).unwrap();
 
@@ -4079,7 +7736,7 @@ mod hashtests {
             }
         }
         match ht.finalize_build(None).unwrap() {
-            IOResult::Done(()) => {}
+            IOResult::Done(_) => {}
             IOResult::IO(_) => panic!("memory IO"),
         }
 
@@ -4118,6 +7775,17 @@ mod hashtests {
             temp_store: crate::TempStore::Default,
             track_matched: false,
             partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
         let mut ht = HashTable::new(config, io).unwrap();
 
@@ -4172,6 +7840,17 @@ mod hashtests {
             temp_store: crate::TempStore::Default,
             track_matched: false,
             partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
         let mut ht = HashTable::new(config, io).unwrap();
 
@@ -4204,6 +7883,17 @@ mod hashtests {
             temp_store: crate::TempStore::Default,
             track_matched: false,
             partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
         let mut ht = HashTable::new(config, io).unwrap();
 
@@ -4249,6 +7939,17 @@ mod hashtests {
             temp_store: crate::TempStore::Default,
             track_matched: false,
             partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
         let mut ht = HashTable::new(config, io).unwrap();
 
@@ -4279,6 +7980,17 @@ mod hashtests {
             temp_store: crate::TempStore::Default,
             track_matched: false,
             partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
         let mut ht = HashTable::new(config, io).unwrap();
 
@@ -4339,7 +8051,7 @@ mod hashtests {
         // Test that payload values survive serialization/deserialization
         let entry = HashEntry::new_with_payload(
             12345,
-            vec![Value::from_i64(1), Value::Text("key".into())],
+            vec![Value::from_i64(1), Value::Text("key".into())].into(),
             100,
             vec![
                 Value::Text("payload_text".into()),
@@ -4384,7 +8096,7 @@ mod hashtests {
     #[test]
     fn test_hash_entry_empty_payload() {
         // Test that entries without payload work correctly
-        let entry = HashEntry::new(12345, vec![Value::from_i64(1)], 100);
+        let entry = HashEntry::new(12345, vec![Value::from_i64(1)].into(), 100);
 
         assert!(!entry.has_payload());
         assert!(entry.payload_values.is_empty());
@@ -4401,11 +8113,11 @@ mod hashtests {
 
     #[test]
     fn test_hash_entry_size_includes_payload() {
-        let entry_no_payload = HashEntry::new(12345, vec![Value::from_i64(1)], 100);
+        let entry_no_payload = HashEntry::new(12345, vec![Value::from_i64(1)].into(), 100);
 
         let entry_with_payload = HashEntry::new_with_payload(
             12345,
-            vec![Value::from_i64(1)],
+            vec![Value::from_i64(1)].into(),
             100,
             vec![
                 Value::Text("a]long payload string".into()),
@@ -4507,6 +8219,109 @@ mod hashtests {
         matches
     }
 
+    #[test]
+    fn test_grace_hash_join_driver_matches_reference() {
+        fn drive<T>(io: &Arc<dyn IO>, mut f: impl FnMut() -> Result<IOResult<T>>) -> T {
+            loop {
+                match f().unwrap() {
+                    IOResult::Done(value) => return value,
+                    IOResult::IO(_) => io.step().unwrap(),
+                }
+            }
+        }
+
+        const ROWS: i64 = 100_000;
+        let io: Arc<dyn IO> = Arc::new(MemoryIO::new());
+        // Every build key appears twice, a third of the probe keys match nothing
+        let build_key = |rowid: i64| rowid % (ROWS / 2);
+        let probe_key = |rowid: i64| (rowid * 7) % (ROWS * 3 / 4);
+
+        let config = HashTableConfig {
+            mem_budget: 1024 * 1024,
+            ..Default::default()
+        };
+        let mut join = GraceHashJoin::new(config, io.clone()).unwrap();
+        for rowid in 0..ROWS {
+            let payload = vec![Value::Text(format!("build_{rowid}").into())];
+            drive(&io, || {
+                join.add_build_row(
+                    vec![Value::from_i64(build_key(rowid))],
+                    rowid,
+                    payload.clone(),
+                )
+            });
+        }
+        drive(&io, || join.finish_build());
+        assert!(
+            join.num_partitions() > 1,
+            "build side should be partitioned"
+        );
+
+        let mut joined = vec![];
+        for rowid in 0..ROWS {
+            drive(&io, || {
+                join.add_probe_row(vec![Value::from_i64(probe_key(rowid))], rowid)
+            });
+            while let Some(row) = drive(&io, || join.next_output()) {
+                joined.push(row);
+            }
+        }
+        drive(&io, || join.finish_probe());
+        let mut partitions_joined = vec![];
+        while let Some(row) = drive(&io, || join.next_output()) {
+            let partition = join
+                .current_partition()
+                .expect("joining a spilled partition");
+            if partitions_joined.last() != Some(&partition) {
+                assert!(
+                    !partitions_joined.contains(&partition),
+                    "partition {partition} resumed after another one"
+                );
+                partitions_joined.push(partition);
+            }
+            joined.push(row);
+        }
+        assert!(partitions_joined.len() > 1);
+        assert_eq!(join.current_partition(), None);
+
+        // Nested loop over the build rows sharing the probe row's key
+        let mut build_by_key = std::collections::BTreeMap::<i64, Vec<i64>>::new();
+        for rowid in 0..ROWS {
+            build_by_key
+                .entry(build_key(rowid))
+                .or_default()
+                .push(rowid);
+        }
+        let mut expected = vec![];
+        for probe_rowid in 0..ROWS {
+            for build_rowid in build_by_key
+                .get(&probe_key(probe_rowid))
+                .into_iter()
+                .flatten()
+            {
+                expected.push((*build_rowid, probe_rowid));
+            }
+        }
+        expected.sort();
+
+        for row in &joined {
+            assert_eq!(
+                row.build_payload,
+                vec![Value::Text(format!("build_{}", row.build_rowid).into())]
+            );
+        }
+        let mut actual: Vec<(i64, i64)> = joined
+            .iter()
+            .map(|row| (row.build_rowid, row.probe_rowid))
+            .collect();
+        actual.sort();
+        assert_eq!(actual.len(), expected.len());
+        assert!(
+            actual == expected,
+            "joined output differs from the reference join"
+        );
+    }
+
     #[test]
     fn test_grace_basic() {
         let io = Arc::new(MemoryIO::new());
@@ -4754,6 +8569,358 @@ mod hashtests {
         assert_eq!(match_count, buffered);
     }
 
+    #[test]
+    fn test_unmatched_entries_left_join() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 1024 * 1024,
+            num_keys: 1,
+            collations: vec![CollationSeq::Binary],
+            temp_store: crate::TempStore::Default,
+            track_matched: true,
+            partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+
+        // Build side: (rowid, key), key 30 is never probed and key NULL never matches
+        let build = [
+            (1, Value::from_i64(10)),
+            (2, Value::from_i64(20)),
+            (3, Value::from_i64(30)),
+        ];
+        for (rowid, key) in build.iter().cloned() {
+            let _ = ht.insert(vec![key], rowid, vec![], None).unwrap();
+        }
+        let _ = ht.insert(vec![Value::Null], 4, vec![], None).unwrap();
+        let _ = ht.finalize_build(None).unwrap();
+
+        let mut joined = vec![];
+        for probe_key in [10, 20, 20, 40] {
+            let mut matched = ht
+                .probe(vec![Value::from_i64(probe_key)], None)
+                .unwrap()
+                .map(|e| e.rowid);
+            while let Some(rowid) = matched {
+                ht.mark_current_matched();
+                joined.push((probe_key, rowid));
+                matched = ht.next_match().unwrap().map(|e| e.rowid);
+            }
+        }
+        assert_eq!(joined, vec![(10, 1), (20, 2), (20, 2)]);
+
+        let mut unmatched: Vec<i64> = ht.unmatched_entries().map(|e| e.rowid).collect();
+        unmatched.sort();
+        assert_eq!(unmatched, vec![3, 4]);
+
+        // Agrees with the cursor based scan
+        ht.begin_unmatched_scan();
+        let mut scanned = vec![];
+        while let Some(entry) = ht.next_unmatched() {
+            scanned.push(entry.rowid);
+        }
+        scanned.sort();
+        assert_eq!(scanned, unmatched);
+    }
+
+    #[test]
+    fn test_match_positions_resolve_to_entries() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            mem_budget: 1024 * 1024,
+            num_keys: 1,
+            collations: vec![CollationSeq::Binary],
+            temp_store: crate::TempStore::Default,
+            track_matched: false,
+            partition_count: None,
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+        // Key 7 is inserted for rowids 0, 10, 20, ... between 100 other keys
+        for rowid in 0..100 {
+            let key = if rowid % 10 == 0 { 7 } else { 1000 + rowid };
+            let _ = ht
+                .insert(
+                    vec![Value::from_i64(key)],
+                    rowid,
+                    vec![Value::from_i64(rowid * 2)],
+                    None,
+                )
+                .unwrap();
+        }
+        let _ = ht.finalize_build(None).unwrap();
+
+        let key = [ValueRef::from_i64(7)];
+        let positions = ht.match_positions(&key);
+        assert_eq!(positions.len(), 10);
+        assert_eq!(ht.match_positions(&key), positions);
+        let rowids: Vec<i64> = positions
+            .iter()
+            .map(|&(bucket_idx, entry_idx)| ht.entry_at(bucket_idx, entry_idx).rowid)
+            .collect();
+        assert_eq!(rowids, (0..100).step_by(10).collect::<Vec<i64>>());
+        for &(bucket_idx, entry_idx) in &positions {
+            let entry = ht.entry_at(bucket_idx, entry_idx);
+            assert_eq!(entry.payload_values, vec![Value::from_i64(entry.rowid * 2)]);
+        }
+
+        assert!(ht.match_positions(&[ValueRef::from_i64(8)]).is_empty());
+        assert!(ht.match_positions(&[ValueRef::Null]).is_empty());
+    }
+
+    #[test]
+    fn test_join_modes_with_duplicate_keys() {
+        let build = |join_mode: JoinMode| {
+            let io = Arc::new(MemoryIO::new());
+            let config = HashTableConfig {
+                initial_buckets: 4,
+                mem_budget: 1024 * 1024,
+                num_keys: 1,
+                collations: vec![CollationSeq::Binary],
+                temp_store: crate::TempStore::Default,
+                track_matched: false,
+                partition_count: None,
+                mmap_spill_reads: false,
+                repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+                max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+                enable_bloom_filter: false,
+                expected_entries: None,
+                join_mode,
+                null_safe: false,
+                seed: DEFAULT_SEED,
+                deferred_index: false,
+                max_matches_per_key: None,
+                shrink_on_finalize: true,
+            };
+            let mut ht = HashTable::new(config, io).unwrap();
+            // Key 1 three times, key 2 once
+            for (rowid, key) in [(1, 1), (2, 1), (3, 2), (4, 1)] {
+                let _ = ht
+                    .insert(vec![Value::from_i64(key)], rowid, vec![], None)
+                    .unwrap();
+            }
+            let _ = ht.finalize_build(None).unwrap();
+            ht
+        };
+        let matches = |ht: &mut HashTable, key: i64| {
+            let mut rowids = vec![];
+            let mut entry = ht
+                .probe(vec![Value::from_i64(key)], None)
+                .unwrap()
+                .map(|e| e.rowid);
+            while let Some(rowid) = entry {
+                rowids.push(rowid);
+                entry = ht.next_match().unwrap().map(|e| e.rowid);
+            }
+            rowids
+        };
+
+        let mut ht = build(JoinMode::Inner);
+        assert_eq!(matches(&mut ht, 1), vec![1, 2, 4]);
+        assert_eq!(matches(&mut ht, 2), vec![3]);
+        assert!(matches(&mut ht, 3).is_empty());
+
+        let mut ht = build(JoinMode::Semi);
+        assert_eq!(matches(&mut ht, 1), vec![1]);
+        assert_eq!(matches(&mut ht, 2), vec![3]);
+        assert!(matches(&mut ht, 3).is_empty());
+
+        let mut ht = build(JoinMode::Anti);
+        assert!(ht.probe_exists(vec![Value::from_i64(1)], None).unwrap());
+        assert!(ht.next_match().unwrap().is_none());
+        assert!(ht.probe_exists(vec![Value::from_i64(2)], None).unwrap());
+        assert!(!ht.probe_exists(vec![Value::from_i64(3)], None).unwrap());
+        assert!(!ht.probe_exists(vec![Value::Null], None).unwrap());
+    }
+
+    #[test]
+    fn test_matches_iterator() {
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+        for (rowid, key) in [(1, 1), (2, 1), (3, 2), (4, 1)] {
+            let _ = ht
+                .insert(vec![Value::from_i64(key)], rowid, vec![], None)
+                .unwrap();
+        }
+        let _ = ht.finalize_build(None).unwrap();
+
+        let mut rowids = vec![];
+        for entry in ht.matches(vec![Value::from_i64(1)]).unwrap() {
+            rowids.push(entry.rowid);
+        }
+        assert_eq!(rowids, vec![1, 2, 4]);
+        assert_eq!(ht.matches(vec![Value::from_i64(2)]).unwrap().count(), 1);
+        assert_eq!(ht.matches(vec![Value::from_i64(3)]).unwrap().count(), 0);
+        assert_eq!(ht.matches(vec![Value::Null]).unwrap().count(), 0);
+
+        // probe and next_match step through the same entries
+        let entry = ht.probe(vec![Value::from_i64(1)], None).unwrap();
+        assert_eq!(entry.map(|e| e.rowid), Some(1));
+        let entry = ht.next_match().unwrap();
+        assert_eq!(entry.map(|e| e.rowid), Some(2));
+        let entry = ht.next_match().unwrap();
+        assert_eq!(entry.map(|e| e.rowid), Some(4));
+        assert!(ht.next_match().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_hash_join_key_owned_matches_ref() {
+        let collations = [
+            CollationSeq::NoCase,
+            CollationSeq::Binary,
+            CollationSeq::Rtrim,
+        ];
+        let keys = [
+            vec![
+                Value::from_i64(10),
+                Value::from_f64(2.5),
+                Value::build_text("a  "),
+            ],
+            vec![
+                Value::from_f64(10.0),
+                Value::Null,
+                Value::from_blob(vec![1, 2, 3]),
+            ],
+            vec![
+                Value::build_text("MiXeD"),
+                Value::build_text("x"),
+                Value::Null,
+            ],
+            vec![
+                Value::from_f64(-0.0),
+                Value::from_i64(i64::MIN),
+                Value::from_i64(7),
+            ],
+        ];
+        for key in &keys {
+            let key_refs: Vec<ValueRef> = key.iter().map(|v| v.as_ref()).try_collect().unwrap();
+            for seed in [DEFAULT_SEED, 42] {
+                assert_eq!(
+                    hash_join_key_owned(key, &collations, seed),
+                    hash_join_key(&key_refs, &collations, seed)
+                );
+            }
+        }
+
+        // The probe cursor compares against the stored probe keys in place.
+        let io = Arc::new(MemoryIO::new());
+        let config = HashTableConfig {
+            initial_buckets: 4,
+            collations: vec![CollationSeq::NoCase],
+            ..Default::default()
+        };
+        let mut ht = HashTable::new(config, io).unwrap();
+        let _ = ht
+            .insert(vec![Value::build_text("abc")], 1, vec![], None)
+            .unwrap();
+        let _ = ht.finalize_build(None).unwrap();
+        ht.begin_probe(vec![Value::build_text("ABC")], None)
+            .unwrap();
+        let cursor = ht.probe_cursor();
+        let stored = ht.current_probe_keys.as_deref().unwrap();
+        assert!(std::ptr::eq(cursor.keys, stored));
+        assert_eq!(cursor.map(|entry| entry.rowid).collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[test]
+    fn test_null_safe_multi_column_keys() {
+        let build = |null_safe: bool| {
+            let io = Arc::new(MemoryIO::new());
+            let config = HashTableConfig {
+                initial_buckets: 4,
+                mem_budget: 1024 * 1024,
+                num_keys: 2,
+                collations: vec![CollationSeq::Binary, CollationSeq::Binary],
+                temp_store: crate::TempStore::Default,
+                track_matched: false,
+                partition_count: None,
+                mmap_spill_reads: false,
+                repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+                max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+                enable_bloom_filter: false,
+                expected_entries: None,
+                join_mode: JoinMode::Inner,
+                null_safe,
+                deferred_index: false,
+                max_matches_per_key: None,
+                shrink_on_finalize: true,
+            };
+            let mut ht = HashTable::new(config, io).unwrap();
+            let rows = [
+                (1, [Value::from_i64(1), Value::from_i64(2)]),
+                (2, [Value::from_i64(1), Value::Null]),
+                (3, [Value::Null, Value::from_i64(2)]),
+            ];
+            for (rowid, keys) in rows {
+                let _ = ht.insert(keys.to_vec(), rowid, vec![], None).unwrap();
+            }
+            let _ = ht.finalize_build(None).unwrap();
+            ht
+        };
+        let probe = |ht: &mut HashTable, keys: [Value; 2]| {
+            let refs: Vec<ValueRef> = keys.iter().map(|v| v.as_ref()).collect();
+            let visited = {
+                let mut rowids = vec![];
+                let _ = ht.visit_matches(&refs, |e| {
+                    rowids.push(e.rowid);
+                    ControlFlow::Continue(())
+                });
+                rowids
+            };
+            let probed = ht.probe(keys.to_vec(), None).unwrap().map(|e| e.rowid);
+            assert_eq!(probed, visited.first().copied());
+            visited
+        };
+
+        // NULL in either column excludes the row from both sides of an equi-join
+        let mut ht = build(false);
+        assert_eq!(ht.stats().num_entries, 1);
+        assert_eq!(
+            probe(&mut ht, [Value::from_i64(1), Value::from_i64(2)]),
+            vec![1]
+        );
+        assert!(probe(&mut ht, [Value::from_i64(1), Value::Null]).is_empty());
+        assert!(probe(&mut ht, [Value::Null, Value::from_i64(2)]).is_empty());
+
+        // IS NOT DISTINCT FROM: NULL matches NULL in the same column only
+        let mut ht = build(true);
+        assert_eq!(ht.stats().num_entries, 3);
+        assert_eq!(
+            probe(&mut ht, [Value::from_i64(1), Value::from_i64(2)]),
+            vec![1]
+        );
+        assert_eq!(probe(&mut ht, [Value::from_i64(1), Value::Null]), vec![2]);
+        assert_eq!(probe(&mut ht, [Value::Null, Value::from_i64(2)]), vec![3]);
+        assert!(probe(&mut ht, [Value::Null, Value::Null]).is_empty());
+        assert!(probe(&mut ht, [Value::from_i64(2), Value::Null]).is_empty());
+    }
+
     #[test]
     fn test_grace_unmatched_scan_uses_current_partition() {
         let io = Arc::new(MemoryIO::new());
@@ -4765,6 +8932,17 @@ mod hashtests {
             temp_store: crate::TempStore::Default,
             track_matched: true,
             partition_count: Some(4),
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
         let mut ht = HashTable::new(config, io).unwrap();
 
@@ -4911,6 +9089,17 @@ mod hashtests {
             temp_store: crate::TempStore::Default,
             track_matched: true,
             partition_count: Some(16),
+            mmap_spill_reads: false,
+            repartition_fanout: DEFAULT_REPARTITION_FANOUT,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            enable_bloom_filter: false,
+            expected_entries: None,
+            join_mode: JoinMode::Inner,
+            null_safe: false,
+            seed: DEFAULT_SEED,
+            deferred_index: false,
+            max_matches_per_key: None,
+            shrink_on_finalize: true,
         };
         let mut ht = HashTable::new(config, io).unwrap();
 
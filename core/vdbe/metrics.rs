@@ -3,6 +3,9 @@ use std::fmt;
 /// Hash join spill/probe metrics.
 #[derive(Debug, Default, Clone)]
 pub struct HashJoinMetrics {
+    // Build metrics
+    pub build_heap_keys: u64,
+
     // Spill metrics
     pub spill_bytes_written: u64,
     pub spill_chunks: u64,
@@ -11,6 +14,8 @@ pub struct HashJoinMetrics {
 
     // Load metrics
     pub load_bytes_read: u64,
+    pub load_read_calls: u64,
+    pub load_mmap_chunks: u64,
 
     // Probe metrics
     pub probe_calls: u64,
@@ -26,6 +31,7 @@ pub struct HashJoinMetrics {
 
 impl HashJoinMetrics {
     pub fn merge(&mut self, other: &HashJoinMetrics) {
+        self.build_heap_keys = self.build_heap_keys.saturating_add(other.build_heap_keys);
         self.spill_bytes_written = self
             .spill_bytes_written
             .saturating_add(other.spill_bytes_written);
@@ -37,6 +43,8 @@ impl HashJoinMetrics {
             .spill_max_partition_bytes
             .max(other.spill_max_partition_bytes);
         self.load_bytes_read = self.load_bytes_read.saturating_add(other.load_bytes_read);
+        self.load_read_calls = self.load_read_calls.saturating_add(other.load_read_calls);
+        self.load_mmap_chunks = self.load_mmap_chunks.saturating_add(other.load_mmap_chunks);
         self.probe_calls = self.probe_calls.saturating_add(other.probe_calls);
         self.probe_spill_bytes_written = self
             .probe_spill_bytes_written
@@ -155,6 +163,11 @@ impl fmt::Display for StatementMetrics {
         writeln!(f, "    Next:             {}", self.btree_next)?;
         writeln!(f, "    Prev:             {}", self.btree_prev)?;
         writeln!(f, "  Hash Join:")?;
+        writeln!(
+            f,
+            "    Heap keys:        {}",
+            self.hash_join.build_heap_keys
+        )?;
         writeln!(
             f,
             "    Spill bytes:      {}",
@@ -176,6 +189,16 @@ impl fmt::Display for StatementMetrics {
             "    Load bytes:       {}",
             self.hash_join.load_bytes_read
         )?;
+        writeln!(
+            f,
+            "    Load reads:       {}",
+            self.hash_join.load_read_calls
+        )?;
+        writeln!(
+            f,
+            "    Load mmap chunks: {}",
+            self.hash_join.load_mmap_chunks
+        )?;
         writeln!(f, "    Probes:           {}", self.hash_join.probe_calls)?;
         writeln!(
             f,
@@ -55,7 +55,7 @@ use crate::{
             OpParseSchemaState, OpProgramState, OpRowIdState, OpSeekState, OpTransactionState,
             VacuumIntoOpContext,
         },
-        hash_table::HashTable,
+        hash_table::{HashTable, JoinKey},
         metrics::StatementMetrics,
         vacuum::VacuumInPlaceOpContext,
     },
@@ -253,6 +253,11 @@ impl CommitState {
 #[derive(Debug, Clone, PartialEq)]
 pub enum Register {
     Value(Value),
+    /// A value written by [Insn::Variable] from a bound statement parameter, which
+    /// otherwise reads like [Register::Value]. [Insn::Copy] and [Insn::Move] keep the
+    /// variant; any other write turns the register back into a [Register::Value].
+    /// External scalar functions use this for `sqlite3_value_frombind`.
+    Bound(Value),
     Aggregate(AggContext),
     Record(ImmutableRecord),
 }
@@ -260,7 +265,25 @@ pub enum Register {
 impl Register {
     #[inline]
     pub const fn is_null(&self) -> bool {
-        matches!(self, Register::Value(Value::Null))
+        matches!(
+            self,
+            Register::Value(Value::Null) | Register::Bound(Value::Null)
+        )
+    }
+
+    /// Whether the register holds a value read from a bound statement parameter.
+    #[inline]
+    pub const fn is_bound(&self) -> bool {
+        matches!(self, Register::Bound(_))
+    }
+
+    /// Turn a [Register::Bound] into a plain [Register::Value] before its value is
+    /// modified in place.
+    #[inline]
+    pub fn unbind(&mut self) {
+        if let Register::Bound(value) = self {
+            *self = Register::Value(std::mem::replace(value, Value::Null));
+        }
     }
 
     #[inline(always)]
@@ -421,7 +444,7 @@ impl ProgramExecutionState {
 /// Allows HashBuild to resume cleanly after async I/O without re-reading the row.
 #[derive(Debug)]
 pub struct OpHashBuildState {
-    pub key_values: crate::alloc::Vec<Value>,
+    pub key_values: JoinKey,
     pub key_idx: usize,
     pub payload_values: crate::alloc::Vec<Value>,
     pub payload_idx: usize,
@@ -1292,7 +1315,7 @@ pub enum EndStatement {
 impl Register {
     pub fn get_value(&self) -> &Value {
         match self {
-            Register::Value(v) => v,
+            Register::Value(v) | Register::Bound(v) => v,
             Register::Record(r) => {
                 turso_assert!(!r.is_invalidated());
                 r.as_blob_value()
@@ -1737,7 +1760,9 @@ impl Program {
                     {
                         if old_reg != new_reg {
                             match new_reg {
-                                Register::Value(v) => eprintln!("R[{i}] = {v}"),
+                                Register::Value(v) | Register::Bound(v) => {
+                                    eprintln!("R[{i}] = {v}")
+                                }
                                 Register::Aggregate(_) => eprintln!("R[{i}] = <aggregate>"),
                                 Register::Record(_) => eprintln!("R[{i}] = <record>"),
                             }
@@ -2798,7 +2823,7 @@ impl Row {
                 .expect("row value pointer should be valid")
         };
         let value = match value {
-            Register::Value(value) => value,
+            Register::Value(value) | Register::Bound(value) => value,
             _ => unreachable!("a row should be formed of values only"),
         };
         T::from_value(value)
@@ -2812,7 +2837,7 @@ impl Row {
                 .expect("row value pointer should be valid")
         };
         match value {
-            Register::Value(value) => value,
+            Register::Value(value) | Register::Bound(value) => value,
             _ => unreachable!("a row should be formed of values only"),
         }
     }
@@ -1183,6 +1183,17 @@ impl Value {
     }
 
     pub fn exec_like(pattern: &str, text: &str, escape: Option<char>) -> Result<bool, LimboError> {
+        Self::exec_like_with_case(pattern, text, escape, false)
+    }
+
+    /// LIKE with `PRAGMA case_sensitive_like` applied: when `case_sensitive` is set,
+    /// ASCII letters only match themselves, as with GLOB.
+    pub fn exec_like_with_case(
+        pattern: &str,
+        text: &str,
+        escape: Option<char>,
+        case_sensitive: bool,
+    ) -> Result<bool, LimboError> {
         const MAX_LIKE_PATTERN_LENGTH: usize = 50000;
         if pattern.len() > MAX_LIKE_PATTERN_LENGTH {
             return Err(LimboError::Constraint(
@@ -1193,10 +1204,15 @@ impl Value {
         let text = sqlite_text_prefix(text);
 
         let has_escape = escape.is_some_and(|e| pattern.contains(e));
+        let (info, eq): (_, fn(&str, &str) -> bool) = if case_sensitive {
+            (&CASE_SENSITIVE_LIKE_INFO, |a, b| a == b)
+        } else {
+            (&LIKE_INFO, |a, b| a.eq_ignore_ascii_case(b))
+        };
 
         // 1. Exact match (no wildcards)
         if !has_escape && !pattern.contains(['%', '_']) {
-            return Ok(pattern.eq_ignore_ascii_case(text));
+            return Ok(eq(pattern, text));
         }
 
         // 2. Fast Path: 'abc%' (Prefix)
@@ -1206,7 +1222,7 @@ impl Value {
         {
             let prefix = &pattern[..pattern.len() - 1];
             if text.len() >= prefix.len() && text.is_char_boundary(prefix.len()) {
-                return Ok(text[..prefix.len()].eq_ignore_ascii_case(prefix));
+                return Ok(eq(&text[..prefix.len()], prefix));
             }
             // Fall through to pattern_compare if boundary check fails (multi-byte UTF-8)
         }
@@ -1216,12 +1232,12 @@ impl Value {
             let suffix = &pattern[1..];
             let start = text.len().wrapping_sub(suffix.len());
             if text.len() >= suffix.len() && text.is_char_boundary(start) {
-                return Ok(text[start..].eq_ignore_ascii_case(suffix));
+                return Ok(eq(&text[start..], suffix));
             }
             // Fall through to pattern_compare if boundary check fails (multi-byte UTF-8)
         }
 
-        Ok(pattern_compare(pattern, text, &LIKE_INFO, escape) == CompareResult::Match)
+        Ok(pattern_compare(pattern, text, info, escape) == CompareResult::Match)
     }
 
     pub fn exec_glob(pattern: &str, text: &str) -> Result<bool, LimboError> {
@@ -1405,6 +1421,11 @@ const LIKE_INFO: PatternInfo = PatternInfo {
     no_case: true,
 };
 
+const CASE_SENSITIVE_LIKE_INFO: PatternInfo = PatternInfo {
+    no_case: false,
+    ..LIKE_INFO
+};
+
 const GLOB_INFO: PatternInfo = PatternInfo {
     match_all: '*',
     match_one: '?',
@@ -2585,6 +2606,21 @@ mod tests {
         assert!(!Value::exec_like("%a.ab", "aaaa", None).unwrap());
     }
 
+    #[test]
+    fn test_exec_like_case_sensitive() {
+        assert!(Value::exec_like("abc", "ABC", None).unwrap());
+        assert!(!Value::exec_like_with_case("abc", "ABC", None, true).unwrap());
+        assert!(Value::exec_like_with_case("abc", "abc", None, true).unwrap());
+        // Fast paths
+        assert!(!Value::exec_like_with_case("ab%", "ABc", None, true).unwrap());
+        assert!(Value::exec_like_with_case("ab%", "abC", None, true).unwrap());
+        assert!(!Value::exec_like_with_case("%bc", "aBC", None, true).unwrap());
+        assert!(Value::exec_like_with_case("%bc", "Abc", None, true).unwrap());
+        // General pattern
+        assert!(!Value::exec_like_with_case("a_c%", "ABCD", None, true).unwrap());
+        assert!(Value::exec_like_with_case("a_c%", "aBcD", None, true).unwrap());
+    }
+
     #[test]
     fn test_exec_like_with_escape() {
         assert!(Value::exec_like("abcX%", "abc%", Some('X')).unwrap());
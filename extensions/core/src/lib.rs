@@ -14,7 +14,7 @@ pub use turso_macros::VfsDerive;
 pub use turso_macros::{
     register_extension, scalar, AggregateDerive, ScalarDerive, VTabModuleDerive,
 };
-pub use types::{ResultCode, StepResult, Value, ValueType};
+pub use types::{with_bound_args, ResultCode, StepResult, Value, ValueType};
 #[cfg(feature = "vfs")]
 pub use vfs_modules::{
     BufferRef, Callback, IOCallback, RegisterVfsFn, SendPtr, VfsExtension, VfsFile, VfsFileImpl,
@@ -1,3 +1,4 @@
+use std::cell::RefCell;
 use std::fmt::Display;
 
 /// Error type is of type ExtError which can be
@@ -113,6 +114,29 @@ pub struct Value {
     value_type: ValueType,
     value: ValueData,
 }
+
+thread_local! {
+    /// Address and per-argument flags of the scalar function arguments currently being
+    /// passed on this thread, see [Value::from_bind]. Kept outside of [Value] so its
+    /// `repr(C)` layout, which extensions are built against, stays the same.
+    static BOUND_ARGS: RefCell<Option<(usize, Vec<bool>)>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with the values in `argv` whose flag in `bound` is set reporting
+/// [Value::from_bind]. Restores the previous flags afterwards, so nested function
+/// calls made from within `f` see their own arguments.
+pub fn with_bound_args<R>(argv: &[Value], bound: Vec<bool>, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<(usize, Vec<bool>)>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            BOUND_ARGS.with(|args| *args.borrow_mut() = previous);
+        }
+    }
+    let previous = BOUND_ARGS.with(|args| args.replace(Some((argv.as_ptr() as usize, bound))));
+    let _restore = Restore(previous);
+    f()
+}
 impl Default for Value {
     fn default() -> Self {
         Self::null()
@@ -294,6 +318,24 @@ impl Value {
         self.value_type
     }
 
+    /// Returns true if the value is a scalar function argument that originated from a
+    /// bound parameter (`?`, `:name`, ...) rather than from a literal or a column,
+    /// mirroring `sqlite3_value_frombind`. Always false outside of [with_bound_args].
+    pub fn from_bind(&self) -> bool {
+        let addr = self as *const Value as usize;
+        BOUND_ARGS.with(|args| match &*args.borrow() {
+            Some((start, bound)) if addr >= *start => {
+                let offset = addr - start;
+                offset % std::mem::size_of::<Value>() == 0
+                    && bound
+                        .get(offset / std::mem::size_of::<Value>())
+                        .copied()
+                        .unwrap_or(false)
+            }
+            _ => false,
+        })
+    }
+
     /// Returns the float value or casts the relevant value to a float
     pub fn to_float(&self) -> Option<f64> {
         match self.value_type {
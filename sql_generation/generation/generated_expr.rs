@@ -145,11 +145,11 @@ fn generate_expr_inner<R: Rng + ?Sized>(
 /// Check if two column types are compatible for expressions.
 fn types_compatible(source: &ColumnType, target: &ColumnType) -> bool {
     match (source, target) {
-        // Integer and Float are interchangeable
-        (ColumnType::Integer, ColumnType::Integer)
-        | (ColumnType::Integer, ColumnType::Float)
-        | (ColumnType::Float, ColumnType::Integer)
-        | (ColumnType::Float, ColumnType::Float) => true,
+        // Integer, Float and Numeric are interchangeable
+        (
+            ColumnType::Integer | ColumnType::Float | ColumnType::Numeric,
+            ColumnType::Integer | ColumnType::Float | ColumnType::Numeric,
+        ) => true,
         // Text only with Text
         (ColumnType::Text, ColumnType::Text) => true,
         // Blob only with Blob
@@ -161,7 +161,7 @@ fn types_compatible(source: &ColumnType, target: &ColumnType) -> bool {
 /// Generate a type-appropriate literal.
 fn generate_literal<R: Rng + ?Sized>(rng: &mut R, target_type: &ColumnType) -> Expr {
     match target_type {
-        ColumnType::Integer => {
+        ColumnType::Integer | ColumnType::Numeric => {
             // Use smaller integer values to avoid overflow in expressions
             let val = rng.random_range(-1000i64..1000);
             Expr::Literal(ast::Literal::Numeric(val.to_string()))
@@ -186,7 +186,7 @@ fn generate_literal<R: Rng + ?Sized>(rng: &mut R, target_type: &ColumnType) -> E
 /// Pick an appropriate binary operator for the target type.
 fn pick_binary_op<R: Rng + ?Sized>(rng: &mut R, target_type: &ColumnType) -> Option<Operator> {
     match target_type {
-        ColumnType::Integer | ColumnType::Float => {
+        ColumnType::Integer | ColumnType::Float | ColumnType::Numeric => {
             let ops = [
                 Operator::Add,
                 Operator::Subtract,
@@ -209,7 +209,7 @@ fn pick_binary_op<R: Rng + ?Sized>(rng: &mut R, target_type: &ColumnType) -> Opt
 /// Pick an appropriate unary operator for the target type (if any).
 fn pick_unary_op<R: Rng + ?Sized>(rng: &mut R, target_type: &ColumnType) -> Option<UnaryOperator> {
     match target_type {
-        ColumnType::Integer | ColumnType::Float => {
+        ColumnType::Integer | ColumnType::Float | ColumnType::Numeric => {
             if rng.random_bool(0.5) {
                 Some(UnaryOperator::Negative)
             } else {
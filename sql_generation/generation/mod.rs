@@ -204,7 +204,7 @@ pub fn pick_unique<'a, T: PartialEq, R: Rng + ?Sized>(
 #[cfg(test)]
 pub mod tests {
     use crate::{
-        generation::{GenerationContext, Opts},
+        generation::{GenerationContext, NameCounter, Opts},
         model::table::Table,
     };
 
@@ -212,6 +212,7 @@ pub mod tests {
     pub struct TestContext {
         pub opts: Opts,
         pub tables: Vec<Table>,
+        pub name_counter: NameCounter,
     }
 
     impl Default for TestContext {
@@ -222,6 +223,7 @@ pub mod tests {
             let mut ctx = Self {
                 opts: Default::default(),
                 tables: Default::default(),
+                name_counter: Default::default(),
             };
             ctx.opts.table.generated_columns.enable = false;
             ctx
@@ -236,5 +238,9 @@ pub mod tests {
         fn opts(&self) -> &Opts {
             &self.opts
         }
+
+        fn name_counter(&self) -> &NameCounter {
+            &self.name_counter
+        }
     }
 }
@@ -2,6 +2,7 @@ use std::{
     fmt::Display,
     num::{NonZero, NonZeroU32},
     ops::Range,
+    sync::atomic::{AtomicU64, Ordering},
 };
 
 use garde::Validate;
@@ -15,6 +16,31 @@ use crate::model::table::Table;
 pub trait GenerationContext {
     fn tables(&self) -> &Vec<Table>;
     fn opts(&self) -> &Opts;
+    /// Counter behind the numeric suffix of generated names. Names drawn from the same
+    /// counter never repeat, even before they show up in [GenerationContext::tables].
+    fn name_counter(&self) -> &NameCounter;
+    /// Names of the databases attached to the connection, which generated statements
+    /// may qualify table names with.
+    fn attached_dbs(&self) -> &[String] {
+        &[]
+    }
+}
+
+/// Per-run source of name suffixes for [crate::model::table::Name]. Cloning copies the
+/// current count, so a cloned context goes on to draw the same names as the original.
+#[derive(Debug, Default)]
+pub struct NameCounter(AtomicU64);
+
+impl NameCounter {
+    pub fn next(&self) -> u64 {
+        self.0.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+impl Clone for NameCounter {
+    fn clone(&self) -> Self {
+        Self(AtomicU64::new(self.0.load(Ordering::Relaxed)))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate, Default)]
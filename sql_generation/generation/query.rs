@@ -710,6 +710,7 @@ impl Arbitrary for CreateIndex {
                 index_name,
                 table_name: table.name.clone(),
                 columns,
+                where_clause: None,
             },
         }
     }
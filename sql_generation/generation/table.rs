@@ -1,21 +1,31 @@
 use std::collections::{HashMap, HashSet};
-use std::sync::atomic::{AtomicU64, Ordering};
 
 use indexmap::IndexSet;
 use rand::Rng;
-use turso_parser::ast::{ColumnConstraint, GeneratedColumnType};
+use turso_parser::ast::{self, ColumnConstraint, GeneratedColumnType};
 
 use crate::generation::generated_expr::generate_column_expr_with_refs;
-use crate::generation::{pick, readable_name_custom, Arbitrary, GenerationContext};
-use crate::model::table::{Column, ColumnType, Name, Table};
-
-static COUNTER: AtomicU64 = AtomicU64::new(0);
+use crate::generation::{pick, readable_name_custom, Arbitrary, ArbitraryFrom, GenerationContext};
+use crate::model::table::{Column, ColumnType, Name, SimValue, Table};
 
 impl Arbitrary for Name {
-    fn arbitrary<R: Rng + ?Sized, C: GenerationContext>(rng: &mut R, _c: &C) -> Self {
-        let base = readable_name_custom("_", rng).replace("-", "_");
-        let id = COUNTER.fetch_add(1, Ordering::Relaxed);
-        Name(format!("{base}_{id}"))
+    /// The numeric suffix comes from the context's [crate::generation::NameCounter] rather
+    /// than a global counter, so names only depend on the seed and the context, and never
+    /// repeat within a run even before the context sees them. A name already taken by a
+    /// table or index that did not come from the counter is drawn again.
+    fn arbitrary<R: Rng + ?Sized, C: GenerationContext>(rng: &mut R, context: &C) -> Self {
+        loop {
+            let base = readable_name_custom("_", rng).replace("-", "_");
+            let id = context.name_counter().next();
+            let name = format!("{base}_{id}");
+            let taken = context
+                .tables()
+                .iter()
+                .any(|t| t.name == name || t.indexes.iter().any(|index| index.index_name == name));
+            if !taken {
+                return Name(name);
+            }
+        }
     }
 }
 
@@ -171,6 +181,21 @@ impl Arbitrary for Table {
     }
 }
 
+impl Column {
+    /// An `INTEGER PRIMARY KEY AUTOINCREMENT` column named `name`.
+    pub fn autoincrement_primary_key(name: String) -> Self {
+        Self {
+            name,
+            column_type: ColumnType::Integer,
+            constraints: vec![ColumnConstraint::PrimaryKey {
+                order: None,
+                conflict_clause: None,
+                auto_increment: true,
+            }],
+        }
+    }
+}
+
 impl Arbitrary for Column {
     fn arbitrary<R: Rng + ?Sized, C: GenerationContext>(rng: &mut R, context: &C) -> Self {
         let name = Name::arbitrary(rng, context).0;
@@ -190,8 +215,99 @@ impl Arbitrary for Column {
     }
 }
 
+impl Column {
+    /// Generates a `NOT NULL` column, which also gets a literal `DEFAULT` of its own
+    /// type when `with_default` is set.
+    pub fn arbitrary_not_null<R: Rng + ?Sized, C: GenerationContext>(
+        rng: &mut R,
+        context: &C,
+        with_default: bool,
+    ) -> Self {
+        let mut column = Column::arbitrary(rng, context);
+        column.constraints = vec![ColumnConstraint::NotNull {
+            nullable: false,
+            conflict_clause: None,
+        }];
+        if with_default {
+            let default = SimValue::arbitrary_from(rng, context, &column.column_type);
+            column
+                .constraints
+                .push(ColumnConstraint::Default(Box::new(ast::Expr::Literal(
+                    default.into(),
+                ))));
+        }
+        column
+    }
+}
+
 impl Arbitrary for ColumnType {
     fn arbitrary<R: Rng + ?Sized, C: GenerationContext>(rng: &mut R, _context: &C) -> Self {
         pick(&[Self::Integer, Self::Float, Self::Text, Self::Blob], rng).to_owned()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng as _;
+    use rand_chacha::ChaCha8Rng;
+
+    use crate::{
+        generation::{tests::TestContext, Arbitrary},
+        model::table::{Column, Name, Table},
+    };
+
+    #[test]
+    fn names_are_determined_by_the_seed() {
+        let generate = |seed: u64| {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            let mut context = TestContext::default();
+            let mut names = vec![];
+            for _ in 0..100 {
+                let table = Table::arbitrary(&mut rng, &context);
+                names.push(table.name.clone());
+                context.tables.push(table);
+            }
+            names.push(Name::arbitrary(&mut rng, &context).0);
+            names
+        };
+
+        let names = generate(42);
+        assert_eq!(names, generate(42));
+        assert_ne!(names, generate(43));
+        let unique: std::collections::HashSet<&String> = names.iter().collect();
+        assert_eq!(unique.len(), names.len());
+    }
+
+    #[test]
+    fn names_are_unique_before_the_context_sees_them() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut context = TestContext::default();
+        context.opts.table.rowid_alias_prob = 1.0;
+
+        let mut names = std::collections::HashSet::new();
+        for _ in 0..100 {
+            let table = Table::arbitrary(&mut rng, &context);
+            assert!(names.insert(table.name.clone()));
+            for column in table.columns {
+                assert!(names.insert(column.name));
+            }
+            assert!(names.insert(Name::arbitrary(&mut rng, &context).0));
+        }
+    }
+
+    #[test]
+    fn not_null_column_can_have_a_default() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let context = TestContext::default();
+
+        let column = Column::arbitrary_not_null(&mut rng, &context, true);
+        let sql = column.to_string();
+        let not_null = sql.find(" NOT NULL").expect("column should be NOT NULL");
+        let default = sql.find(" DEFAULT ").expect("column should have a DEFAULT");
+        assert!(not_null < default, "{sql}");
+
+        let column = Column::arbitrary_not_null(&mut rng, &context, false);
+        let sql = column.to_string();
+        assert!(sql.ends_with(" NOT NULL"), "{sql}");
+    }
+}
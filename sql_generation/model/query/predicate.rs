@@ -6,9 +6,9 @@ use turso_parser::ast::{
     fmt::{BlankContext, ToTokens},
 };
 
-use crate::model::table::{SimValue, Table, TableContext};
+use crate::model::table::{ColumnType, SimValue, Table, TableContext};
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Predicate(pub ast::Expr);
 
 impl Predicate {
@@ -130,9 +130,18 @@ pub fn expr_to_value<T: TableContext>(
                 })
         }
         ast::Expr::Literal(literal) => Some(literal.into()),
-        ast::Expr::Binary(lhs, op, rhs) => {
-            let lhs = expr_to_value(lhs, row, table)?;
-            let rhs = expr_to_value(rhs, row, table)?;
+        ast::Expr::Binary(lhs_expr, op, rhs_expr) => {
+            let mut lhs = expr_to_value(lhs_expr, row, table)?;
+            let mut rhs = expr_to_value(rhs_expr, row, table)?;
+            // Comparing two columns converts the operands according to their affinities
+            if is_comparison(*op) {
+                if let (Some(lhs_type), Some(rhs_type)) =
+                    (column_type(lhs_expr, table), column_type(rhs_expr, table))
+                {
+                    lhs = lhs.apply_comparison_affinity(lhs_type, rhs_type);
+                    rhs = rhs.apply_comparison_affinity(rhs_type, lhs_type);
+                }
+            }
             Some(lhs.binary_compare(&rhs, *op))
         }
         ast::Expr::Like {
@@ -160,6 +169,34 @@ pub fn expr_to_value<T: TableContext>(
     }
 }
 
+fn is_comparison(op: ast::Operator) -> bool {
+    matches!(
+        op,
+        ast::Operator::Equals
+            | ast::Operator::NotEquals
+            | ast::Operator::Less
+            | ast::Operator::LessEquals
+            | ast::Operator::Greater
+            | ast::Operator::GreaterEquals
+            | ast::Operator::Is
+            | ast::Operator::IsNot
+    )
+}
+
+/// Returns the declared type of the column `expr` refers to, if it is a plain column reference
+fn column_type<T: TableContext>(expr: &ast::Expr, table: &T) -> Option<ColumnType> {
+    match expr {
+        ast::Expr::DoublyQualified(_, _, col_name)
+        | ast::Expr::Qualified(_, col_name)
+        | ast::Expr::Name(col_name)
+        | ast::Expr::Id(col_name) => table
+            .columns()
+            .find(|col_ctx| col_ctx.column.name == col_name.as_str())
+            .map(|col_ctx| col_ctx.column.column_type),
+        _ => None,
+    }
+}
+
 impl Display for Predicate {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.0.displayer(&BlankContext).fmt(f)
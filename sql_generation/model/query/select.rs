@@ -164,6 +164,10 @@ pub enum CompoundOperator {
     Union,
     /// `UNION ALL`
     UnionAll,
+    /// `INTERSECT`
+    Intersect,
+    /// `EXCEPT`
+    Except,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -353,6 +357,8 @@ impl Select {
                         operator: match compound.operator {
                             CompoundOperator::Union => ast::CompoundOperator::Union,
                             CompoundOperator::UnionAll => ast::CompoundOperator::UnionAll,
+                            CompoundOperator::Intersect => ast::CompoundOperator::Intersect,
+                            CompoundOperator::Except => ast::CompoundOperator::Except,
                         },
                         select: ast::OneSelect::Select {
                             distinctness: Some(compound.select.distinctness),
@@ -2,7 +2,7 @@ use std::{fmt::Display, hash::Hash, ops::Deref};
 
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
-use turso_core::{numeric::Numeric, types, LimboError};
+use turso_core::{numeric::Numeric, types, vdbe::affinity::apply_numeric_affinity, LimboError};
 use turso_parser::ast::{self, ColumnConstraint, SortOrder};
 
 use crate::model::query::predicate::Predicate;
@@ -105,6 +105,19 @@ impl Column {
             .any(|c| matches!(c, ColumnConstraint::PrimaryKey { .. }))
     }
 
+    /// True if this column is an `INTEGER PRIMARY KEY AUTOINCREMENT`.
+    pub fn is_autoincrement(&self) -> bool {
+        self.constraints.iter().any(|c| {
+            matches!(
+                c,
+                ColumnConstraint::PrimaryKey {
+                    auto_increment: true,
+                    ..
+                }
+            )
+        })
+    }
+
     pub fn is_generated(&self) -> bool {
         self.constraints
             .iter()
@@ -117,6 +130,13 @@ impl Column {
             _ => None,
         })
     }
+
+    pub fn default_expr(&self) -> Option<&ast::Expr> {
+        self.constraints.iter().find_map(|c| match c {
+            ColumnConstraint::Default(expr) => Some(expr.as_ref()),
+            _ => None,
+        })
+    }
 }
 
 impl Display for Column {
@@ -135,12 +155,15 @@ impl Display for Column {
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ColumnType {
     Integer,
     Float,
     Text,
     Blob,
+    /// NUMERIC affinity. Not produced by [ColumnType]'s `Arbitrary` impl, which the
+    /// model's conversions are not written for.
+    Numeric,
 }
 
 impl Display for ColumnType {
@@ -150,15 +173,18 @@ impl Display for ColumnType {
             Self::Float => write!(f, "REAL"),
             Self::Text => write!(f, "TEXT"),
             Self::Blob => write!(f, "BLOB"),
+            Self::Numeric => write!(f, "NUMERIC"),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Index {
     pub table_name: String,
     pub index_name: String,
     pub columns: Vec<(String, SortOrder)>,
+    /// `WHERE` clause of a partial index
+    pub where_clause: Option<Predicate>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -234,13 +260,33 @@ impl SimValue {
     fn sqlite_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         match (&self.0, &other.0) {
             (types::Value::Null, _) | (_, types::Value::Null) => None,
-            _ => Some(self.0.cmp(&other.0)),
+            _ => Some(self.storage_class_cmp(other)),
+        }
+    }
+
+    /// Orders values the way SQLite does: by storage class first (NULL < INTEGER/REAL
+    /// < TEXT < BLOB), and by content only within a class. See
+    /// <https://www.sqlite.org/datatype3.html#sort_order>. Text uses BINARY collation.
+    pub fn storage_class_cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let class = |value: &types::Value| match value {
+            types::Value::Null => 0,
+            types::Value::Numeric(_) => 1,
+            types::Value::Text(_) => 2,
+            types::Value::Blob(_) => 3,
+        };
+        match (&self.0, &other.0) {
+            (types::Value::Numeric(a), types::Value::Numeric(b)) => a.cmp(b),
+            (types::Value::Text(a), types::Value::Text(b)) => {
+                a.as_str().as_bytes().cmp(b.as_str().as_bytes())
+            }
+            (types::Value::Blob(a), types::Value::Blob(b)) => a.cmp(b),
+            (a, b) => class(a).cmp(&class(b)),
         }
     }
 
     pub fn unique_for_type(column_type: &ColumnType, offset: i64) -> Self {
         match column_type {
-            ColumnType::Integer => SimValue(types::Value::from_i64(offset)),
+            ColumnType::Integer | ColumnType::Numeric => SimValue(types::Value::from_i64(offset)),
             ColumnType::Float => SimValue(types::Value::from_f64(offset as f64)),
             ColumnType::Text => SimValue(types::Value::Text(format!("u{offset}").into())),
             ColumnType::Blob => SimValue(types::Value::Blob(format!("u{offset}").into_bytes())),
@@ -317,14 +363,16 @@ impl SimValue {
         }
     }
 
-    // TODO: support more operators. Copy the implementation for exec_glob
+    // TODO: support more operators
     pub fn like_compare(
         &self,
         other: &Self,
         operator: ast::LikeOperator,
     ) -> Result<bool, LimboError> {
         match operator {
-            ast::LikeOperator::Glob => todo!(),
+            ast::LikeOperator::Glob => {
+                types::Value::exec_glob(other.0.to_string().as_str(), self.0.to_string().as_str())
+            }
             ast::LikeOperator::Like => {
                 // TODO: support ESCAPE `expr` option in AST
                 // TODO: regex cache
@@ -355,7 +403,7 @@ impl SimValue {
 
     pub fn apply_affinity(self, column_type: ColumnType) -> SimValue {
         match column_type {
-            ColumnType::Integer => {
+            ColumnType::Integer | ColumnType::Numeric => {
                 if let types::Value::Numeric(Numeric::Float(fl)) = &self.0 {
                     let fl = f64::from(*fl);
                     if fl.is_finite() && fl.trunc() == fl {
@@ -382,6 +430,32 @@ impl SimValue {
             _ => self,
         }
     }
+
+    /// Applies the conversion SQLite performs on one operand of a comparison between
+    /// two columns, `own` being the affinity of this operand's column and `other` that
+    /// of the column it is compared against. See
+    /// <https://www.sqlite.org/datatype3.html#type_conversions_prior_to_comparison>:
+    /// - INTEGER/REAL against TEXT/BLOB: NUMERIC affinity is applied to the latter.
+    /// - TEXT against BLOB (no affinity): TEXT affinity is applied to the latter.
+    /// - Otherwise neither operand is converted.
+    pub fn apply_comparison_affinity(self, own: ColumnType, other: ColumnType) -> SimValue {
+        let is_numeric = |ty: ColumnType| {
+            matches!(
+                ty,
+                ColumnType::Integer | ColumnType::Float | ColumnType::Numeric
+            )
+        };
+        if is_numeric(other) && !is_numeric(own) {
+            if let Some(value) = apply_numeric_affinity(self.0.as_ref(), false) {
+                return SimValue(value.into());
+            }
+        } else if matches!((own, other), (ColumnType::Blob, ColumnType::Text))
+            && matches!(self.0, types::Value::Numeric(_))
+        {
+            return SimValue(types::Value::build_text(self.0.to_string()));
+        }
+        self
+    }
 }
 
 impl From<ast::Literal> for SimValue {
@@ -536,7 +610,11 @@ impl From<&turso_core::types::Value> for SimValue {
 
 #[cfg(test)]
 mod tests {
-    use crate::model::table::{escape_singlequotes, unescape_singlequotes};
+    use std::cmp::Ordering;
+
+    use turso_core::types;
+
+    use crate::model::table::{escape_singlequotes, unescape_singlequotes, SimValue};
 
     #[test]
     fn test_unescape_singlequotes() {
@@ -561,4 +639,22 @@ mod tests {
         assert_eq!(escape_singlequotes("test''test"), "'test''''test'");
         assert_eq!(escape_singlequotes("many'''quotes"), "'many''''''quotes'");
     }
+
+    #[test]
+    fn test_storage_class_cmp() {
+        let int = |i| SimValue(types::Value::from_i64(i));
+        let real = |f| SimValue(types::Value::from_f64(f));
+        let text = |t: &'static str| SimValue(types::Value::build_text(t));
+        let blob = |b: &[u8]| SimValue(types::Value::Blob(b.to_vec()));
+
+        assert_eq!(int(100).storage_class_cmp(&text("5")), Ordering::Less);
+        assert_eq!(real(2.5).storage_class_cmp(&text("1")), Ordering::Less);
+        assert_eq!(text("5").storage_class_cmp(&blob(b"5")), Ordering::Less);
+        assert_eq!(SimValue::NULL.storage_class_cmp(&int(-1)), Ordering::Less);
+        assert_eq!(int(3).storage_class_cmp(&real(2.5)), Ordering::Greater);
+        assert_eq!(int(2).storage_class_cmp(&real(2.0)), Ordering::Equal);
+        assert_eq!(text("100").storage_class_cmp(&text("5")), Ordering::Less);
+        assert_eq!(text("Z").storage_class_cmp(&text("a")), Ordering::Less);
+        assert_eq!(blob(b"").storage_class_cmp(&blob(b"\x00")), Ordering::Less);
+    }
 }
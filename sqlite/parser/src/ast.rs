@@ -1778,6 +1778,8 @@ pub enum PragmaName {
     CacheSize,
     /// set the cache spill behavior
     CacheSpill,
+    /// Make LIKE case-sensitive for ASCII characters
+    CaseSensitiveLike,
     /// encryption cipher algorithm name for encrypted databases
     #[strum(serialize = "cipher")]
     #[cfg_attr(feature = "serde", serde(rename = "cipher"))]
@@ -1822,6 +1824,8 @@ pub enum PragmaName {
     /// `module_list` pragma
     /// `module_list` lists modules used by virtual tables.
     ModuleList,
+    /// Read spilled hash table partitions through memory-mapped files instead of `pread`
+    MmapSpillReads,
     /// Return the total number of pages in the database file.
     PageCount,
     /// Return the page size of the database in bytes.
@@ -4,7 +4,7 @@
 use rand::{Rng, RngCore, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 use sql_generation::{
-    generation::Opts,
+    generation::{NameCounter, Opts},
     model::{
         query::{create::Create, create_index::CreateIndex},
         table::{Column, ColumnType, Index, Table},
@@ -574,6 +574,8 @@ pub struct SimulatorState {
     pub execution_id: u64,
     /// Counter for generating unique transaction IDs
     pub txn_id: u64,
+    /// Suffixes for generated table, column and index names
+    pub name_counter: NameCounter,
 }
 
 impl SimulatorState {
@@ -596,6 +598,7 @@ impl SimulatorState {
             seq_default_tables: MergableMap::new(),
             execution_id: 0,
             txn_id: 0,
+            name_counter: NameCounter::default(),
         }
     }
     pub fn tables_vec(&self) -> Vec<Table> {
@@ -1605,6 +1608,7 @@ pub fn create_initial_indexes(rng: &mut ChaCha8Rng, tables: &[Table]) -> Vec<Cre
                             index_name,
                             table_name: table.name.clone(),
                             columns: selected_columns,
+                            where_clause: None,
                         },
                     };
                     indexes.push(create_index);
@@ -3,7 +3,7 @@
 use rand::{Rng, seq::IndexedRandom};
 use rand_chacha::ChaCha8Rng;
 use sql_generation::{
-    generation::{Arbitrary, GenerationContext, Opts},
+    generation::{Arbitrary, GenerationContext, NameCounter, Opts},
     model::{
         query::{
             create_index::CreateIndex, delete::Delete, drop_index::DropIndex, insert::Insert,
@@ -37,6 +37,10 @@ impl GenerationContext for WorkloadContext<'_> {
     fn opts(&self) -> &Opts {
         self.opts
     }
+
+    fn name_counter(&self) -> &NameCounter {
+        &self.sim_state.name_counter
+    }
 }
 
 /// A workload generates operations to be executed on a fiber.
@@ -6,6 +6,7 @@ use crate::runner::env::ShadowTablesMut;
 pub mod plan;
 pub mod property;
 pub mod query;
+pub(crate) mod scenario;
 
 /// Shadow trait for types that can be "shadowed" in the simulator environment.
 /// Shadowing is a process of applying a transformation to the simulator environment
@@ -0,0 +1,1886 @@
+//! Support for properties that exercise SQL the [crate::model::Query] model cannot
+//! express (triggers, foreign keys, RETURNING, views, ...).
+//!
+//! Such properties run their statements directly on the connection from inside an
+//! assertion, against scratch objects that are not part of the shadow model, and check
+//! the results against expectations computed in Rust. Scratch objects are named after
+//! the property id so that concurrent connections never collide, and they are dropped
+//! again before the assertion returns.
+
+use std::{
+    num::NonZeroUsize,
+    panic::RefUnwindSafe,
+    sync::{Arc, Mutex},
+};
+
+use sql_generation::model::{
+    query::{
+        Create, Insert,
+        predicate::{Predicate, expr_to_value},
+        select::{ResultColumn, SelectTable},
+    },
+    table::{ColumnType, SimValue, Table},
+};
+use turso_core::{IO, LimboError, Numeric, OpenFlags, types::Value};
+
+use crate::{
+    common::print_diff,
+    model::{
+        Query,
+        interactions::{Assertion, InteractionBuilder, InteractionType},
+        property::CheckpointMode,
+    },
+    runner::{
+        env::{SimConnection, SimulatorEnv},
+        execution::is_recoverable_tx_error,
+    },
+};
+
+pub(super) type Rows = Vec<Vec<SimValue>>;
+
+/// Connection handle handed to a scratch scenario.
+pub(super) struct Scratch<'a> {
+    env: &'a mut SimulatorEnv,
+    connection_index: usize,
+}
+
+impl Scratch<'_> {
+    /// Runs a single statement and returns all rows it produced.
+    pub(super) fn query(&mut self, sql: &str) -> turso_core::Result<Rows> {
+        run_sql(self.env, self.connection_index, sql)
+    }
+
+    /// Runs a single statement, discarding any rows.
+    pub(super) fn execute(&mut self, sql: &str) -> turso_core::Result<()> {
+        self.query(sql).map(|_| ())
+    }
+
+    /// Runs a query that must produce exactly one value.
+    pub(super) fn query_value(&mut self, sql: &str) -> turso_core::Result<SimValue> {
+        let rows = self.query(sql)?;
+        match rows.as_slice() {
+            [row] if row.len() == 1 => Ok(row[0].clone()),
+            _ => Err(LimboError::InternalError(format!(
+                "expected a single value from '{sql}', got {rows:?}"
+            ))),
+        }
+    }
+
+    /// Prepares `sql` without running it.
+    pub(super) fn prepare(&mut self, sql: &str) -> turso_core::Result<ScratchStatement<'_>> {
+        match &mut self.env.connections[self.connection_index] {
+            SimConnection::LimboConnection(conn) => Ok(ScratchStatement::Limbo(conn.prepare(sql)?)),
+            SimConnection::SQLiteConnection(conn) => Ok(ScratchStatement::SQLite(
+                conn.prepare(sql).map_err(rusqlite_error)?,
+            )),
+            SimConnection::Disconnected => Err(LimboError::InternalError(
+                "connection is disconnected during scratch scenario".into(),
+            )),
+        }
+    }
+
+    /// Whether the underlying connection has an open transaction.
+    pub(super) fn in_transaction(&self) -> bool {
+        self.env.conn_db_in_transaction(self.connection_index)
+    }
+
+    /// Length in bytes of the main database's WAL file, 0 if there is none.
+    pub(super) fn wal_size(&self) -> turso_core::Result<u64> {
+        let wal_path = self.env.get_db_path().with_extension("db-wal");
+        match &self.env.connections[self.connection_index] {
+            // Turso may be running on in-memory files, so go through its IO
+            SimConnection::LimboConnection(_) => self
+                .env
+                .io
+                .open_file(wal_path.to_str().unwrap(), OpenFlags::None, false)?
+                .size(),
+            SimConnection::SQLiteConnection(_) => match std::fs::metadata(&wal_path) {
+                Ok(metadata) => Ok(metadata.len()),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(0),
+                Err(err) => Err(LimboError::InternalError(format!(
+                    "could not stat {}: {err}",
+                    wal_path.display()
+                ))),
+            },
+            SimConnection::Disconnected => Err(LimboError::InternalError(
+                "connection is disconnected during scratch scenario".into(),
+            )),
+        }
+    }
+}
+
+/// Runs `sql` on the given connection, returning all result rows.
+pub(super) fn run_sql(
+    env: &mut SimulatorEnv,
+    connection_index: usize,
+    sql: &str,
+) -> turso_core::Result<Rows> {
+    match &mut env.connections[connection_index] {
+        SimConnection::LimboConnection(conn) => {
+            let Some(mut rows) = conn.query(sql)? else {
+                return Ok(vec![]);
+            };
+            let mut out = Vec::new();
+            rows.run_with_row_callback(|row| {
+                out.push(row.get_values().map(SimValue::from).collect());
+                Ok(())
+            })?;
+            Ok(out)
+        }
+        SimConnection::SQLiteConnection(conn) => {
+            let mut stmt = conn.prepare(sql).map_err(rusqlite_error)?;
+            let column_count = stmt.column_count();
+            let rows = stmt.query([]).map_err(rusqlite_error)?;
+            rusqlite_rows(rows, column_count)
+        }
+        SimConnection::Disconnected => Err(LimboError::InternalError(
+            "connection is disconnected during scratch scenario".into(),
+        )),
+    }
+}
+
+fn rusqlite_rows(mut rows: rusqlite::Rows<'_>, column_count: usize) -> turso_core::Result<Rows> {
+    let mut out = Vec::new();
+    while let Some(row) = rows.next().map_err(rusqlite_error)? {
+        let mut values = Vec::with_capacity(column_count);
+        for i in 0..column_count {
+            let value = match row
+                .get::<_, rusqlite::types::Value>(i)
+                .map_err(rusqlite_error)?
+            {
+                rusqlite::types::Value::Null => Value::Null,
+                rusqlite::types::Value::Integer(i) => Value::from_i64(i),
+                rusqlite::types::Value::Real(f) => Value::from_f64(f),
+                rusqlite::types::Value::Text(s) => Value::build_text(s),
+                rusqlite::types::Value::Blob(b) => Value::Blob(b),
+            };
+            values.push(SimValue(value));
+        }
+        out.push(values);
+    }
+    Ok(out)
+}
+
+/// A statement prepared on the scenario's connection, for scenarios that drive the
+/// parameter binding API directly.
+pub(super) enum ScratchStatement<'a> {
+    Limbo(turso_core::Statement),
+    SQLite(rusqlite::Statement<'a>),
+}
+
+impl ScratchStatement<'_> {
+    /// Binds `value` to the 1-based parameter `index`.
+    pub(super) fn bind(&mut self, index: usize, value: &SimValue) -> turso_core::Result<()> {
+        let Some(index) = NonZeroUsize::new(index) else {
+            return Err(LimboError::InternalError(
+                "parameter indexes start at 1".into(),
+            ));
+        };
+        match self {
+            ScratchStatement::Limbo(stmt) => stmt.bind_at(index, value.0.clone()),
+            ScratchStatement::SQLite(stmt) => {
+                let value = match &value.0 {
+                    Value::Null => rusqlite::types::Value::Null,
+                    Value::Text(t) => rusqlite::types::Value::Text(t.as_str().to_string()),
+                    Value::Blob(b) => rusqlite::types::Value::Blob(b.to_vec()),
+                    v => match v.as_int() {
+                        Some(i) => rusqlite::types::Value::Integer(i),
+                        None => rusqlite::types::Value::Real(v.as_float()),
+                    },
+                };
+                stmt.raw_bind_parameter(index.get(), value)
+                    .map_err(rusqlite_error)
+            }
+        }
+    }
+
+    /// Resets every parameter to NULL, like `sqlite3_clear_bindings`.
+    pub(super) fn clear_bindings(&mut self) {
+        match self {
+            ScratchStatement::Limbo(stmt) => stmt.clear_bindings(),
+            ScratchStatement::SQLite(stmt) => stmt.clear_bindings(),
+        }
+    }
+
+    /// Runs the statement to completion with its current bindings and resets it,
+    /// keeping the bindings, so that it can run again.
+    pub(super) fn run(&mut self) -> turso_core::Result<Rows> {
+        match self {
+            ScratchStatement::Limbo(stmt) => {
+                let mut out = Vec::new();
+                let result = stmt.run_with_row_callback(|row| {
+                    out.push(row.get_values().map(SimValue::from).collect());
+                    Ok(())
+                });
+                stmt.reset()?;
+                result.map(|_| out)
+            }
+            ScratchStatement::SQLite(stmt) => {
+                let column_count = stmt.column_count();
+                // Dropping the rows resets the statement.
+                rusqlite_rows(stmt.raw_query(), column_count)
+            }
+        }
+    }
+}
+
+/// Maps rusqlite errors onto the [LimboError] variants scenarios care about, so that
+/// assertions behave the same against both backends.
+fn rusqlite_error(err: rusqlite::Error) -> LimboError {
+    match &err {
+        rusqlite::Error::SqliteFailure(e, msg) => match e.code {
+            rusqlite::ErrorCode::ConstraintViolation => {
+                LimboError::Constraint(msg.clone().unwrap_or_else(|| err.to_string()))
+            }
+            rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked => {
+                LimboError::Busy
+            }
+            _ => LimboError::InternalError(err.to_string()),
+        },
+        _ => LimboError::InternalError(err.to_string()),
+    }
+}
+
+/// Builds an assertion that runs `scenario` on the property's connection.
+///
+/// Scenarios manage their own transactions, so they are skipped when the connection
+/// is already inside one. `cleanup` statements run afterwards whatever the outcome,
+/// and transient errors (busy, write-write conflicts) end the scenario without failing
+/// the property.
+pub(super) fn scratch_assertion<F>(
+    name: String,
+    connection_index: usize,
+    cleanup: Vec<String>,
+    scenario: F,
+) -> InteractionBuilder
+where
+    F: Fn(&mut Scratch<'_>) -> turso_core::Result<Result<(), String>> + RefUnwindSafe + 'static,
+{
+    InteractionBuilder::with_interaction(InteractionType::Assertion(Assertion::new(
+        name,
+        move |_stack, env: &mut SimulatorEnv| {
+            if env.conn_db_in_transaction(connection_index) {
+                tracing::debug!("skipping scratch scenario: connection is in a transaction");
+                return Ok(Ok(()));
+            }
+            let mut scratch = Scratch {
+                env,
+                connection_index,
+            };
+            let result = scenario(&mut scratch);
+            if scratch.in_transaction() {
+                let _ = scratch.execute("ROLLBACK");
+            }
+            for sql in cleanup.iter() {
+                if let Err(err) = scratch.execute(sql) {
+                    tracing::debug!("scratch cleanup '{sql}' failed: {err}");
+                }
+            }
+            match result {
+                Err(err) if is_recoverable_tx_error(&err) => {
+                    tracing::debug!("scratch scenario ended early: {err}");
+                    Ok(Ok(()))
+                }
+                result => result,
+            }
+        },
+        vec![],
+    )))
+}
+
+/// Compares `actual` against `expected` and reports a diff on mismatch.
+pub(super) fn expect_rows(what: &str, expected: &Rows, actual: &Rows) -> Result<(), String> {
+    if expected == actual {
+        return Ok(());
+    }
+    print_diff(expected, actual, "expected", "database");
+    Err(format!("{what}: expected {expected:?}, got {actual:?}"))
+}
+
+pub(super) fn int(i: i64) -> SimValue {
+    SimValue(Value::from_i64(i))
+}
+
+pub(super) fn text(s: impl Into<String>) -> SimValue {
+    SimValue(Value::build_text(s.into()))
+}
+
+/// REPLACE INTO a parent table with an FK child and DELETE/INSERT triggers. See
+/// [crate::model::property::Property::ReplaceIntoSemantics].
+pub(super) fn replace_into_semantics(
+    id: usize,
+    connection_index: usize,
+    cascade: bool,
+    seed: Vec<(i64, String)>,
+    replace: (i64, String),
+) -> InteractionBuilder {
+    let p = format!("sim_replace_{id}");
+    let cleanup = vec![
+        format!("DROP TRIGGER IF EXISTS {p}_ad"),
+        format!("DROP TRIGGER IF EXISTS {p}_ai"),
+        format!("DROP TABLE IF EXISTS {p}_log"),
+        format!("DROP TABLE IF EXISTS {p}_child"),
+        format!("DROP TABLE IF EXISTS {p}_parent"),
+    ];
+    let action = if cascade { "CASCADE" } else { "SET NULL" };
+    scratch_assertion(
+        format!("REPLACE INTO {p}_parent should delete then insert (ON DELETE {action})"),
+        connection_index,
+        cleanup,
+        move |db| {
+            // foreign_keys is connection state; restore whatever the run configured.
+            let foreign_keys = db.query_value("PRAGMA foreign_keys")?;
+            db.execute("PRAGMA foreign_keys = ON")?;
+            let result = replace_into_scenario(db, &p, action, &seed, &replace);
+            db.execute(&format!("PRAGMA foreign_keys = {foreign_keys}"))?;
+            result
+        },
+    )
+}
+
+fn replace_into_scenario(
+    db: &mut Scratch<'_>,
+    p: &str,
+    action: &str,
+    seed: &[(i64, String)],
+    replace: &(i64, String),
+) -> turso_core::Result<Result<(), String>> {
+    db.execute(&format!(
+        "CREATE TABLE {p}_parent (id INTEGER PRIMARY KEY, val TEXT)"
+    ))?;
+    db.execute(&format!(
+        "CREATE TABLE {p}_child (id INTEGER PRIMARY KEY, parent_id INTEGER REFERENCES {p}_parent(id) ON DELETE {action})"
+    ))?;
+    db.execute(&format!(
+        "CREATE TABLE {p}_log (op TEXT, id INTEGER, val TEXT)"
+    ))?;
+    db.execute(&format!(
+        "CREATE TRIGGER {p}_ad AFTER DELETE ON {p}_parent BEGIN INSERT INTO {p}_log VALUES ('delete', OLD.id, OLD.val); END"
+    ))?;
+    db.execute(&format!(
+        "CREATE TRIGGER {p}_ai AFTER INSERT ON {p}_parent BEGIN INSERT INTO {p}_log VALUES ('insert', NEW.id, NEW.val); END"
+    ))?;
+    for (child_id, (parent_id, val)) in seed.iter().enumerate() {
+        db.execute(&format!(
+            "INSERT INTO {p}_parent VALUES ({parent_id}, '{val}')"
+        ))?;
+        db.execute(&format!(
+            "INSERT INTO {p}_child VALUES ({child_id}, {parent_id})"
+        ))?;
+    }
+    db.execute(&format!("DELETE FROM {p}_log"))?;
+
+    let (key, val) = replace;
+    db.execute(&format!("REPLACE INTO {p}_parent VALUES ({key}, '{val}')"))?;
+
+    // With recursive_triggers off (the default) the implicit delete of the conflicting
+    // row does not fire DELETE triggers, so only the insert is logged.
+    let log = db.query(&format!("SELECT op, id, val FROM {p}_log ORDER BY rowid"))?;
+    if let Err(e) = expect_rows(
+        "trigger log",
+        &vec![vec![text("insert"), int(*key), text(val.as_str())]],
+        &log,
+    ) {
+        return Ok(Err(e));
+    }
+
+    let mut expected_parents: Rows = seed
+        .iter()
+        .map(|(id, v)| {
+            let v = if id == key { val } else { v };
+            vec![int(*id), text(v.as_str())]
+        })
+        .collect();
+    expected_parents.sort();
+    let parents = db.query(&format!("SELECT id, val FROM {p}_parent ORDER BY id"))?;
+    if let Err(e) = expect_rows("parent rows", &expected_parents, &parents) {
+        return Ok(Err(e));
+    }
+
+    let expected_children: Rows = seed
+        .iter()
+        .enumerate()
+        .filter_map(|(child_id, (parent_id, _))| {
+            let child_id = int(child_id as i64);
+            match (parent_id == key, action) {
+                (false, _) => Some(vec![child_id, int(*parent_id)]),
+                (true, "SET NULL") => Some(vec![child_id, SimValue::NULL]),
+                (true, _) => None,
+            }
+        })
+        .collect();
+    let children = db.query(&format!("SELECT id, parent_id FROM {p}_child ORDER BY id"))?;
+    Ok(expect_rows("child rows", &expected_children, &children))
+}
+
+/// Self-firing AFTER INSERT trigger. See
+/// [crate::model::property::Property::RecursiveTriggerTerminates].
+pub(super) fn recursive_trigger_terminates(
+    id: usize,
+    connection_index: usize,
+    bound: Option<i64>,
+    start: i64,
+) -> InteractionBuilder {
+    let p = format!("sim_rtrig_{id}");
+    let cleanup = vec![
+        format!("DROP TRIGGER IF EXISTS {p}_ai"),
+        format!("DROP TABLE IF EXISTS {p}"),
+        "PRAGMA recursive_triggers = OFF".to_string(),
+    ];
+    scratch_assertion(
+        format!("self-firing trigger on {p} should terminate"),
+        connection_index,
+        cleanup,
+        move |db| {
+            db.execute(&format!("CREATE TABLE {p} (n INTEGER)"))?;
+            let when = bound
+                .map(|bound| format!("WHEN NEW.n < {bound} "))
+                .unwrap_or_default();
+            db.execute(&format!(
+                "CREATE TRIGGER {p}_ai AFTER INSERT ON {p} {when}BEGIN INSERT INTO {p} VALUES (NEW.n + 1); END"
+            ))?;
+            // Engines without the pragma keep recursion off, where a trigger never
+            // fires itself again.
+            let recursive = db.execute("PRAGMA recursive_triggers = ON").is_ok()
+                && db
+                    .query_value("PRAGMA recursive_triggers")
+                    .is_ok_and(|v| v == int(1));
+
+            let result = db.execute(&format!("INSERT INTO {p} VALUES ({start})"));
+            let fires = |n: i64| bound.is_none_or(|bound| n < bound);
+            let expected: Rows = match (recursive, bound) {
+                // Recursion stops at SQLITE_MAX_TRIGGER_DEPTH, failing the statement.
+                (true, None) => {
+                    return Ok(match result {
+                        Err(err)
+                            if err
+                                .to_string()
+                                .contains("too many levels of trigger recursion") =>
+                        {
+                            let rows = db.query(&format!("SELECT n FROM {p}"))?;
+                            expect_rows("rows after the failed insert", &vec![], &rows)
+                        }
+                        Err(err) => Err(format!("unbounded recursion failed with: {err}")),
+                        Ok(()) => Err("unbounded recursion succeeded".to_string()),
+                    });
+                }
+                (true, Some(bound)) => (start..=bound.max(start)).map(|n| vec![int(n)]).collect(),
+                (false, _) if fires(start) => vec![vec![int(start)], vec![int(start + 1)]],
+                (false, _) => vec![vec![int(start)]],
+            };
+            result?;
+            let rows = db.query(&format!("SELECT n FROM {p} ORDER BY n"))?;
+            Ok(expect_rows("rows written by the trigger", &expected, &rows))
+        },
+    )
+}
+
+/// BEFORE or AFTER INSERT trigger writing audit rows. See
+/// [crate::model::property::Property::TriggerFires].
+pub(super) fn trigger_fires(
+    id: usize,
+    connection_index: usize,
+    before: bool,
+    rows: Vec<(i64, i64)>,
+    offset: i64,
+) -> InteractionBuilder {
+    let p = format!("sim_trig_{id}");
+    let cleanup = vec![
+        format!("DROP TRIGGER IF EXISTS {p}_ins"),
+        format!("DROP TABLE IF EXISTS {p}_audit"),
+        format!("DROP TABLE IF EXISTS {p}"),
+    ];
+    let timing = if before { "BEFORE" } else { "AFTER" };
+    scratch_assertion(
+        format!("{timing} INSERT trigger on {p} should write one audit row per row"),
+        connection_index,
+        cleanup,
+        move |db| {
+            db.execute(&format!("CREATE TABLE {p} (a INTEGER, b INTEGER)"))?;
+            db.execute(&format!(
+                "CREATE TABLE {p}_audit (a INTEGER, b INTEGER, seen INTEGER)"
+            ))?;
+            db.execute(&format!(
+                "CREATE TRIGGER {p}_ins {timing} INSERT ON {p} BEGIN INSERT INTO {p}_audit VALUES (NEW.a, NEW.b + {offset}, (SELECT count(*) FROM {p})); END"
+            ))?;
+            let values = rows
+                .iter()
+                .map(|(a, b)| format!("({a}, {b})"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            db.execute(&format!("INSERT INTO {p} VALUES {values}"))?;
+            // Rows are written one at a time, firing the trigger around each
+            let expected: Rows = rows
+                .iter()
+                .enumerate()
+                .map(|(i, (a, b))| {
+                    let seen = if before { i } else { i + 1 };
+                    vec![int(*a), int(b + offset), int(seen as i64)]
+                })
+                .collect();
+            let audit = db.query(&format!("SELECT * FROM {p}_audit ORDER BY rowid"))?;
+            Ok(expect_rows(
+                &format!("audit rows written by the {timing} INSERT trigger"),
+                &expected,
+                &audit,
+            ))
+        },
+    )
+}
+
+/// Orphan child rows inserted with `foreign_keys = OFF`, reported by
+/// `PRAGMA foreign_key_check`. See
+/// [crate::model::property::Property::ForeignKeyCheckReportsOrphans].
+pub(super) fn foreign_key_check_reports_orphans(
+    id: usize,
+    connection_index: usize,
+    parents: Vec<i64>,
+    children: Vec<Option<i64>>,
+) -> InteractionBuilder {
+    let p = format!("sim_fkcheck_{id}");
+    let cleanup = vec![
+        format!("DROP TABLE IF EXISTS {p}_child"),
+        format!("DROP TABLE IF EXISTS {p}_parent"),
+    ];
+    scratch_assertion(
+        format!("PRAGMA foreign_key_check should report the orphans of {p}_child"),
+        connection_index,
+        cleanup,
+        move |db| {
+            let pragmas = db.query("PRAGMA pragma_list")?;
+            if !pragmas.contains(&vec![text("foreign_key_check")]) {
+                tracing::debug!("skipping foreign_key_check scenario: pragma not supported");
+                return Ok(Ok(()));
+            }
+            // foreign_keys is connection state; restore whatever the run configured.
+            let foreign_keys = db.query_value("PRAGMA foreign_keys")?;
+            db.execute("PRAGMA foreign_keys = OFF")?;
+            let result = foreign_key_check_scenario(db, &p, &parents, &children);
+            db.execute(&format!("PRAGMA foreign_keys = {foreign_keys}"))?;
+            result
+        },
+    )
+}
+
+fn foreign_key_check_scenario(
+    db: &mut Scratch<'_>,
+    p: &str,
+    parents: &[i64],
+    children: &[Option<i64>],
+) -> turso_core::Result<Result<(), String>> {
+    db.execute(&format!("CREATE TABLE {p}_parent (id INTEGER PRIMARY KEY)"))?;
+    db.execute(&format!(
+        "CREATE TABLE {p}_child (id INTEGER PRIMARY KEY, parent_id INTEGER REFERENCES {p}_parent(id))"
+    ))?;
+    for id in parents {
+        db.execute(&format!("INSERT INTO {p}_parent VALUES ({id})"))?;
+    }
+    for (rowid, parent_id) in (1..).zip(children) {
+        let parent_id = match parent_id {
+            Some(id) => id.to_string(),
+            None => "NULL".to_string(),
+        };
+        db.execute(&format!(
+            "INSERT INTO {p}_child VALUES ({rowid}, {parent_id})"
+        ))?;
+    }
+    db.execute("PRAGMA foreign_keys = ON")?;
+
+    // NULL references are never violations
+    let expected: Rows = (1..)
+        .zip(children)
+        .filter(|(_, parent_id)| parent_id.is_some_and(|id| !parents.contains(&id)))
+        .map(|(rowid, _)| {
+            vec![
+                text(format!("{p}_child")),
+                int(rowid),
+                text(format!("{p}_parent")),
+                int(0),
+            ]
+        })
+        .collect();
+    let mut violations = db.query(&format!("PRAGMA foreign_key_check({p}_child)"))?;
+    violations.sort();
+    Ok(expect_rows(
+        "foreign_key_check rows",
+        &expected,
+        &violations,
+    ))
+}
+
+/// Renames the parent of a scratch foreign key. See
+/// [crate::model::property::Property::RenameTableFollowThrough].
+pub(super) fn rename_table_foreign_keys(id: usize, connection_index: usize) -> InteractionBuilder {
+    let p = format!("sim_rename_{id}");
+    let cleanup = vec![
+        format!("DROP TABLE IF EXISTS {p}_child"),
+        format!("DROP TABLE IF EXISTS {p}_parent"),
+        format!("DROP TABLE IF EXISTS {p}_renamed"),
+    ];
+    scratch_assertion(
+        format!("foreign keys of {p}_child should follow {p}_parent through a rename"),
+        connection_index,
+        cleanup,
+        move |db| {
+            // foreign_keys is connection state; restore whatever the run configured.
+            let foreign_keys = db.query_value("PRAGMA foreign_keys")?;
+            let result = rename_table_foreign_keys_scenario(db, &p);
+            db.execute(&format!("PRAGMA foreign_keys = {foreign_keys}"))?;
+            result
+        },
+    )
+}
+
+fn rename_table_foreign_keys_scenario(
+    db: &mut Scratch<'_>,
+    p: &str,
+) -> turso_core::Result<Result<(), String>> {
+    db.execute(&format!("CREATE TABLE {p}_parent (id INTEGER PRIMARY KEY)"))?;
+    db.execute(&format!(
+        "CREATE TABLE {p}_child (id INTEGER PRIMARY KEY, parent_id INTEGER REFERENCES {p}_parent(id))"
+    ))?;
+    db.execute(&format!("INSERT INTO {p}_parent VALUES (1)"))?;
+    db.execute(&format!("INSERT INTO {p}_child VALUES (1, 1)"))?;
+    db.execute(&format!("ALTER TABLE {p}_parent RENAME TO {p}_renamed"))?;
+
+    let sql = db.query_value(&format!(
+        "SELECT sql FROM sqlite_master WHERE name = '{p}_child'"
+    ))?;
+    if !sql
+        .0
+        .to_text()
+        .is_some_and(|sql| sql.contains(&format!("{p}_renamed")))
+    {
+        return Ok(Err(format!(
+            "{p}_child should reference {p}_renamed after the rename, its schema is {sql}"
+        )));
+    }
+
+    db.execute("PRAGMA foreign_keys = ON")?;
+    db.execute(&format!("INSERT INTO {p}_child VALUES (2, 1)"))?;
+    match db.execute(&format!("INSERT INTO {p}_child VALUES (3, 2)")) {
+        Ok(()) => Ok(Err(format!(
+            "inserting an orphan into {p}_child succeeded after renaming its parent"
+        ))),
+        Err(err) if err.to_string().contains("FOREIGN KEY constraint failed") => {
+            let rows = db.query(&format!("SELECT id, parent_id FROM {p}_child ORDER BY id"))?;
+            Ok(expect_rows(
+                &format!("{p}_child rows"),
+                &vec![vec![int(1), int(1)], vec![int(2), int(1)]],
+                &rows,
+            ))
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// An insert followed by a conflicting upsert into a scratch table, with DO UPDATE
+/// or DO NOTHING. See [crate::model::property::Property::UpsertDoUpdate].
+pub(super) fn upsert_do_update(
+    connection_index: usize,
+    create: Create,
+    insert: Insert,
+    upsert: Insert,
+    do_nothing: bool,
+) -> InteractionBuilder {
+    let table = create.table.name.clone();
+    let cleanup = vec![format!("DROP TABLE IF EXISTS {table}")];
+    let Insert::Values {
+        values,
+        on_conflict: Some(on_conflict),
+        ..
+    } = &upsert
+    else {
+        unreachable!("upsert is a single-row insert with ON CONFLICT");
+    };
+    let column_index = |name: &str| {
+        create
+            .table
+            .columns
+            .iter()
+            .position(|c| c.name == name)
+            .expect("upsert columns are part of the table")
+    };
+    let mut expected = insert.rows()[0].clone();
+    let sql = if do_nothing {
+        let target = &on_conflict.target_column;
+        let upsert = Insert::Values {
+            table: table.clone(),
+            values: values.clone(),
+            on_conflict: None,
+        };
+        format!("{upsert} ON CONFLICT({target}) DO NOTHING")
+    } else {
+        for assignment in &on_conflict.assignments {
+            expected[column_index(&assignment.column)] =
+                values[0][column_index(&assignment.excluded_column)].clone();
+        }
+        upsert.to_string()
+    };
+    scratch_assertion(
+        format!("'{sql}' should resolve the conflict without adding a row"),
+        connection_index,
+        cleanup,
+        move |db| {
+            db.execute(&create.to_string())?;
+            db.execute(&insert.to_string())?;
+            db.execute(&sql)?;
+            let rows = db.query(&format!("SELECT * FROM {table}"))?;
+            if rows.len() > 1 {
+                return Ok(Err(format!(
+                    "'{sql}' left {} rows in {table}, the conflicting row was duplicated: {rows:?}",
+                    rows.len()
+                )));
+            }
+            Ok(expect_rows(
+                &format!("rows in {table}"),
+                &vec![expected.clone()],
+                &rows,
+            ))
+        },
+    )
+}
+
+/// A correlated `IN (SELECT ...)` over a scratch parent/child table pair. See
+/// [crate::model::property::Property::CorrelatedSubquery].
+pub(super) fn correlated_subquery(
+    id: usize,
+    connection_index: usize,
+    parents: Vec<(Option<i64>, Option<i64>)>,
+    children: Vec<(Option<i64>, Option<i64>)>,
+) -> InteractionBuilder {
+    let p = format!("sim_correlated_{id}");
+    let cleanup = vec![
+        format!("DROP TABLE IF EXISTS {p}_child"),
+        format!("DROP TABLE IF EXISTS {p}_parent"),
+    ];
+    let sql = format!(
+        "SELECT * FROM {p}_parent WHERE col IN (SELECT fk FROM {p}_child WHERE {p}_child.x = {p}_parent.y)"
+    );
+    scratch_assertion(
+        format!("'{sql}' should match the model"),
+        connection_index,
+        cleanup,
+        move |db| {
+            let literal = |v: &Option<i64>| v.map_or("NULL".to_string(), |v| v.to_string());
+            let value = |v: &Option<i64>| v.map_or(SimValue::NULL, int);
+            db.execute(&format!(
+                "CREATE TABLE {p}_parent (id INTEGER PRIMARY KEY, col INTEGER, y INTEGER)"
+            ))?;
+            db.execute(&format!("CREATE TABLE {p}_child (fk INTEGER, x INTEGER)"))?;
+            for (rowid, (col, y)) in (1..).zip(&parents) {
+                db.execute(&format!(
+                    "INSERT INTO {p}_parent VALUES ({rowid}, {}, {})",
+                    literal(col),
+                    literal(y)
+                ))?;
+            }
+            for (fk, x) in &children {
+                db.execute(&format!(
+                    "INSERT INTO {p}_child VALUES ({}, {})",
+                    literal(fk),
+                    literal(x)
+                ))?;
+            }
+            // `col IN (...)` is false for an empty subquery, even when col is NULL.
+            // Otherwise it is NULL if col is NULL, or if no fk matches and one is NULL.
+            let expected: Rows = (1..)
+                .zip(&parents)
+                .filter(|(_, (col, y))| {
+                    let fks: Vec<Option<i64>> = children
+                        .iter()
+                        .filter(|(_, x)| x.is_some() && x == y)
+                        .map(|(fk, _)| *fk)
+                        .collect();
+                    col.is_some() && fks.contains(col)
+                })
+                .map(|(rowid, (col, y))| vec![int(rowid), value(col), value(y)])
+                .collect();
+            let mut rows = db.query(&sql)?;
+            rows.sort();
+            Ok(expect_rows(&format!("rows of '{sql}'"), &expected, &rows))
+        },
+    )
+}
+
+/// A second insert of a UNIQUE value into a scratch table, plain or OR IGNORE. See
+/// [crate::model::property::Property::UniqueViolation].
+pub(super) fn unique_violation(
+    connection_index: usize,
+    create: Create,
+    insert: Insert,
+    duplicate: Insert,
+    or_ignore: bool,
+) -> InteractionBuilder {
+    let table = create.table.name.clone();
+    let cleanup = vec![format!("DROP TABLE IF EXISTS {table}")];
+    let duplicate = if or_ignore {
+        duplicate
+            .to_string()
+            .replacen("INSERT INTO", "INSERT OR IGNORE INTO", 1)
+    } else {
+        duplicate.to_string()
+    };
+    scratch_assertion(
+        format!("'{duplicate}' should not add a second row with the same UNIQUE value"),
+        connection_index,
+        cleanup,
+        move |db| {
+            db.execute(&create.to_string())?;
+            db.execute(&insert.to_string())?;
+            match db.execute(&duplicate) {
+                Ok(()) if or_ignore => {}
+                Ok(()) => {
+                    return Ok(Err(format!(
+                        "'{duplicate}' should fail with a UNIQUE constraint error"
+                    )));
+                }
+                Err(LimboError::Constraint(msg))
+                    if !or_ignore && msg.contains("UNIQUE constraint failed") => {}
+                Err(LimboError::Constraint(msg)) => {
+                    return Ok(Err(format!("'{duplicate}' failed with '{msg}'")));
+                }
+                Err(err) => return Err(err),
+            }
+            let count = db.query(&format!("SELECT count(*) FROM {table}"))?;
+            Ok(expect_rows(
+                &format!("rows in {table}"),
+                &vec![vec![int(1)]],
+                &count,
+            ))
+        },
+    )
+}
+
+/// The same values inserted into one column of each affinity. See
+/// [crate::model::property::Property::TypeAffinity].
+pub(super) fn type_affinity(
+    connection_index: usize,
+    create: Create,
+    values: Vec<SimValue>,
+) -> InteractionBuilder {
+    let table = create.table.name.clone();
+    let cleanup = vec![format!("DROP TABLE IF EXISTS {table}")];
+    let columns = create.table.columns.clone();
+    let select = format!(
+        "SELECT {} FROM {table} ORDER BY rowid",
+        columns
+            .iter()
+            .map(|c| format!("{0}, typeof({0})", c.name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    scratch_assertion(
+        format!("values stored in {table} should follow column affinity"),
+        connection_index,
+        cleanup,
+        move |db| {
+            db.execute(&create.to_string())?;
+            for value in &values {
+                // SimValue prints 3.0 as 3, which would insert an integer
+                let literal = match &value.0 {
+                    Value::Numeric(Numeric::Float(f)) => format!("{:?}", f64::from(*f)),
+                    _ => value.to_string(),
+                };
+                let row = vec![literal; columns.len()].join(", ");
+                db.execute(&format!("INSERT INTO {table} VALUES ({row})"))?;
+            }
+            let rows = db.query(&select)?;
+            if rows.len() != values.len() {
+                return Ok(Err(format!(
+                    "'{select}' returned {} rows instead of {}",
+                    rows.len(),
+                    values.len()
+                )));
+            }
+            for (value, row) in values.iter().zip(&rows) {
+                for (column, stored) in columns.iter().zip(row.chunks(2)) {
+                    let expected = affinity_conversion(value, column.column_type);
+                    let [stored, stored_type] = stored else {
+                        return Ok(Err(format!("'{select}' returned row {row:?}")));
+                    };
+                    // Numeric equality does not tell 3 from 3.0, typeof does
+                    if *stored != expected || *stored_type != text(type_name(&expected)) {
+                        return Ok(Err(format!(
+                            "{value} inserted into {} column {} was stored as {stored} ({stored_type}), expected {expected} ({})",
+                            column.column_type,
+                            column.name,
+                            type_name(&expected)
+                        )));
+                    }
+                }
+            }
+            Ok(Ok(()))
+        },
+    )
+}
+
+/// What SQLite stores for `value` inserted into a column of `column_type`, following
+/// <https://www.sqlite.org/datatype3.html#type_affinity>.
+///
+/// REAL values converted to TEXT are expected in their shortest form with a `.0`
+/// for integral values, which is what SQLite prints for the few short values the
+/// property inserts, not in general.
+fn affinity_conversion(value: &SimValue, column_type: ColumnType) -> SimValue {
+    let integral = |f: f64| {
+        // Only values that survive the round trip through i64 become integers
+        if f.trunc() == f && (-9.223372036854776e18..9.223372036854776e18).contains(&f) {
+            int(f as i64)
+        } else {
+            SimValue(Value::from_f64(f))
+        }
+    };
+    match (column_type, &value.0) {
+        (ColumnType::Blob, _) | (_, Value::Null | Value::Blob(_)) => value.clone(),
+        (ColumnType::Text, Value::Numeric(Numeric::Integer(i))) => text(i.to_string()),
+        (ColumnType::Text, Value::Numeric(Numeric::Float(f))) => {
+            let f = f64::from(*f);
+            if f.trunc() == f {
+                text(format!("{f:.1}"))
+            } else {
+                text(f.to_string())
+            }
+        }
+        (ColumnType::Text, Value::Text(_)) => value.clone(),
+        (ColumnType::Integer | ColumnType::Numeric, Value::Numeric(Numeric::Integer(_))) => {
+            value.clone()
+        }
+        (ColumnType::Integer | ColumnType::Numeric, Value::Numeric(Numeric::Float(f))) => {
+            integral(f64::from(*f))
+        }
+        (ColumnType::Float, Value::Numeric(number)) => SimValue(Value::from_f64(match number {
+            Numeric::Integer(i) => *i as f64,
+            Numeric::Float(f) => f64::from(*f),
+        })),
+        (ColumnType::Integer | ColumnType::Numeric | ColumnType::Float, Value::Text(t)) => {
+            match parse_numeric_text(t.as_str()) {
+                None => value.clone(),
+                Some(Ok(i)) if column_type == ColumnType::Float => {
+                    SimValue(Value::from_f64(i as f64))
+                }
+                Some(Ok(i)) => int(i),
+                Some(Err(f)) if column_type == ColumnType::Float => SimValue(Value::from_f64(f)),
+                Some(Err(f)) => integral(f),
+            }
+        }
+    }
+}
+
+/// Parses text that is a well-formed integer (`Ok`) or real (`Err`) literal,
+/// ignoring surrounding spaces. Hexadecimal literals are not converted.
+fn parse_numeric_text(text: &str) -> Option<Result<i64, f64>> {
+    let text = text.trim_matches(|c: char| c.is_ascii_whitespace());
+    let digits = text.strip_prefix(['+', '-']).unwrap_or(text);
+    if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+        // Integers out of range become reals
+        return Some(
+            text.parse::<i64>()
+                .map_err(|_| text.parse::<f64>().unwrap()),
+        );
+    }
+    let (mantissa, exponent) = match digits.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (digits, None),
+    };
+    let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let well_formed = (!whole.is_empty() || !fraction.is_empty())
+        && whole.bytes().all(|b| b.is_ascii_digit())
+        && fraction.bytes().all(|b| b.is_ascii_digit())
+        && exponent.is_none_or(|e| {
+            let e = e.strip_prefix(['+', '-']).unwrap_or(e);
+            !e.is_empty() && e.bytes().all(|b| b.is_ascii_digit())
+        });
+    well_formed.then(|| Err(text.parse::<f64>().unwrap()))
+}
+
+/// The result of `typeof(value)`.
+fn type_name(value: &SimValue) -> &'static str {
+    match &value.0 {
+        Value::Null => "null",
+        Value::Numeric(Numeric::Integer(_)) => "integer",
+        Value::Numeric(Numeric::Float(_)) => "real",
+        Value::Text(_) => "text",
+        Value::Blob(_) => "blob",
+    }
+}
+
+/// Chunked reads and a spliced overwrite of a BLOB in a scratch table. See
+/// [crate::model::property::Property::BlobIncrementalIo].
+pub(super) fn blob_incremental_io(
+    id: usize,
+    connection_index: usize,
+    data: Vec<u8>,
+    chunk: usize,
+    patch_offset: usize,
+    patch: Vec<u8>,
+) -> InteractionBuilder {
+    let table = format!("sim_blob_io_{id}");
+    let cleanup = vec![format!("DROP TABLE IF EXISTS {table}")];
+    let blob = |bytes: &[u8]| SimValue(Value::Blob(bytes.to_vec()));
+    scratch_assertion(
+        format!(
+            "{}-byte blob in {table} should read back in {chunk}-byte chunks and take a patch",
+            data.len()
+        ),
+        connection_index,
+        cleanup,
+        move |db| {
+            let len = data.len();
+            db.execute(&format!(
+                "CREATE TABLE {table} (id INTEGER PRIMARY KEY, data BLOB)"
+            ))?;
+            db.execute(&format!("INSERT INTO {table} VALUES (1, {})", blob(&data)))?;
+            // Reading at or past the end yields an empty blob, it never fails
+            for offset in (0..len).step_by(chunk).chain([len]) {
+                let sql = format!("SELECT substr(data, {}, {chunk}) FROM {table}", offset + 1);
+                let expected = blob(&data[offset..(offset + chunk).min(len)]);
+                let actual = db.query_value(&sql)?;
+                if actual != expected {
+                    return Ok(Err(format!(
+                        "'{sql}' returned {actual} instead of {expected}"
+                    )));
+                }
+            }
+            if patch.is_empty() {
+                return Ok(Ok(()));
+            }
+            db.execute(&format!(
+                "UPDATE {table} SET data = CAST(substr(data, 1, {patch_offset}) || {} || substr(data, {}) AS BLOB)",
+                blob(&patch),
+                patch_offset + patch.len() + 1
+            ))?;
+            let mut expected = data.clone();
+            expected[patch_offset..patch_offset + patch.len()].copy_from_slice(&patch);
+            let rows = db.query(&format!("SELECT data FROM {table}"))?;
+            Ok(expect_rows(
+                &format!(
+                    "data in {table} after patching {} bytes at {patch_offset}",
+                    patch.len()
+                ),
+                &vec![vec![blob(&expected)]],
+                &rows,
+            ))
+        },
+    )
+}
+
+/// Mixed explicit and generated rowid inserts into an AUTOINCREMENT table, followed
+/// by deleting the highest row. See
+/// [crate::model::property::Property::AutoincrementMonotonic].
+pub(super) fn autoincrement_monotonic(
+    connection_index: usize,
+    create: Create,
+    rowids: Vec<Option<i64>>,
+) -> InteractionBuilder {
+    let table = create.table.name.clone();
+    let cleanup = vec![format!("DROP TABLE IF EXISTS {table}")];
+    let pk = create
+        .table
+        .columns
+        .iter()
+        .find(|c| c.is_autoincrement())
+        .expect("table has an AUTOINCREMENT column")
+        .name
+        .clone();
+    let payload = create
+        .table
+        .columns
+        .iter()
+        .find(|c| !c.is_autoincrement())
+        .expect("table has a payload column")
+        .name
+        .clone();
+    scratch_assertion(
+        format!("AUTOINCREMENT rowids of {table} should never be reused"),
+        connection_index,
+        cleanup,
+        move |db| {
+            db.execute(&create.to_string())?;
+            let generated_rowid = |db: &mut Scratch<'_>, tag: &str| -> turso_core::Result<i64> {
+                db.execute(&format!("INSERT INTO {table} ({payload}) VALUES ('{tag}')"))?;
+                let rowid = db.query_value(&format!(
+                    "SELECT {pk} FROM {table} WHERE {payload} = '{tag}'"
+                ))?;
+                rowid.0.as_int().ok_or_else(|| {
+                    LimboError::InternalError(format!("rowid of '{tag}' is {rowid}"))
+                })
+            };
+            let mut max_used = 0;
+            for (i, rowid) in rowids.iter().enumerate() {
+                let tag = format!("r{i}");
+                let rowid = match rowid {
+                    Some(rowid) => {
+                        db.execute(&format!(
+                            "INSERT INTO {table} ({pk}, {payload}) VALUES ({rowid}, '{tag}')"
+                        ))?;
+                        *rowid
+                    }
+                    None => {
+                        let rowid = generated_rowid(db, &tag)?;
+                        if rowid <= max_used {
+                            return Ok(Err(format!(
+                                "generated rowid {rowid} for '{tag}' is not above the highest used rowid {max_used}"
+                            )));
+                        }
+                        rowid
+                    }
+                };
+                max_used = max_used.max(rowid);
+            }
+            db.execute(&format!("DELETE FROM {table} WHERE {pk} = {max_used}"))?;
+            let rowid = generated_rowid(db, "after_delete")?;
+            if rowid <= max_used {
+                return Ok(Err(format!(
+                    "rowid {rowid} generated after deleting the highest row reuses a value at or below {max_used}"
+                )));
+            }
+            Ok(Ok(()))
+        },
+    )
+}
+
+/// Inserts a row that omits a column with a DEFAULT into a scratch table. See
+/// [crate::model::property::Property::DefaultValue].
+pub(super) fn default_value(
+    connection_index: usize,
+    create: Create,
+    column: String,
+    value: SimValue,
+) -> InteractionBuilder {
+    let table = create.table.name.clone();
+    let cleanup = vec![format!("DROP TABLE IF EXISTS {table}")];
+    let default_column = create
+        .table
+        .columns
+        .iter()
+        .find(|c| c.name == column)
+        .expect("default column is part of the table");
+    let default = default_column
+        .default_expr()
+        .and_then(|expr| expr_to_value(expr, &[], &create.table))
+        .expect("default is a literal");
+    let other = create
+        .table
+        .columns
+        .iter()
+        .find(|c| c.name != column)
+        .expect("table has a column without a default")
+        .name
+        .clone();
+    scratch_assertion(
+        format!("{table}.{column} should take its DEFAULT when omitted from the insert"),
+        connection_index,
+        cleanup,
+        move |db| {
+            db.execute(&create.to_string())?;
+            db.execute(&format!("INSERT INTO {table} ({other}) VALUES ({value})"))?;
+            let rows = db.query(&format!("SELECT {other}, {column} FROM {table}"))?;
+            Ok(expect_rows(
+                &format!("rows in {table}"),
+                &vec![vec![value.clone(), default.clone()]],
+                &rows,
+            ))
+        },
+    )
+}
+
+/// Inserts violating, satisfying and NULL values into a scratch table with a CHECK
+/// constraint. See [crate::model::property::Property::CheckConstraint].
+pub(super) fn check_constraint(
+    connection_index: usize,
+    table: String,
+    column: String,
+    column_type: ColumnType,
+    check: Predicate,
+    invalid: SimValue,
+    valid: SimValue,
+) -> InteractionBuilder {
+    let cleanup = vec![format!("DROP TABLE IF EXISTS {table}")];
+    scratch_assertion(
+        format!("CHECK ({check}) on {table}.{column} should reject only false rows"),
+        connection_index,
+        cleanup,
+        move |db| {
+            db.execute(&format!(
+                "CREATE TABLE {table} ({column} {column_type} CHECK ({check}))"
+            ))?;
+            let insert = |value: &SimValue| format!("INSERT INTO {table} VALUES ({value})");
+            match db.execute(&insert(&invalid)) {
+                Err(LimboError::Constraint(msg)) if msg.contains("CHECK constraint failed") => {}
+                Err(LimboError::Constraint(msg)) => {
+                    return Ok(Err(format!(
+                        "row ({invalid}) failed with '{msg}' instead of the CHECK constraint"
+                    )));
+                }
+                Ok(()) => {
+                    return Ok(Err(format!(
+                        "row ({invalid}) violates CHECK ({check}) but was inserted"
+                    )));
+                }
+                Err(err) => return Err(err),
+            }
+            // A CHECK that evaluates to NULL is not a violation
+            for value in [&valid, &SimValue::NULL] {
+                match db.execute(&insert(value)) {
+                    Err(LimboError::Constraint(msg)) => {
+                        return Ok(Err(format!(
+                            "row ({value}) satisfies CHECK ({check}) but failed with '{msg}'"
+                        )));
+                    }
+                    result => result?,
+                }
+            }
+            let mut rows = db.query(&format!("SELECT * FROM {table}"))?;
+            rows.sort();
+            Ok(expect_rows(
+                &format!("rows in {table}"),
+                &vec![vec![SimValue::NULL], vec![valid.clone()]],
+                &rows,
+            ))
+        },
+    )
+}
+
+/// Inserts rows with and without NULLs into a scratch table with NOT NULL columns.
+/// See [crate::model::property::Property::NotNullConstraint].
+pub(super) fn not_null_constraint(
+    connection_index: usize,
+    create: Create,
+    row: Vec<SimValue>,
+    partial: SimValue,
+    default: SimValue,
+) -> InteractionBuilder {
+    let table = create.table.name.clone();
+    let cleanup = vec![format!("DROP TABLE IF EXISTS {table}")];
+    let [c, d] = [0, 1].map(|i| create.table.columns[i].name.clone());
+    scratch_assertion(
+        format!("NOT NULL columns of {table} should reject NULL but accept their DEFAULT"),
+        connection_index,
+        cleanup,
+        move |db| {
+            db.execute(&create.to_string())?;
+            for (i, column) in [&c, &d].into_iter().enumerate() {
+                let mut values = row.clone();
+                values[i] = SimValue::NULL;
+                let sql = format!(
+                    "INSERT INTO {table} ({c}, {d}) VALUES ({}, {})",
+                    values[0], values[1]
+                );
+                match db.execute(&sql) {
+                    Err(LimboError::Constraint(msg))
+                        if msg.contains("NOT NULL constraint failed") => {}
+                    Err(LimboError::Constraint(msg)) => {
+                        return Ok(Err(format!(
+                            "'{sql}' failed with '{msg}' instead of the NOT NULL constraint on {column}"
+                        )));
+                    }
+                    Ok(()) => {
+                        return Ok(Err(format!(
+                            "'{sql}' stores NULL in NOT NULL column {column} but was accepted"
+                        )));
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+            let inserts = [
+                format!(
+                    "INSERT INTO {table} ({c}, {d}) VALUES ({}, {})",
+                    row[0], row[1]
+                ),
+                format!("INSERT INTO {table} ({c}) VALUES ({partial})"),
+            ];
+            for sql in &inserts {
+                match db.execute(sql) {
+                    Err(LimboError::Constraint(msg)) => {
+                        return Ok(Err(format!("'{sql}' has no NULL but failed with '{msg}'")));
+                    }
+                    result => result?,
+                }
+            }
+            let mut rows = db.query(&format!("SELECT {c}, {d} FROM {table}"))?;
+            rows.sort();
+            let mut expected = vec![row.clone(), vec![partial.clone(), default.clone()]];
+            expected.sort();
+            Ok(expect_rows(&format!("rows in {table}"), &expected, &rows))
+        },
+    )
+}
+
+/// Runs one prepared statement with full, partial and cleared bindings. See
+/// [crate::model::property::Property::BindingLifecycle].
+pub(super) fn binding_lifecycle(
+    connection_index: usize,
+    sql: String,
+    first_binds: Vec<SimValue>,
+    second_binds: Vec<(usize, SimValue)>,
+) -> InteractionBuilder {
+    scratch_assertion(
+        format!("re-binding some parameters of '{sql}' should keep the others until cleared"),
+        connection_index,
+        vec![],
+        move |db| {
+            let mut stmt = db.prepare(&sql)?;
+            for (i, value) in first_binds.iter().enumerate() {
+                stmt.bind(i + 1, value)?;
+            }
+            let rows = stmt.run()?;
+            if let Err(e) = expect_rows("all bound", &vec![first_binds.clone()], &rows) {
+                return Ok(Err(e));
+            }
+
+            let mut expected = first_binds.clone();
+            for (index, value) in &second_binds {
+                stmt.bind(*index, value)?;
+                expected[index - 1] = value.clone();
+            }
+            let rows = stmt.run()?;
+            if let Err(e) = expect_rows("partially re-bound", &vec![expected], &rows) {
+                return Ok(Err(e));
+            }
+
+            stmt.clear_bindings();
+            let rows = stmt.run()?;
+            Ok(expect_rows(
+                "bindings cleared",
+                &vec![vec![SimValue::NULL; first_binds.len()]],
+                &rows,
+            ))
+        },
+    )
+}
+
+/// Journal mode a [journal_mode_switch] found in effect, checked again after the reopen.
+pub(super) type SavedJournalMode = Arc<Mutex<Option<String>>>;
+
+/// Switches the journal mode to `to_mode` and back. See
+/// [crate::model::property::Property::JournalModeSwitch].
+pub(super) fn journal_mode_switch(
+    connection_index: usize,
+    to_mode: &'static str,
+    saved: SavedJournalMode,
+) -> InteractionBuilder {
+    scratch_assertion(
+        format!("switching journal_mode to {to_mode} and back should be reported faithfully"),
+        connection_index,
+        vec![],
+        move |db| {
+            *saved.lock().unwrap() = None;
+            let from_mode = journal_mode(db, None)?;
+            let result = switch_journal_mode(db, to_mode, &from_mode);
+            // Only a database that made it back to its original mode has a known mode
+            // after the reopen: WAL persists in the file, the other modes do not.
+            let current = journal_mode(db, None).ok();
+            *saved.lock().unwrap() = current.filter(|mode| mode.eq_ignore_ascii_case(&from_mode));
+            result
+        },
+    )
+}
+
+fn switch_journal_mode(
+    db: &mut Scratch<'_>,
+    to_mode: &str,
+    from_mode: &str,
+) -> turso_core::Result<Result<(), String>> {
+    for (target, previous) in [(to_mode, from_mode), (from_mode, to_mode)] {
+        let reported = journal_mode(db, Some(target))?;
+        // The switch may be refused, in which case the previous mode stays
+        let accepted = [target, previous];
+        if !accepted.iter().any(|m| m.eq_ignore_ascii_case(&reported)) {
+            return Ok(Err(format!(
+                "PRAGMA journal_mode = {target} reported {reported}, expected one of {accepted:?}"
+            )));
+        }
+        let current = journal_mode(db, None)?;
+        if !current.eq_ignore_ascii_case(&reported) {
+            return Ok(Err(format!(
+                "PRAGMA journal_mode = {target} reported {reported}, but the mode in effect is {current}"
+            )));
+        }
+    }
+    Ok(Ok(()))
+}
+
+/// Checks that the database came back from a reopen in the mode [journal_mode_switch]
+/// left it in.
+pub(super) fn journal_mode_after_reopen(
+    connection_index: usize,
+    saved: SavedJournalMode,
+) -> InteractionBuilder {
+    scratch_assertion(
+        "journal_mode should survive a reopen".to_string(),
+        connection_index,
+        vec![],
+        move |db| {
+            // Nothing to compare against if the switch was skipped or not undone
+            let Some(expected) = saved.lock().unwrap().clone() else {
+                return Ok(Ok(()));
+            };
+            let current = journal_mode(db, None)?;
+            if current.eq_ignore_ascii_case(&expected) {
+                Ok(Ok(()))
+            } else {
+                Ok(Err(format!(
+                    "journal_mode is {current} after reopen, expected {expected}"
+                )))
+            }
+        },
+    )
+}
+
+fn journal_mode(db: &mut Scratch<'_>, set: Option<&str>) -> turso_core::Result<String> {
+    let sql = match set {
+        Some(mode) => format!("PRAGMA journal_mode = {mode}"),
+        None => "PRAGMA journal_mode".to_string(),
+    };
+    let value = db.query_value(&sql)?;
+    value.0.to_text().map(str::to_string).ok_or_else(|| {
+        LimboError::InternalError(format!("{sql} returned a non-text value: {value:?}"))
+    })
+}
+
+/// Checkpoints the WAL with `mode` and checks what is left of it. See
+/// [crate::model::property::Property::WalCheckpointIntegrity].
+pub(super) fn wal_checkpoint(connection_index: usize, mode: CheckpointMode) -> InteractionBuilder {
+    let sql = format!("PRAGMA wal_checkpoint({})", mode.as_str());
+    scratch_assertion(
+        format!("{sql} should move the WAL into the database"),
+        connection_index,
+        vec![],
+        move |db| {
+            // A previous property may have left the database in another journal mode
+            if !journal_mode(db, None)?.eq_ignore_ascii_case("wal") {
+                return Ok(Ok(()));
+            }
+            let rows = db.query(&sql)?;
+            let values: Option<Vec<i64>> = match rows.as_slice() {
+                [row] => row.iter().map(|value| value.0.as_int()).collect(),
+                _ => None,
+            };
+            let Some(&[busy, log, checkpointed]) = values.as_deref() else {
+                return Ok(Err(format!(
+                    "{sql} returned {rows:?}, expected one row of 3 integers"
+                )));
+            };
+            if checkpointed > log {
+                return Ok(Err(format!(
+                    "{sql} checkpointed {checkpointed} frames of a {log} frame WAL"
+                )));
+            }
+            // Another connection's reader can block the checkpoint from finishing
+            if mode != CheckpointMode::Truncate || busy != 0 {
+                return Ok(Ok(()));
+            }
+            if log != 0 || checkpointed != 0 {
+                return Ok(Err(format!(
+                    "{sql} reported log={log} checkpointed={checkpointed}, expected an empty WAL"
+                )));
+            }
+            let wal_size = db.wal_size()?;
+            if wal_size != 0 {
+                return Ok(Err(format!(
+                    "WAL is {wal_size} bytes after {sql}, expected 0"
+                )));
+            }
+            Ok(Ok(()))
+        },
+    )
+}
+
+/// Runs the same workload under each `mmap_size`. See
+/// [crate::model::property::Property::MmapSizeNeutral].
+pub(super) fn mmap_size_neutral(
+    id: usize,
+    connection_index: usize,
+    workload: Vec<(i64, String, usize)>,
+    mmap_sizes: Vec<i64>,
+) -> InteractionBuilder {
+    let t = format!("sim_mmap_{id}");
+    let cleanup = vec![format!("DROP TABLE IF EXISTS {t}")];
+    scratch_assertion(
+        format!("reads of {t} should not depend on mmap_size ({mmap_sizes:?})"),
+        connection_index,
+        cleanup,
+        move |db| {
+            // mmap_size is connection state; restore whatever the run configured.
+            let original = db.query("PRAGMA mmap_size")?;
+            let result = mmap_size_scenario(db, &t, &workload, &mmap_sizes);
+            if let Some(size) = original.first().and_then(|row| row.first()) {
+                db.execute(&format!("PRAGMA mmap_size = {size}"))?;
+            }
+            result
+        },
+    )
+}
+
+fn mmap_size_scenario(
+    db: &mut Scratch<'_>,
+    t: &str,
+    workload: &[(i64, String, usize)],
+    mmap_sizes: &[i64],
+) -> turso_core::Result<Result<(), String>> {
+    let reads = [
+        format!("SELECT id, val, data FROM {t} ORDER BY id"),
+        format!("SELECT count(*), sum(length(data)) FROM {t}"),
+    ];
+    let mut baseline: Option<(i64, Vec<Rows>)> = None;
+    for &size in mmap_sizes {
+        db.execute(&format!("PRAGMA mmap_size = {size}"))?;
+        db.execute(&format!(
+            "CREATE TABLE {t} (id INTEGER PRIMARY KEY, val TEXT, data BLOB)"
+        ))?;
+        for (id, val, len) in workload {
+            // Bytes derived from the id, so that rows can't stand in for each other.
+            let data: String = (0..*len)
+                .map(|i| {
+                    format!(
+                        "{:02X}",
+                        (*id as usize).wrapping_mul(31).wrapping_add(i) as u8
+                    )
+                })
+                .collect();
+            db.execute(&format!(
+                "INSERT INTO {t} VALUES ({id}, '{val}', X'{data}')"
+            ))?;
+        }
+        db.execute(&format!("UPDATE {t} SET val = val || '!' WHERE id % 2 = 0"))?;
+        db.execute(&format!("DELETE FROM {t} WHERE id % 3 = 0"))?;
+        let results = reads
+            .iter()
+            .map(|sql| db.query(sql))
+            .collect::<turso_core::Result<Vec<_>>>()?;
+        db.execute(&format!("DROP TABLE {t}"))?;
+
+        let Some((baseline_size, expected)) = &baseline else {
+            baseline = Some((size, results));
+            continue;
+        };
+        for ((sql, expected), actual) in reads.iter().zip(expected).zip(&results) {
+            if let Err(e) = expect_rows(
+                &format!("'{sql}' with mmap_size = {size} vs {baseline_size}"),
+                expected,
+                actual,
+            ) {
+                return Ok(Err(e));
+            }
+        }
+    }
+    Ok(Ok(()))
+}
+
+/// Whether `EXPLAIN QUERY PLAN <select>` reports a lookup through `index_name`, e.g.
+/// `SEARCH t USING INDEX idx (a=?)` or `SCAN t USING COVERING INDEX idx`.
+pub(super) fn query_plan_uses_index(
+    env: &mut SimulatorEnv,
+    connection_index: usize,
+    select: &str,
+    index_name: &str,
+) -> turso_core::Result<bool> {
+    let plan = run_sql(
+        env,
+        connection_index,
+        &format!("EXPLAIN QUERY PLAN {select}"),
+    )?;
+    let needle = format!("INDEX {index_name}");
+    Ok(plan.iter().any(|row| {
+        // The detail text is the last column
+        row.last()
+            .and_then(|detail| detail.0.to_text())
+            .is_some_and(|detail| {
+                detail.match_indices(&needle).any(|(at, _)| {
+                    !detail[at + needle.len()..]
+                        .starts_with(|c: char| c.is_alphanumeric() || c == '_')
+                })
+            })
+    }))
+}
+
+/// Whether the `sqlite_master` of the database holding `table_name` still has an
+/// entry for `index_name`.
+pub(super) fn index_in_schema(
+    env: &mut SimulatorEnv,
+    connection_index: usize,
+    table_name: &str,
+    index_name: &str,
+) -> turso_core::Result<bool> {
+    let schema = match table_name.rsplit_once('.') {
+        Some((db, _)) => format!("{db}.sqlite_master"),
+        None => "sqlite_master".to_string(),
+    };
+    let rows = run_sql(
+        env,
+        connection_index,
+        &format!(
+            "SELECT name FROM {schema} WHERE type = 'index' AND name = '{}'",
+            index_name.replace('\'', "''")
+        ),
+    )?;
+    Ok(!rows.is_empty())
+}
+
+/// Error messages of statements the engine and SQLite intentionally disagree on, see
+/// COMPAT.md. A query failing on one side with one of these is not a divergence.
+const KNOWN_DIVERGENCES: &[&str] = &["not supported", "not implemented"];
+
+/// Whether `connection_index` runs on the engine, so that SQLite can serve as its
+/// reference. Differential runs already execute every query on SQLite.
+pub(super) fn reference_available(env: &SimulatorEnv, connection_index: usize) -> bool {
+    matches!(
+        env.connections[connection_index],
+        SimConnection::LimboConnection(_)
+    )
+}
+
+/// In-memory SQLite copy of the model's tables, including attached databases, kept
+/// in the [SimulatorEnv] between differential checks. Each check only recreates the
+/// tables whose schema or rows changed since the previous one.
+pub(crate) struct ReferenceDb {
+    conn: rusqlite::Connection,
+    /// Tables the copy holds.
+    tables: Vec<Table>,
+}
+
+impl ReferenceDb {
+    fn new(attached_dbs: &[String]) -> turso_core::Result<Self> {
+        let conn = rusqlite::Connection::open_in_memory().map_err(rusqlite_error)?;
+        for name in attached_dbs {
+            conn.execute(&format!("ATTACH ':memory:' AS {name}"), [])
+                .map_err(rusqlite_error)?;
+        }
+        Ok(Self {
+            conn,
+            tables: Vec::new(),
+        })
+    }
+
+    /// Brings the copy in line with `tables`.
+    fn sync(&mut self, tables: &[Table]) -> turso_core::Result<()> {
+        let unchanged = |a: &Table, b: &Table| {
+            a.name == b.name && a.rows == b.rows && create_sql(a) == create_sql(b)
+        };
+        let stale: Vec<&Table> = self
+            .tables
+            .iter()
+            .filter(|old| !tables.iter().any(|new| unchanged(old, new)))
+            .collect();
+        let fresh: Vec<&Table> = tables
+            .iter()
+            .filter(|new| !self.tables.iter().any(|old| unchanged(old, new)))
+            .collect();
+        if stale.is_empty() && fresh.is_empty() {
+            return Ok(());
+        }
+
+        self.conn.execute_batch("BEGIN").map_err(rusqlite_error)?;
+        for table in stale {
+            self.conn
+                .execute(&format!("DROP TABLE {}", table.name), [])
+                .map_err(rusqlite_error)?;
+        }
+        for table in fresh {
+            self.conn
+                .execute(&create_sql(table), [])
+                .map_err(rusqlite_error)?;
+            // Generated columns are computed by SQLite itself
+            let columns: Vec<usize> = (0..table.columns.len())
+                .filter(|&i| !table.columns[i].is_generated())
+                .collect();
+            for row in &table.rows {
+                let insert = Insert::ValuesWithColumns {
+                    table: table.name.clone(),
+                    columns: columns
+                        .iter()
+                        .map(|&i| table.columns[i].name.clone())
+                        .collect(),
+                    values: vec![columns.iter().map(|&i| row[i].clone()).collect()],
+                };
+                self.conn
+                    .execute(&insert.to_string(), [])
+                    .map_err(rusqlite_error)?;
+            }
+        }
+        self.conn.execute_batch("COMMIT").map_err(rusqlite_error)?;
+        self.tables = tables.to_vec();
+        Ok(())
+    }
+
+    fn query(&self, sql: &str) -> turso_core::Result<Rows> {
+        let mut stmt = self.conn.prepare(sql).map_err(rusqlite_error)?;
+        let column_count = stmt.column_count();
+        let rows = stmt.query([]).map_err(rusqlite_error)?;
+        rusqlite_rows(rows, column_count)
+    }
+}
+
+fn create_sql(table: &Table) -> String {
+    Create {
+        table: table.clone(),
+    }
+    .to_string()
+}
+
+/// Takes the reference database out of `env`, up to date with the model's tables for
+/// `connection_index`. A copy that fails to update is dropped and rebuilt next time.
+fn take_reference_db(
+    env: &mut SimulatorEnv,
+    connection_index: usize,
+) -> turso_core::Result<ReferenceDb> {
+    let mut reference = match env.reference_db.take() {
+        Some(reference) => reference,
+        None => ReferenceDb::new(&env.attached_dbs)?,
+    };
+    reference.sync(&env.get_conn_tables(connection_index))?;
+    Ok(reference)
+}
+
+/// Positions in the result rows of the ORDER BY keys of `query`, when every key is one
+/// of its result columns. Rows tied on the keys may come back in any order, but the
+/// sequence of keys is fixed by the ORDER BY.
+fn order_by_key_positions(query: &Query, tables: &[Table]) -> Option<Vec<usize>> {
+    let Query::Select(select) = query else {
+        return None;
+    };
+    if !select.body.compounds.is_empty() {
+        return None;
+    }
+    let inner = &select.body.select;
+    let order_by = inner.order_by.as_ref()?;
+    let mut names: Vec<Option<String>> = Vec::new();
+    for column in &inner.columns {
+        match column {
+            ResultColumn::Star => {
+                let from = inner.from.as_ref()?;
+                let SelectTable::Table(first) = &from.table else {
+                    return None;
+                };
+                for name in std::iter::once(first).chain(from.joins.iter().map(|j| &j.table)) {
+                    let table = tables.iter().find(|t| &t.name == name)?;
+                    names.extend(
+                        table
+                            .columns
+                            .iter()
+                            .map(|c| Some(format!("{name}.{}", c.name))),
+                    );
+                }
+            }
+            ResultColumn::Column(name) => names.push(Some(name.clone())),
+            ResultColumn::Expr(_) => names.push(None),
+        }
+    }
+    order_by
+        .columns
+        .iter()
+        .map(|(key, _)| {
+            let mut matches = names
+                .iter()
+                .enumerate()
+                .filter(|(_, name)| name.as_deref() == Some(key.as_str()));
+            match (matches.next(), matches.next()) {
+                (Some((i, _)), None) => Some(i),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Runs `query` on the property's connection and on a SQLite database seeded with the
+/// model's state. See [crate::model::property::Property::DifferentialAgainstReference].
+pub(super) fn differential_against_reference(
+    connection_index: usize,
+    query: Query,
+) -> InteractionBuilder {
+    let sql = query.to_string();
+    InteractionBuilder::with_interaction(InteractionType::Assertion(Assertion::new(
+        format!("'{sql}' should return the same result as SQLite"),
+        move |_stack, env: &mut SimulatorEnv| {
+            if !reference_available(env, connection_index) {
+                return Ok(Ok(()));
+            }
+            let reference = match take_reference_db(env, connection_index) {
+                Ok(reference) => reference,
+                Err(err) => {
+                    tracing::warn!("skipping differential check, cannot seed SQLite: {err}");
+                    return Ok(Ok(()));
+                }
+            };
+            let expected = reference.query(&sql);
+            let order_keys = order_by_key_positions(&query, &reference.tables);
+            env.reference_db = Some(reference);
+            let actual = run_sql(env, connection_index, &sql);
+
+            match (actual, expected) {
+                (Err(err), _) if is_recoverable_tx_error(&err) => Ok(Ok(())),
+                (Ok(mut actual), Ok(mut expected)) => {
+                    // Rows tied on the ORDER BY keys may come back in any order, so the
+                    // rows are compared as a multiset and the keys in order.
+                    let keys = |rows: &Rows, positions: &[usize]| -> Rows {
+                        rows.iter()
+                            .map(|row| {
+                                positions
+                                    .iter()
+                                    .filter_map(|&i| row.get(i).cloned())
+                                    .collect()
+                            })
+                            .collect()
+                    };
+                    let ordered = order_keys
+                        .as_ref()
+                        .map(|positions| (keys(&expected, positions), keys(&actual, positions)));
+                    actual.sort();
+                    expected.sort();
+                    if !rows_match(&expected, &actual) {
+                        print_diff(&expected, &actual, "sqlite", "database");
+                        return Ok(Err(format!(
+                            "'{sql}' returned {} rows but SQLite returned {}",
+                            actual.len(),
+                            expected.len()
+                        )));
+                    }
+                    if let Some((expected, actual)) = ordered
+                        && !rows_match(&expected, &actual)
+                    {
+                        print_diff(&expected, &actual, "sqlite", "database");
+                        return Ok(Err(format!(
+                            "'{sql}' returned its rows in a different ORDER BY order than SQLite"
+                        )));
+                    }
+                    Ok(Ok(()))
+                }
+                (Err(_), Err(_)) => Ok(Ok(())),
+                (Err(err), Ok(_)) | (Ok(_), Err(err)) if is_known_divergence(&err) => {
+                    tracing::warn!(
+                        "skipping differential check of '{sql}', known divergence: {err}"
+                    );
+                    Ok(Ok(()))
+                }
+                (Err(err), Ok(_)) => {
+                    Ok(Err(format!("'{sql}' failed but succeeds on SQLite: {err}")))
+                }
+                (Ok(_), Err(err)) => {
+                    Ok(Err(format!("'{sql}' succeeded but fails on SQLite: {err}")))
+                }
+            }
+        },
+        vec![],
+    )))
+}
+
+fn is_known_divergence(err: &LimboError) -> bool {
+    let msg = err.to_string();
+    KNOWN_DIVERGENCES.iter().any(|known| msg.contains(known))
+}
+
+/// Row equality that tolerates rounding differences between floating point results,
+/// e.g. sums accumulated in a different order.
+fn rows_match(expected: &Rows, actual: &Rows) -> bool {
+    expected.len() == actual.len()
+        && expected.iter().zip(actual).all(|(e, a)| {
+            e.len() == a.len()
+                && e.iter().zip(a).all(|(e, a)| match (&e.0, &a.0) {
+                    (Value::Numeric(Numeric::Float(_)), Value::Numeric(Numeric::Float(_))) => {
+                        let (e, a) = (e.0.as_float(), a.0.as_float());
+                        (e - a).abs() <= 1e-9 * e.abs().max(a.abs()).max(1.0)
+                    }
+                    _ => e == a,
+                })
+        })
+}
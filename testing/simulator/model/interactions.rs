@@ -498,6 +498,11 @@ impl InteractionBuilder {
     pub fn has_property_meta(&self) -> bool {
         self.property_meta.is_some()
     }
+
+    /// Checks to see if the connection index was already set
+    pub fn has_connection_index(&self) -> bool {
+        self.connection_index.is_some()
+    }
 }
 
 impl Deref for Interaction {
@@ -927,6 +932,7 @@ fn reopen_database(env: &mut SimulatorEnv) {
                 turso_core::OpenFlags::default(),
                 turso_core::DatabaseOpts::new()
                     .with_autovacuum(true)
+                    .with_vacuum(true)
                     .with_attach(true)
                     .with_generated_columns(true),
                 None,
@@ -759,6 +759,17 @@ impl Shadow for Drop {
     }
 }
 
+/// Value the existing rows of `table` report for `column` once it is added: its
+/// DEFAULT if that is a literal, NULL otherwise. The rows are not rewritten.
+pub(crate) fn added_column_value(table: &Table, column: &Column) -> SimValue {
+    column
+        .default_expr()
+        .and_then(|expr| expr_to_value(expr, &[], table))
+        .map_or(SimValue::NULL, |value| {
+            value.apply_affinity(column.column_type)
+        })
+}
+
 // TODO having &[SimValue] sometimes be expanded, and sometimes not (with NULL placeholders) is
 // error-prone. To make this type-safe, we should have domain types for expanded and non-expanded rows.
 /// Expand a partial row to a full row, by evaluating generated column expressions.
@@ -770,6 +781,16 @@ pub(crate) fn expand_with_generated_columns(
     let mut full_row = vec![SimValue::NULL; table.columns.len()];
 
     if let Some(cols) = insert_columns {
+        // Omitted columns take their DEFAULT, if it is a literal
+        for (col_idx, col) in table.columns.iter().enumerate() {
+            if let Some(value) = col
+                .default_expr()
+                .and_then(|expr| expr_to_value(expr, &[], table))
+            {
+                full_row[col_idx] = value.apply_affinity(col.column_type);
+            }
+        }
+
         for (i, col_name) in cols.iter().enumerate() {
             if let Some(pos) = table.columns.iter().position(|c| &c.name == col_name) {
                 full_row[pos] = insert_values[i].clone();
@@ -1210,6 +1231,14 @@ impl Shadow for Select {
                     // Union all means we just concatenate the results
                     rows.extend(compound_results.rows.into_iter());
                 }
+                CompoundOperator::Intersect | CompoundOperator::Except => {
+                    // Both remove duplicates like DISTINCT, then keep the rows that are
+                    // (or are not) in the right side
+                    let keep = matches!(compound.operator, CompoundOperator::Intersect);
+                    rows.sort_unstable();
+                    rows.dedup();
+                    rows.retain(|row| compound_results.rows.contains(row) == keep);
+                }
             }
         }
 
@@ -1463,9 +1492,10 @@ impl Shadow for AlterTable {
                 };
             }
             AlterTableType::AddColumn { column } => {
+                let value = added_column_value(table, column);
                 table.columns.push(column.clone());
                 table.rows.iter_mut().for_each(|row| {
-                    row.push(SimValue(turso_core::Value::Null));
+                    row.push(value.clone());
                 });
             }
             AlterTableType::AlterColumn { old, new } => {
@@ -1,5 +1,11 @@
 use serde::{Deserialize, Serialize};
-use sql_generation::model::query::{Create, Insert, Select, predicate::Predicate, update::Update};
+use sql_generation::model::{
+    query::{
+        Create, CreateIndex, Drop, Insert, Select, predicate::Predicate, transaction::Begin,
+        update::Update,
+    },
+    table::{Column, ColumnType, SimValue},
+};
 
 use crate::model::{CreateSequence, DropSequence, Query, QueryDiscriminants};
 
@@ -101,6 +107,34 @@ pub enum Property {
         /// The select query
         select: Select,
     },
+    /// Select-OrderBy is a property in which the rows of a select query
+    /// with an order by clause come back sorted, whatever index the
+    /// optimizer picks to satisfy the order by.
+    /// The execution of the property is as follows
+    ///     SELECT <c1>, <c2>, ... FROM <t> WHERE <predicate> ORDER BY <c1> [ASC|DESC], <c2> [ASC|DESC], ...
+    /// This property is a single-interaction property.
+    /// The interaction has the following constraints;
+    /// - Each row compares less than or equal to the next one in the requested
+    ///   directions, using SQLite's sort order (NULLs first, then numbers, text and blobs).
+    SelectOrderBy {
+        /// The select query, returning the order by columns in order
+        select: Select,
+    },
+    /// Select-Distinct is a property in which a select distinct query
+    /// returns each distinct projection of the matching rows exactly once.
+    /// Unlike `=`, DISTINCT treats NULLs as equal to each other, and an
+    /// INTEGER and a REAL holding the same number are duplicates.
+    /// The execution of the property is as follows
+    ///     SELECT DISTINCT <c1>, <c2>, ... FROM <t> [WHERE <predicate>]
+    /// This property is a single-interaction property.
+    /// The interaction has the following constraints;
+    /// - No two returned rows are equal.
+    /// - The returned rows are the deduplicated projection of the model rows
+    ///   matching the predicate.
+    SelectDistinct {
+        /// The select distinct query, over plain columns of a single table
+        select: Select,
+    },
     /// Delete-Select is a property in which the deleted row
     /// must not be in the resulting rows of a select query that has a
     /// where clause that matches the deleted row. In practice, `p1` of
@@ -139,6 +173,46 @@ pub enum Property {
         queries: Vec<Query>,
         select: Select,
     },
+    /// Rename-Table-Follow-Through is the rename-aware complement to Drop-Select:
+    /// after renaming a table, its old name must be gone and its new name must
+    /// hold the original content, with its indexes and the foreign keys pointing at
+    /// it following along.
+    /// The execution of the property is as follows
+    ///     ALTER TABLE <t> RENAME TO <new_name>
+    ///     SELECT * FROM <t> -> Error
+    ///     SELECT * FROM <new_name>
+    ///     PRAGMA integrity_check
+    /// followed by a scratch parent and child table, linked by a foreign key, where
+    /// the parent is renamed and the child is written to with `foreign_keys` on.
+    /// The assertions are
+    /// - Selecting from the old name fails with "no such table".
+    /// - The new name has the model's content.
+    /// - The table's indexes are attached to the new name and agree with it.
+    /// - The child's foreign key references the new name and is still enforced.
+    RenameTableFollowThrough {
+        table: String,
+        new_name: String,
+    },
+    /// View-Content is a property in which a view, which stores no rows of its
+    /// own, should return what its defining query returns when it is read.
+    /// The view is a projection of one table or an inner join of two.
+    /// The execution of the property is as follows
+    ///     CREATE VIEW <v> AS SELECT <columns> FROM <t> [JOIN <t2> ON <t>.<c> = <t2>.<c2>] WHERE <predicate>
+    ///     I_0
+    ///     I_1
+    ///     ...
+    ///     I_n
+    ///     SELECT * FROM <v>
+    ///     DROP VIEW <v>
+    /// The view returns the rows of the defining query evaluated in the model, in any order.
+    /// The interactions in the middle has the following constraints;
+    /// - There will be no errors in the middle interactions.
+    /// - The tables of the view will not be created, dropped, or altered.
+    ViewContent {
+        /// The defining query, whose result columns are qualified column names
+        select: Select,
+        queries: Vec<Query>,
+    },
     /// Select-Select-Optimizer is a property in which we test the optimizer by
     /// running two equivalent select queries, one with `SELECT <predicate> from <t>`
     /// and the other with `SELECT * from <t> WHERE <predicate>`. As highlighted by
@@ -168,6 +242,22 @@ pub enum Property {
         select: Select,
         where_clause: Predicate,
     },
+    /// IntersectExcept combines two filtered scans of a table with INTERSECT or
+    /// EXCEPT and compares the result with the model. Unlike UNION ALL, both remove
+    /// duplicate rows like DISTINCT does, even rows that appear only on the left.
+    ///
+    /// Execution:
+    ///     SELECT * FROM <t> WHERE <p1> INTERSECT | EXCEPT SELECT * FROM <t> WHERE <p2>
+    ///
+    /// Assertion:
+    /// - The rows are the distinct model rows satisfying `p1` that do (INTERSECT) or
+    ///   do not (EXCEPT) also satisfy `p2`.
+    IntersectExcept {
+        table: String,
+        p1: Predicate,
+        p2: Predicate,
+        except: bool,
+    },
     /// FsyncNoWait is a property which tests if we do not loose any data after not waiting for fsync.
     ///
     /// # Interactions
@@ -186,10 +276,47 @@ pub enum Property {
     /// rolls them back, then checks that the database still matches the shadow
     /// model. This targets pager/WAL/cache-spill bugs where rolled-back page
     /// contents remain visible.
+    ///
+    /// When `nested`, the first half of the writes run in an outer savepoint
+    /// before the inner one is opened, so rolling back to the inner savepoint
+    /// must undo only the second half, and releasing the outer one commits the
+    /// first half.
+    ///
+    /// Execution:
+    ///     SAVEPOINT <outer>      -- when nested
+    ///     I_0 ... I_k            -- when nested
+    ///     SAVEPOINT <sp>
+    ///     I_k+1 ... I_n
+    ///     ROLLBACK TO <sp>
+    ///     RELEASE <sp>
+    ///     RELEASE <outer>        -- when nested
+    ///     SELECT * FROM <t>      -- for each table
     SavepointRollback {
         queries: Vec<Query>,
         tables: Vec<String>,
         write_kinds: Vec<QueryDiscriminants>,
+        #[serde(default)]
+        nested: bool,
+    },
+    /// TransactionRollback runs random write interactions in an explicit
+    /// transaction, rolls it back, then checks that every table matches the
+    /// shadow model, which restores its snapshot from before BEGIN on ROLLBACK.
+    ///
+    /// Execution:
+    ///     BEGIN IMMEDIATE
+    ///     I_0
+    ///     ...
+    ///     I_n
+    ///     ROLLBACK
+    ///     SELECT * FROM <t>  -- for each table
+    ///
+    /// The interactions in the middle has the following constraints;
+    /// - There will be no errors in the middle interactions.
+    /// - The connection is not in a transaction already.
+    TransactionRollback {
+        queries: Vec<Query>,
+        tables: Vec<String>,
+        write_kinds: Vec<QueryDiscriminants>,
     },
     /// SequenceMonotonicity verifies that nextval() returns monotonically increasing
     /// values matching the expected arithmetic sequence.
@@ -205,18 +332,1092 @@ pub enum Property {
         num_calls: usize,
         drop: DropSequence,
     },
+    /// SkipScanCorrectness creates a fresh table with a low-cardinality leading
+    /// column and a two-column index on it, then filters on the second index
+    /// column only. Engines may answer such a query with a skip-scan of the
+    /// index (iterating the distinct leading values), which is easy to get
+    /// subtly wrong.
+    ///
+    /// Execution:
+    ///     CREATE TABLE <t> (a, b, c)
+    ///     INSERT INTO <t> VALUES (...)  -- few distinct values of `a`
+    ///     CREATE INDEX <i> ON <t> (a, b)
+    ///     SELECT * FROM <t> WHERE b <op> <v>   -- index eligible
+    ///     SELECT * FROM <t> WHERE +b <op> <v>  -- forced full scan
+    ///     DROP TABLE <t>
+    ///
+    /// Assertion:
+    /// - Both selects return the same multiset of rows, which is also the
+    ///   set of model rows matching the predicate.
+    SkipScanCorrectness {
+        create: Create,
+        insert: Insert,
+        index: CreateIndex,
+        /// Predicate constraining only the second index column
+        predicate: Predicate,
+        /// The same predicate with the column wrapped in unary `+`, which
+        /// keeps the planner from using the index
+        full_scan_predicate: Predicate,
+        drop: Drop,
+    },
+    /// ColumnToColumnComparison compares two columns of the same table against each
+    /// other and checks the result against the model, which converts the operands
+    /// according to SQLite's comparison affinity rules when the columns have different
+    /// declared types.
+    ///
+    /// Execution:
+    ///     SELECT * FROM <t> WHERE a < b
+    ///     SELECT * FROM <t> WHERE a = b
+    ///     SELECT * FROM <t> WHERE a IS b
+    ///
+    /// Assertion:
+    /// - Each select returns exactly the model rows for which the comparison holds.
+    ColumnToColumnComparison {
+        table: String,
+        column_a: String,
+        column_b: String,
+    },
+    /// JournalModeSwitch switches the journal mode of a populated database away and
+    /// back again, then reopens it, checking that no data is lost along the way.
+    ///
+    /// Execution:
+    ///     PRAGMA journal_mode              -- remember the current mode
+    ///     PRAGMA journal_mode = <to_mode>
+    ///     PRAGMA journal_mode = <previous mode>
+    ///     REOPEN_DATABASE
+    ///     SELECT * FROM <t>                -- for every table
+    ///     PRAGMA journal_mode
+    ///
+    /// Assertion:
+    /// - Each switch reports either the requested mode or, when the engine refuses
+    ///   the change (unsupported mode, or leaving/entering WAL without exclusive
+    ///   access), the mode that was already in effect; a following query agrees.
+    /// - All tables have their expected content after the reopen.
+    /// - The mode in effect after the reopen is the one the database started with.
+    JournalModeSwitch {
+        tables: Vec<String>,
+        to_mode: JournalMode,
+    },
+    /// ReplaceIntoSemantics checks that `REPLACE INTO` on a conflicting key behaves as
+    /// a delete of the conflicting row followed by an insert, including the side
+    /// effects of that delete.
+    ///
+    /// Execution (on scratch tables, with `foreign_keys = ON`):
+    ///     CREATE TABLE parent (id INTEGER PRIMARY KEY, val)
+    ///     CREATE TABLE child (id, parent_id REFERENCES parent ON DELETE <action>)
+    ///     CREATE TRIGGER ... AFTER DELETE / AFTER INSERT ON parent  -- log to a table
+    ///     INSERT INTO parent/child VALUES (...)
+    ///     REPLACE INTO parent VALUES (<conflicting id>, <new val>)
+    ///
+    /// Assertion:
+    /// - Exactly one parent row has the replaced key, carrying the new value.
+    /// - Children of the replaced row were cascaded or nulled per the FK action.
+    /// - Only the INSERT trigger fired: like SQLite with `recursive_triggers` off,
+    ///   the implicit delete does not fire DELETE triggers.
+    ReplaceIntoSemantics {
+        /// `ON DELETE CASCADE` if true, `ON DELETE SET NULL` otherwise
+        cascade: bool,
+        /// Parent rows inserted up front, each with one child row
+        seed: Vec<(i64, String)>,
+        /// The replacing row, whose key always collides with a seeded parent
+        replace: (i64, String),
+    },
+    /// DropIndexFallsBackToScan creates an index that an equality lookup is planned
+    /// through, drops it, and checks that the same lookup still returns the same
+    /// rows through a table scan.
+    ///
+    /// Execution:
+    ///     CREATE INDEX <index> ON <t> (<col>)
+    ///     EXPLAIN QUERY PLAN SELECT * FROM <t> WHERE <predicate>
+    ///     SELECT * FROM <t> WHERE <predicate>
+    ///     DROP INDEX <index>
+    ///     SELECT * FROM <t> WHERE <predicate>
+    ///     EXPLAIN QUERY PLAN SELECT * FROM <t> WHERE <predicate>
+    ///
+    /// Assertion:
+    /// - The plan uses the index while it exists, and no longer mentions it after
+    ///   the drop.
+    /// - Both selects return the same rows, which are the model rows matching the
+    ///   predicate.
+    /// - `sqlite_master` no longer has an entry for the index, and neither does
+    ///   the model.
+    DropIndexFallsBackToScan {
+        table: String,
+        index: CreateIndex,
+        /// Equality predicate on the indexed column
+        predicate: Predicate,
+    },
+    /// PartialIndex creates an index with a WHERE clause, and runs one query whose
+    /// WHERE includes that clause, so that the index holds every row it can return,
+    /// and one whose WHERE does not, so that the index must not be used for it
+    /// alone. Both are compared with a full scan after the index is dropped.
+    ///
+    /// Execution:
+    ///     CREATE INDEX <index> ON <t> (<col>) WHERE <condition>
+    ///     SELECT * FROM <t> WHERE <condition> AND <predicate>   -- covered
+    ///     SELECT * FROM <t> WHERE <predicate>                   -- not covered
+    ///     DROP INDEX <index>
+    ///     SELECT * FROM <t> WHERE <condition> AND <predicate>
+    ///     SELECT * FROM <t> WHERE <predicate>
+    ///
+    /// Assertion:
+    /// - Each query returns the same rows while the index exists as with the scan,
+    ///   and those are the model rows matching its WHERE.
+    PartialIndex {
+        table: String,
+        /// Index whose `where_clause` is a comparison of `<col>` with a literal
+        index: CreateIndex,
+        predicate: Predicate,
+    },
+    /// GlobVsLikeCaseSensitivity inserts differently-cased spellings of the same word
+    /// into a text column and contrasts LIKE, which ignores ASCII case by default,
+    /// with GLOB, which never does.
+    ///
+    /// Execution:
+    ///     INSERT INTO <t> VALUES (...)    -- rows with 'abc', 'ABC', 'AbC' in <column>
+    ///     SELECT * FROM <t> WHERE <column> LIKE '<pattern>'
+    ///     SELECT * FROM <t> WHERE <column> GLOB '<pattern>'
+    ///     PRAGMA case_sensitive_like = ON
+    ///     SELECT * FROM <t> WHERE <column> LIKE '<pattern>'
+    ///     PRAGMA case_sensitive_like = OFF
+    ///
+    /// Assertion:
+    /// - Each select returns exactly the model rows matching its pattern.
+    /// - LIKE matches every spelling of the word, GLOB and case-sensitive LIKE only
+    ///   the one spelled like the pattern.
+    GlobVsLikeCaseSensitivity {
+        table: String,
+        column: String,
+        insert: Insert,
+        /// Lowercase word that the inserted values are case variants of
+        pattern: String,
+    },
+    /// ExpressionIndexQueryMatch creates a fresh table with an index on an arithmetic
+    /// expression of its columns, and checks that lookups on the expression agree
+    /// with a scan and with the model, both right after the index is built and
+    /// after writes that move rows to and from the looked-up value.
+    ///
+    /// Execution:
+    ///     CREATE TABLE <t> (a, b, c)
+    ///     INSERT INTO <t> VALUES (...)
+    ///     CREATE INDEX <i> ON <t> (<expr>)
+    ///     EXPLAIN QUERY PLAN SELECT * FROM <t> WHERE <expr> = <v>
+    ///     SELECT * FROM <t> WHERE <expr> = <v>      -- index eligible
+    ///     SELECT * FROM <t> WHERE +(<expr>) = <v>   -- forced full scan
+    ///     INSERT INTO <t> VALUES (...)              -- some rows with <expr> = <v>
+    ///     UPDATE <t> SET a = <x> WHERE <expr> = <v>
+    ///     SELECT * FROM <t> WHERE <expr> = <v>
+    ///     SELECT * FROM <t> WHERE +(<expr>) = <v>
+    ///     PRAGMA integrity_check
+    ///     DROP TABLE <t>
+    ///
+    /// Assertion:
+    /// - The lookup is planned through the expression index.
+    /// - Each pair of selects returns the same rows, which are the model rows for
+    ///   which the expression, evaluated per row, equals `<v>`.
+    /// - The index is consistent with the table after the writes.
+    ExpressionIndexQueryMatch {
+        create: Create,
+        insert: Insert,
+        /// Indexed expression over the integer columns `a` and `b`
+        index_expr: Predicate,
+        /// Value of the expression that is looked up
+        value: SimValue,
+        insert_after: Insert,
+        update: Update,
+        drop: Drop,
+    },
+    /// NumericTextComparison stores integers, reals, numeric-looking text and blobs
+    /// in a column without type affinity, and checks that comparisons order them by
+    /// storage class before content: every number sorts before every text value,
+    /// which sorts before every blob, e.g. integer 100 sorts before text '5'.
+    ///
+    /// Execution:
+    ///     CREATE TABLE <t> (<column> BLOB)
+    ///     INSERT INTO <t> VALUES (100), (2.5), ('5'), ('abc'), (X'35'), ...
+    ///     SELECT * FROM <t> ORDER BY <column>
+    ///     SELECT * FROM <t> WHERE <column> < 'a'
+    ///     DROP TABLE <t>
+    ///
+    /// Assertion:
+    /// - ORDER BY returns the values in storage class order, and in the order given
+    ///   by the model's storage class comparator.
+    /// - The WHERE select returns all numbers, no blobs, and exactly the model rows
+    ///   that compare below 'a'.
+    NumericTextComparison {
+        create: Create,
+        column: String,
+        insert: Insert,
+        drop: Drop,
+    },
+    /// AggregateViewReadOnly creates a view over a GROUP BY aggregation and checks
+    /// that it reads back the aggregation computed from the model, and that it
+    /// cannot be written to, since aggregate views are never updatable.
+    ///
+    /// Execution:
+    ///     CREATE TABLE <t> (g INTEGER, v INTEGER)
+    ///     INSERT INTO <t> VALUES (...)
+    ///     CREATE VIEW <v> AS SELECT g, count(*), sum(v) FROM <t> GROUP BY g
+    ///     SELECT * FROM <v>
+    ///     INSERT INTO <v> ... | UPDATE <v> ... | DELETE FROM <v>
+    ///     SELECT * FROM <t>
+    ///     DROP VIEW <v>
+    ///     DROP TABLE <t>
+    ///
+    /// Assertion:
+    /// - The view returns one row per distinct `g` with the count and sum of the
+    ///   model rows in that group.
+    /// - The mutation fails with an error naming the view, as SQLite's
+    ///   "cannot modify <v> because it is a view" does, and leaves `<t>` unchanged.
+    AggregateViewReadOnly {
+        create: Create,
+        insert: Insert,
+        view: String,
+        create_aggregate_view: String,
+        /// INSERT, UPDATE or DELETE against the view
+        bad_insert: String,
+        drop: Drop,
+    },
+    /// CrossDatabaseTransactionAtomicity writes to `main` and to attached databases
+    /// in one transaction, and checks that the transaction is atomic across all of
+    /// them: a rollback leaves every database untouched and a commit applies the
+    /// writes to every database.
+    ///
+    /// Execution:
+    ///     CREATE TABLE <t> (...)
+    ///     CREATE TABLE <aux>.<t> (...)          -- for each attached database
+    ///     BEGIN IMMEDIATE
+    ///     INSERT INTO <t> VALUES (...)
+    ///     INSERT INTO <aux>.<t> VALUES (...)
+    ///     ROLLBACK
+    ///     SELECT * FROM <t>; SELECT * FROM <aux>.<t>
+    ///     BEGIN IMMEDIATE
+    ///     INSERT INTO <t> VALUES (...)
+    ///     INSERT INTO <aux>.<t> VALUES (...)
+    ///     COMMIT
+    ///     SELECT * FROM <t>; SELECT * FROM <aux>.<t>
+    ///     DROP TABLE <t>; DROP TABLE <aux>.<t>
+    ///
+    /// Assertion:
+    /// - After ROLLBACK no table holds any row.
+    /// - After COMMIT every table holds exactly the rows inserted into it.
+    CrossDatabaseTransactionAtomicity {
+        /// Attached databases written to along with `main`
+        attach: Vec<String>,
+        /// One table per database, `main` first
+        creates: Vec<Create>,
+        /// One insert per table, in the order of `creates`
+        writes_to_both: Vec<Insert>,
+        drops: Vec<Drop>,
+    },
+    /// BindingLifecycle re-runs one prepared statement while re-binding some of its
+    /// parameters, and checks how parameter values carry over between runs.
+    ///
+    /// Execution (on a single prepared statement):
+    ///     SELECT ?1, ?2, ..., ?n   -- every parameter bound
+    ///     SELECT ?1, ?2, ..., ?n   -- only some parameters re-bound
+    ///     SELECT ?1, ?2, ..., ?n   -- after clearing the bindings
+    ///
+    /// Assertion:
+    /// - The first run returns the bound values.
+    /// - The second run returns the re-bound values, and the first values for every
+    ///   parameter that was not re-bound: resetting a statement keeps its bindings.
+    /// - After clearing the bindings every parameter is NULL.
+    BindingLifecycle {
+        sql_template: String,
+        /// Value bound to each parameter, in order
+        first_binds: Vec<SimValue>,
+        /// Parameters (1-based) re-bound before the second run, with their new values
+        second_binds_partial: Vec<(usize, SimValue)>,
+    },
+    /// CollatedPrimaryKeyUniqueness creates a table whose TEXT PRIMARY KEY is declared
+    /// `COLLATE NOCASE` or `COLLATE BINARY`, and inserts two keys that only differ in
+    /// case. Uniqueness of the key must be decided by its collation.
+    ///
+    /// Execution:
+    ///     CREATE TABLE <t> (k TEXT COLLATE <NOCASE|BINARY> PRIMARY KEY, v INTEGER)
+    ///     INSERT INTO <t> VALUES ('abc', ...)
+    ///     INSERT INTO <t> VALUES ('ABC', ...)
+    ///     SELECT k FROM <t>
+    ///     DROP TABLE <t>
+    ///
+    /// Assertion:
+    /// - Under NOCASE the second insert fails with a UNIQUE constraint violation
+    ///   and only the first key is stored.
+    /// - Under BINARY both inserts succeed and both keys are stored.
+    CollatedPrimaryKeyUniqueness {
+        create_nocase_pk: Create,
+        insert_first: Insert,
+        /// Same key as `insert_first` with its case flipped
+        insert_case_variant: Insert,
+        drop: Drop,
+    },
+    /// DifferentialAgainstReference runs a generated query on the engine and on a
+    /// reference SQLite database seeded with the model's tables and rows, turning the
+    /// query generator into a differential fuzzer. It only runs when the connection is
+    /// an engine connection, differential runs already compare every query.
+    ///
+    /// Execution:
+    ///     SELECT ...   -- on the connection and on the reference database
+    ///
+    /// Assertion:
+    /// - Both return the same rows, compared as a multiset since the order of
+    ///   ORDER BY ties is unspecified, or both fail.
+    /// - When the ORDER BY keys are result columns, both return the keys in the same
+    ///   order.
+    /// - Errors listed as known divergences from SQLite are ignored.
+    DifferentialAgainstReference {
+        query: Query,
+    },
+    /// ReturningClauseResult runs an INSERT, UPDATE or DELETE with a RETURNING clause
+    /// on a fresh table, and checks the returned rows against the rows the model says
+    /// the mutation affects. An INSERT may omit `c`, which must then be returned as
+    /// its DEFAULT.
+    ///
+    /// Execution:
+    ///     CREATE TABLE <t> (a INTEGER, b TEXT, c INTEGER DEFAULT <d>)
+    ///     INSERT INTO <t> VALUES (...)
+    ///     INSERT INTO <t> [(a, b)] VALUES (...) RETURNING <cols>
+    ///       | UPDATE <t> SET b = ... WHERE a >= <k> RETURNING <cols>
+    ///       | DELETE FROM <t> WHERE a >= <k> RETURNING <cols>
+    ///     DROP TABLE <t>
+    ///
+    /// Assertion:
+    /// - INSERT returns the inserted values, with the rowids assigned after the
+    ///   existing rows.
+    /// - UPDATE returns the matching rows with their new values.
+    /// - DELETE returns the matching rows as they were before the delete.
+    ReturningClauseResult {
+        create: Create,
+        insert: Insert,
+        /// INSERT, UPDATE or DELETE on the table, without its RETURNING clause
+        mutation: Query,
+        /// Columns of the RETURNING clause, `rowid` or a table column, or just `*`
+        returning_columns: Vec<String>,
+        drop: Drop,
+    },
+    /// RowValueInSubquery filters a table with a row-value IN against a subquery on
+    /// another table, and checks the result against the model's semi-join on the
+    /// column tuples.
+    ///
+    /// Execution:
+    ///     CREATE TABLE <outer> (a0 INTEGER, a1 INTEGER, ...)
+    ///     CREATE TABLE <inner> (b0 INTEGER, b1 INTEGER, ...)
+    ///     INSERT INTO <outer> VALUES (...)
+    ///     INSERT INTO <inner> VALUES (...)   -- skipped for an empty subquery
+    ///     SELECT * FROM <outer> WHERE (a0, a1, ...) IN (SELECT b0, b1, ... FROM <inner>)
+    ///     SELECT * FROM <outer> WHERE (a0, a1, ...) NOT IN (SELECT b0, b1, ... FROM <inner>)
+    ///     DROP TABLE <outer>; DROP TABLE <inner>
+    ///
+    /// Assertion:
+    /// - IN keeps the outer rows equal to some inner tuple.
+    /// - NOT IN keeps the outer rows that can't be equal to any inner tuple, even
+    ///   when the NULLs in either tuple are replaced by values.
+    /// - A tuple compared to an empty subquery is never IN and always NOT IN, even if
+    ///   it contains NULL.
+    RowValueInSubquery {
+        outer_table: Create,
+        inner_table: Create,
+        /// Pairs of outer and inner columns compared by the row values
+        columns: Vec<(String, String)>,
+        inserts: Vec<Insert>,
+        drops: Vec<Drop>,
+    },
+    /// SavepointWithConstraintFailure runs statements inside a savepoint, one of which
+    /// fails a UNIQUE constraint. Under the default ABORT resolution only the failing
+    /// statement is rolled back, the savepoint and the other statements are kept.
+    ///
+    /// Execution:
+    ///     CREATE TABLE <t> (k INTEGER UNIQUE, v TEXT)
+    ///     INSERT INTO <t> VALUES (...)
+    ///     SAVEPOINT <sp>
+    ///     INSERT INTO <t> VALUES (...)              -- succeeds
+    ///     INSERT INTO <t> VALUES (<new k>, ...), (<existing k>, ...)  -- fails
+    ///     INSERT INTO <t> VALUES (...)              -- succeeds
+    ///     SELECT * FROM <t>
+    ///     RELEASE <sp>
+    ///     SELECT * FROM <t>
+    ///     DROP TABLE <t>
+    ///
+    /// Assertion:
+    /// - The failing statement reports a constraint error.
+    /// - Its rows are gone, including the ones before the conflicting row, while the
+    ///   rows of the other statements are visible inside the savepoint.
+    /// - The savepoint can be released, keeping those rows.
+    SavepointWithConstraintFailure {
+        /// Creates the table and inserts its first rows
+        setup: Vec<Query>,
+        savepoint: String,
+        /// Inserts run inside the savepoint
+        statements_with_failure: Vec<Query>,
+        /// Index of the insert in `statements_with_failure` that repeats a key
+        failing: usize,
+        drop: Drop,
+    },
+    /// RecursiveTriggerTerminates creates an AFTER INSERT trigger that inserts into its
+    /// own table, so each row it writes would fire it again, and checks that the
+    /// triggering insert terminates.
+    ///
+    /// Execution (on a scratch table, after trying `PRAGMA recursive_triggers = ON`):
+    ///     CREATE TABLE t (n INTEGER)
+    ///     CREATE TRIGGER ... AFTER INSERT ON t [WHEN NEW.n < <bound>]
+    ///         BEGIN INSERT INTO t VALUES (NEW.n + 1); END
+    ///     INSERT INTO t VALUES (<start>)
+    ///
+    /// Assertion:
+    /// - With recursive triggers, a bounded trigger writes every value from `start` up
+    ///   to the bound, and an unbounded one fails with "too many levels of trigger
+    ///   recursion" once it reaches `SQLITE_MAX_TRIGGER_DEPTH`, leaving no rows.
+    /// - Without them (the default, and the only mode of engines lacking the pragma)
+    ///   the trigger fires once for the triggering insert and not for its own insert.
+    RecursiveTriggerTerminates {
+        /// Bound of the trigger's WHEN clause, `None` for a trigger without one
+        bound: Option<i64>,
+        /// Value written by the triggering insert
+        start: i64,
+    },
+    /// ForeignKeyCheckReportsOrphans inserts child rows whose parent does not exist
+    /// while foreign keys are off, then turns them on and checks that
+    /// `PRAGMA foreign_key_check` reports exactly those rows. Unlike insert-time
+    /// enforcement this scans the existing contents of the child table.
+    ///
+    /// Execution (on scratch tables, skipped if the pragma is not listed by
+    /// `PRAGMA pragma_list`):
+    ///     PRAGMA foreign_keys = OFF
+    ///     CREATE TABLE parent (id INTEGER PRIMARY KEY)
+    ///     CREATE TABLE child (id INTEGER PRIMARY KEY, parent_id REFERENCES parent(id))
+    ///     INSERT INTO parent/child VALUES (...)
+    ///     PRAGMA foreign_keys = ON
+    ///     PRAGMA foreign_key_check(child)
+    ///
+    /// Assertion:
+    /// - One (child, rowid, parent, 0) row for every child referencing a missing
+    ///   parent, and none for children referencing NULL or an existing parent.
+    ForeignKeyCheckReportsOrphans {
+        /// Keys of the parent rows
+        parents: Vec<i64>,
+        /// Parent key of each child row, the child at index `i` has rowid `i + 1`
+        children: Vec<Option<i64>>,
+    },
+    /// UpsertDoUpdate inserts a row into a scratch table with a PRIMARY KEY or UNIQUE
+    /// column, then a row with the same value in that column using
+    /// `ON CONFLICT(<c>) DO UPDATE SET <col> = excluded.<col>, ...`, or
+    /// `ON CONFLICT(<c>) DO NOTHING` when `do_nothing` is set.
+    ///
+    /// Execution (on a scratch table):
+    ///     CREATE TABLE <t> (<c> ... PRIMARY KEY | UNIQUE, ...)
+    ///     INSERT INTO <t> VALUES (<v>, ...)
+    ///     INSERT INTO <t> VALUES (<v>, ...) ON CONFLICT(<c>) DO UPDATE SET ... | DO NOTHING
+    ///     SELECT * FROM <t>
+    ///
+    /// Assertion:
+    /// - There is still a single row.
+    /// - With DO UPDATE the assigned columns hold the conflicting row's values and the
+    ///   others are unchanged; with DO NOTHING the row is the original one.
+    UpsertDoUpdate {
+        create: Create,
+        insert: Insert,
+        /// Conflicting insert, always carrying the DO UPDATE clause; its target is
+        /// also the DO NOTHING target
+        upsert: Insert,
+        do_nothing: bool,
+    },
+    /// CorrelatedSubquery filters a scratch parent table by a correlated subquery over a
+    /// child table and compares the result with the model. `col IN (<subquery>)` holds
+    /// only when `col` equals a non-NULL `fk`; it is false when the subquery is
+    /// empty and NULL when `col` is NULL or the subquery only adds NULLs, and
+    /// neither keeps the row.
+    ///
+    /// Execution (on scratch tables):
+    ///     CREATE TABLE <p>_parent (id INTEGER PRIMARY KEY, col INTEGER, y INTEGER)
+    ///     CREATE TABLE <p>_child (fk INTEGER, x INTEGER)
+    ///     INSERT ...
+    ///     SELECT * FROM <p>_parent WHERE col IN (SELECT fk FROM <p>_child WHERE <p>_child.x = <p>_parent.y)
+    ///
+    /// Assertion:
+    /// - The selected rows are exactly the parents whose `col` is among the `fk`s of
+    ///   the children with `x = y`.
+    CorrelatedSubquery {
+        /// `(col, y)` of each parent row, the parent at index `i` has rowid `i + 1`
+        parents: Vec<(Option<i64>, Option<i64>)>,
+        /// `(fk, x)` of each child row
+        children: Vec<(Option<i64>, Option<i64>)>,
+    },
+    /// UniqueViolation inserts a row into a scratch table with a UNIQUE column, then a
+    /// second row with the same value in that column. A plain insert must fail with a
+    /// UNIQUE constraint error and `INSERT OR IGNORE` must succeed without inserting.
+    ///
+    /// Execution (on a scratch table):
+    ///     CREATE TABLE <t> (<u> ... UNIQUE, ...)
+    ///     INSERT INTO <t> VALUES (<v>, ...)
+    ///     INSERT [OR IGNORE] INTO <t> VALUES (<v>, ...)
+    ///     SELECT count(*) FROM <t>
+    ///
+    /// Assertion:
+    /// - The duplicate insert fails with "UNIQUE constraint failed", or succeeds with
+    ///   `or_ignore`.
+    /// - The table holds one row.
+    UniqueViolation {
+        /// Scratch table with a UNIQUE column
+        create: Create,
+        insert: Insert,
+        /// Row repeating the UNIQUE value of `insert`
+        duplicate: Insert,
+        or_ignore: bool,
+    },
+    /// AutoincrementMonotonic creates a scratch table with an
+    /// `INTEGER PRIMARY KEY AUTOINCREMENT` column, inserts rows with explicit and
+    /// generated rowids, deletes the highest row and inserts once more. Unlike a
+    /// plain rowid alias, AUTOINCREMENT must never hand out a rowid at or below the
+    /// largest one ever used, even after that row is deleted.
+    ///
+    /// Execution (on a scratch table):
+    ///     CREATE TABLE <t> (<pk> INTEGER PRIMARY KEY AUTOINCREMENT, <payload> TEXT)
+    ///     INSERT INTO <t> (<pk>, <payload>) VALUES (<rowid>, ...) | INSERT INTO <t> (<payload>) VALUES (...)
+    ///     DELETE FROM <t> WHERE <pk> = <max>
+    ///     INSERT INTO <t> (<payload>) VALUES (...)
+    ///
+    /// Assertion:
+    /// - Every generated rowid is greater than every rowid used before it.
+    AutoincrementMonotonic {
+        create: Create,
+        /// Explicit rowid per insert, `None` to let the table assign one
+        rowids: Vec<Option<i64>>,
+    },
+    /// DefaultValue creates a scratch table with a column declaring a literal DEFAULT
+    /// (an integer, float, text or NULL) and inserts a row that omits that column.
+    /// The expected value is the DEFAULT expression stored in the column constraints.
+    ///
+    /// Execution (on a scratch table):
+    ///     CREATE TABLE <t> (<other> <type>, <c> <type> DEFAULT <literal>)
+    ///     INSERT INTO <t> (<other>) VALUES (<value>)
+    ///     SELECT <other>, <c> FROM <t>
+    ///
+    /// Assertion:
+    /// - The only row is `(<value>, <literal>)`.
+    DefaultValue {
+        create: Create,
+        /// Column carrying the DEFAULT constraint
+        column: String,
+        /// Value inserted into the other column
+        value: SimValue,
+    },
+    /// CheckConstraint creates a scratch table whose only column has a CHECK built
+    /// from comparisons of the column with literals, and inserts a row violating it,
+    /// a row satisfying it and a NULL. Comparisons with NULL are NULL, and a CHECK
+    /// that evaluates to NULL is not a violation, so the NULL row is accepted.
+    ///
+    /// Execution (on a scratch table):
+    ///     CREATE TABLE <t> (<c> <type> CHECK (<check>))
+    ///     INSERT INTO <t> VALUES (<invalid>)
+    ///     INSERT INTO <t> VALUES (<valid>)
+    ///     INSERT INTO <t> VALUES (NULL)
+    ///     SELECT * FROM <t>
+    ///
+    /// Assertion:
+    /// - The invalid row fails with "CHECK constraint failed".
+    /// - The valid and NULL rows are inserted, and they are the only rows.
+    CheckConstraint {
+        table: String,
+        column: String,
+        column_type: ColumnType,
+        /// Comparisons of `column` with literals, joined by AND or OR
+        check: Predicate,
+        /// Value for which `check` is false
+        invalid: SimValue,
+        /// Value for which `check` is true
+        valid: SimValue,
+    },
+    /// NotNullConstraint creates a scratch table with two NOT NULL columns, the second
+    /// of which has a literal DEFAULT. An explicit NULL is rejected in either column,
+    /// since a DEFAULT only fills in a column the insert leaves out.
+    ///
+    /// Execution (on a scratch table):
+    ///     CREATE TABLE <t> (<c> <type> NOT NULL, <d> <type> NOT NULL DEFAULT <default>)
+    ///     INSERT INTO <t> (<c>, <d>) VALUES (NULL, <d value>)
+    ///     INSERT INTO <t> (<c>, <d>) VALUES (<c value>, NULL)
+    ///     INSERT INTO <t> (<c>, <d>) VALUES (<c value>, <d value>)
+    ///     INSERT INTO <t> (<c>) VALUES (<partial>)
+    ///     SELECT <c>, <d> FROM <t>
+    ///
+    /// Assertion:
+    /// - Both rows with a NULL fail with "NOT NULL constraint failed".
+    /// - The other two rows are inserted, the one omitting `<d>` with `<default>`.
+    NotNullConstraint {
+        create: Create,
+        /// Values of `<c>` and `<d>` for the complete row
+        row: Vec<SimValue>,
+        /// Value of `<c>` for the row that omits `<d>`
+        partial: SimValue,
+        /// DEFAULT of `<d>`, with its column's affinity applied
+        default: SimValue,
+    },
+    /// NoOpUpdateBehavior updates a column of an existing table to its own value
+    /// and checks the changes-counter semantics of such an update: SQLite counts
+    /// every row matched by the WHERE clause, whether or not a value changed.
+    ///
+    /// Execution:
+    ///     SELECT rowid, * FROM <t> ORDER BY rowid
+    ///     UPDATE <t> SET <c> = <c> WHERE <predicate>
+    ///     SELECT changes()
+    ///     SELECT rowid, * FROM <t> ORDER BY rowid
+    ///     UPDATE <t> SET <c> = <c> WHERE FALSE
+    ///     SELECT changes()
+    ///
+    /// Assertion:
+    /// - `changes()` equals the number of model rows matching the predicate.
+    /// - The table contents and their rowids are the same before and after.
+    /// - The update matching no rows reports `changes() = 0`.
+    NoOpUpdateBehavior {
+        table: String,
+        /// Column set to its current value
+        update_setting_same_value: String,
+        predicate: Predicate,
+    },
+    /// CompoundSelectTypes unions a column of one table with a column of another,
+    /// possibly of a different type, and checks SQLite's rules for the values of a
+    /// compound select: each value keeps the storage class it has in its table, while
+    /// deduplication and ordering compare values across arms, so an INTEGER and an
+    /// equal REAL are one row but an INTEGER and the TEXT of its digits are two.
+    ///
+    /// Execution:
+    ///     SELECT <col1> FROM <t1> UNION SELECT <col2> FROM <t2> ORDER BY 1
+    ///
+    /// Assertion:
+    /// - One row for each distinct value of the model columns, compared by storage
+    ///   class then value, in that order.
+    /// - Every returned value appears with the same storage class in one of the arms.
+    CompoundSelectTypes {
+        t1: String,
+        col1: String,
+        t2: String,
+        col2: String,
+    },
+    /// ZeroblobBehavior stores a `zeroblob(n)` in a fresh table, which may keep only
+    /// the length instead of `n` zero bytes, and checks that it reads back as zeros
+    /// and can be partially overwritten. Sizes range from empty to several overflow
+    /// pages. There is no incremental blob I/O, so the overwrite is an UPDATE splicing
+    /// the patch between two `substr` slices of the blob.
+    ///
+    /// Execution:
+    ///     CREATE TABLE <t> (<c> BLOB)
+    ///     INSERT INTO <t> VALUES (zeroblob(<size>))
+    ///     SELECT length(<c>), typeof(<c>), <c> = zeroblob(<size>) FROM <t>
+    ///     UPDATE <t> SET <c> = CAST(substr(<c>, 1, <offset>) || <patch> || substr(<c>, ...) AS BLOB)
+    ///     SELECT length(<c>), substr(<c>, ...) = zeroblob(...), ... FROM <t>
+    ///     DROP TABLE <t>
+    ///
+    /// Assertion:
+    /// - The stored blob has length `size` and equals `size` zero bytes.
+    /// - After the overwrite the length is unchanged, the patched range holds the
+    ///   patch and the bytes before and after it are still zero.
+    ZeroblobBehavior {
+        table: String,
+        column: String,
+        size: usize,
+        /// Byte offset of `patch` in the blob
+        patch_offset: usize,
+        patch: Vec<u8>,
+    },
+    /// TriggerFires creates a BEFORE or AFTER INSERT trigger that writes an audit row
+    /// from `NEW` and the size of the table into a second table, and inserts rows into
+    /// the first. The trigger fires once per row: before the row is written, BEFORE
+    /// sees the rows inserted so far, and AFTER sees them including the new row.
+    ///
+    /// Execution (on scratch tables):
+    ///     CREATE TABLE <t> (a INTEGER, b INTEGER)
+    ///     CREATE TABLE <t>_audit (a INTEGER, b INTEGER, seen INTEGER)
+    ///     CREATE TRIGGER ... BEFORE|AFTER INSERT ON <t>
+    ///         BEGIN INSERT INTO <t>_audit VALUES (NEW.a, NEW.b + <offset>, (SELECT count(*) FROM <t>)); END
+    ///     INSERT INTO <t> VALUES (<a>, <b>), ...
+    ///     SELECT * FROM <t>_audit ORDER BY rowid
+    ///
+    /// Assertion:
+    /// - There is one audit row per inserted row, in insertion order, with the values
+    ///   of the row, `b` shifted by the offset, and the count seen by the trigger.
+    TriggerFires {
+        /// Whether the trigger is BEFORE INSERT rather than AFTER INSERT
+        before: bool,
+        rows: Vec<(i64, i64)>,
+        offset: i64,
+    },
+    /// TypeAffinity inserts each value into a scratch table with one column per
+    /// affinity, INTEGER, REAL, TEXT, BLOB and NUMERIC, and checks the stored values
+    /// against SQLite's conversion rules: numeric-looking text becomes a number
+    /// outside TEXT and BLOB columns, integral reals become integers in INTEGER and
+    /// NUMERIC columns, integers become reals in REAL columns, numbers become text in
+    /// TEXT columns, and NULL and blobs are never converted.
+    ///
+    /// Execution (on a scratch table):
+    ///     CREATE TABLE <t> (c_integer INTEGER, c_real REAL, c_text TEXT, c_blob BLOB, c_numeric NUMERIC)
+    ///     INSERT INTO <t> VALUES (<v>, <v>, <v>, <v>, <v>)   -- for each value
+    ///     SELECT c_integer, typeof(c_integer), ... FROM <t> ORDER BY rowid
+    ///
+    /// Assertion:
+    /// - Each column holds the converted value, with the converted type.
+    TypeAffinity {
+        create: Create,
+        values: Vec<SimValue>,
+    },
+    /// BlobIncrementalIo stores a BLOB of random bytes, possibly empty, reads it back
+    /// in chunks including one starting at its end, and overwrites a region in the
+    /// middle. Turso has no incremental blob handles (`sqlite3_blob_open` is a stub),
+    /// so reads are `substr` slices and the overwrite splices the patch between two
+    /// slices, as in [Property::ZeroblobBehavior]. Unlike `sqlite3_blob_read`, a
+    /// slice past the end is not an error but an empty blob.
+    ///
+    /// Execution (on a scratch table):
+    ///     CREATE TABLE <t> (id INTEGER PRIMARY KEY, data BLOB)
+    ///     INSERT INTO <t> VALUES (1, X'...')
+    ///     SELECT substr(data, <offset> + 1, <chunk>) FROM <t>   -- for each chunk
+    ///     UPDATE <t> SET data = CAST(substr(data, 1, <offset>) || X'<patch>' || substr(data, ...) AS BLOB)
+    ///     SELECT data FROM <t>
+    ///
+    /// Assertion:
+    /// - Each chunk holds the bytes of `data` at its offset, and the chunk at the end
+    ///   is empty.
+    /// - After the update the blob is `data` with `patch` written at `patch_offset`.
+    BlobIncrementalIo {
+        data: Vec<u8>,
+        chunk: usize,
+        /// Byte offset of `patch` in the blob, `patch` is empty for an empty blob
+        patch_offset: usize,
+        patch: Vec<u8>,
+    },
+    /// TopNPerGroup selects the top `n` rows of each group with a correlated count,
+    /// keeping a row when fewer than `n` rows of its group have a larger value. Ties at
+    /// the boundary are all kept, as tied rows do not count each other, and a row whose
+    /// group or value is NULL has no larger rows, so it is always kept for `n > 0`.
+    ///
+    /// Execution:
+    ///     SELECT * FROM <t> AS o WHERE (
+    ///         SELECT count(*) FROM <t> AS i WHERE i.<g> = o.<g> AND i.<v> > o.<v>
+    ///     ) < <n>
+    ///
+    /// Assertion:
+    /// - The rows returned are exactly the model rows passing the same count.
+    TopNPerGroup {
+        table: String,
+        group_column: String,
+        value_column: String,
+        n: u32,
+    },
+    /// GroupByAggregate groups a table by a column and recomputes the count and sum of
+    /// each group from the model rows. Groups compare values the way `=` does except
+    /// that all NULLs form one group, `sum` skips NULLs and is NULL for a group without
+    /// other values, and is REAL once any input is. An integer sum that overflows is an
+    /// error, which depends on the order rows are added in, so a query that may
+    /// overflow is allowed to fail with "integer overflow".
+    ///
+    /// Execution:
+    ///     SELECT <g>, count(*), sum(<c>) FROM <t> GROUP BY <g>
+    ///
+    /// Assertion:
+    /// - There is one row per model group, with the group's row count and sum.
+    GroupByAggregate {
+        table: String,
+        group_column: String,
+        sum_column: String,
+    },
+    /// GroupByHaving groups a table by a column and keeps the groups with more than
+    /// `min_count` rows. HAVING filters the groups after counting, so a group with
+    /// exactly `min_count` rows is dropped while its rows still count. `min_count`
+    /// is taken from the group sizes when the property is generated.
+    ///
+    /// Execution:
+    ///     SELECT <g>, count(*) FROM <t> GROUP BY <g> HAVING count(*) > <min_count>
+    ///
+    /// Assertion:
+    /// - The rows are the model groups with more than `min_count` rows, with their
+    ///   row counts.
+    GroupByHaving {
+        table: String,
+        group_column: String,
+        min_count: i64,
+    },
+    /// CountMatchesContent counts the rows of a table, optionally filtered by a
+    /// predicate, and compares the count with the model rows the predicate holds for.
+    /// An empty table, or a predicate that holds for no row, must count 0.
+    ///
+    /// Execution:
+    ///     SELECT count(*) FROM <t> [WHERE <predicate>]
+    ///
+    /// Assertion:
+    /// - The count equals the number of model rows satisfying the predicate.
+    CountMatchesContent {
+        table: String,
+        predicate: Option<Predicate>,
+    },
+    /// RangeScanBoundaries inserts rows whose values of a column repeat a few known
+    /// values, indexes the column, and runs range scans with bounds taken from the
+    /// stored values, so that every boundary is hit exactly. The bounds include the
+    /// column's minimum and maximum, equal bounds and reversed bounds, whose ranges
+    /// are empty.
+    ///
+    /// Execution:
+    ///     INSERT INTO <t> VALUES (...)
+    ///     CREATE INDEX <index> ON <t> (<c>)
+    ///     SELECT * FROM <t> WHERE <c> >= <lo> AND <c> < <hi>     -- for each (lo, hi)
+    ///     SELECT * FROM <t> WHERE <c> > <lo> AND <c> <= <hi>
+    ///     SELECT * FROM <t> WHERE <c> > <lo> AND <c> < <hi>
+    ///     SELECT * FROM <t> WHERE <c> BETWEEN <lo> AND <hi>
+    ///     DROP INDEX <index>
+    ///
+    /// Assertion:
+    /// - Each scan returns exactly the model rows inside its range, with the bounds
+    ///   included or excluded as written.
+    RangeScanBoundaries {
+        table: String,
+        indexed_column: String,
+        insert: Insert,
+        index: CreateIndex,
+        /// (lo, hi) pairs, all of them values stored in the column
+        bounds: Vec<(SimValue, SimValue)>,
+    },
+    /// WindowFunction numbers the rows of a table with `row_number()`, or computes a
+    /// running `sum()`, over `ORDER BY <c>`, and checks the result against the model.
+    /// Rows with equal values of the column may be numbered in any order, and the
+    /// default window frame ends at the last peer of the current row, so peers share
+    /// one running sum.
+    ///
+    /// Execution:
+    ///     SELECT <c>, row_number() OVER (ORDER BY <c>) FROM <t>
+    ///       | SELECT <c>, sum(<c>) OVER (ORDER BY <c>) FROM <t>
+    ///
+    /// Assertion:
+    /// - The values of the column are the model's values.
+    /// - Row numbers are `1..=n` and follow the ORDER BY order.
+    /// - Each running sum is the sum of the model values sorting at or before the
+    ///   row's value.
+    WindowFunction {
+        table: String,
+        column: String,
+        running_sum: bool,
+    },
+    /// ArithmeticOverflow adds or multiplies two integer literals near the edge of the
+    /// 64-bit range. SQLite computes `+` and `*` on integers exactly while the result
+    /// fits, and otherwise returns the REAL result of the same operation on the
+    /// operands converted to REAL, never wrapping or failing.
+    ///
+    /// Execution:
+    ///     SELECT <lhs> + <rhs> | SELECT <lhs> * <rhs>
+    ///
+    /// Assertion:
+    /// - The result is the INTEGER result if it fits, else the REAL result.
+    ArithmeticOverflow {
+        lhs: i64,
+        rhs: i64,
+        multiply: bool,
+    },
+    /// RecursiveCte counts from 1 to `limit` with a recursive CTE. Turso does not plan
+    /// recursive CTEs yet, so the property is skipped while the statement fails with
+    /// "Recursive CTEs are not yet supported".
+    ///
+    /// Execution:
+    ///     WITH RECURSIVE c(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM c WHERE n < <limit>) SELECT n FROM c
+    ///
+    /// Assertion:
+    /// - There are exactly `limit` rows.
+    /// - The rows are `1, 2, ..., limit`, in that order.
+    RecursiveCte {
+        limit: u32,
+    },
+    /// VacuumPreservesContent reads every table of the main database, rebuilds the
+    /// database with VACUUM, and reads them again. VACUUM rewrites every b-tree, so a
+    /// lost, duplicated or altered row points at a bug in the copy. It may renumber
+    /// the rowids of tables without an INTEGER PRIMARY KEY, but not of the others.
+    ///
+    /// Execution:
+    ///     SELECT rowid, * FROM <t>   -- for each table
+    ///     VACUUM
+    ///     SELECT rowid, * FROM <t>   -- for each table
+    ///     SELECT * FROM <t>          -- for each table, as in AllTableHaveExpectedContent
+    ///
+    /// Assertion:
+    /// - Each table holds the same rows after VACUUM, with the same rowids if it has
+    ///   an INTEGER PRIMARY KEY.
+    /// - Each table has the model's content.
+    VacuumPreservesContent {
+        tables: Vec<String>,
+    },
+    /// ConcurrentReadSnapshot opens a read transaction on one connection, lets a second
+    /// connection commit writes to the same table, and reads the table again. A reader
+    /// must keep its snapshot until it ends its transaction, whether the isolation comes
+    /// from the WAL read marks or from MVCC, so the writes only show up afterwards.
+    ///
+    /// Execution:
+    ///     BEGIN                      -- reader
+    ///     SELECT * FROM <t>          -- reader
+    ///     INSERT/UPDATE/DELETE ...   -- writer, autocommit
+    ///     SELECT * FROM <t>          -- reader
+    ///     COMMIT                     -- reader
+    ///     SELECT * FROM <t>          -- reader
+    ///
+    /// Assertion:
+    /// - Both reads inside the transaction return the same rows.
+    /// - The read after COMMIT returns the model's content, writes included.
+    ConcurrentReadSnapshot {
+        begin: Begin,
+        select: Select,
+        writer: SecondConnectionInfo,
+    },
+    /// AddColumnDefault adds a column with a literal DEFAULT to a populated table.
+    /// SQLite does not rewrite the existing rows for this; it stores the DEFAULT in the
+    /// schema and reads it back for every row that is too short to hold the column.
+    /// A NOT NULL column may only be added this way when it has a non-NULL DEFAULT, so
+    /// adding one without must be rejected first.
+    ///
+    /// Execution:
+    ///     ALTER TABLE <t> ADD COLUMN <rejected> <type> NOT NULL -> Error
+    ///     ALTER TABLE <t> ADD COLUMN <c> <type> [NOT NULL] DEFAULT <v>
+    ///     SELECT <c> FROM <t>
+    ///     SELECT * FROM <t>
+    ///
+    /// Assertion:
+    /// - Adding the NOT NULL column without a DEFAULT fails.
+    /// - Every row reports `<c> = <v>`.
+    /// - The table has the model's content, which fills `<c>` with `<v>` too.
+    AddColumnDefault {
+        table: String,
+        /// Column with the DEFAULT, added successfully
+        column: Column,
+        /// NOT NULL column without a DEFAULT, which must be rejected
+        rejected: Column,
+    },
+    /// WalCheckpointIntegrity commits a batch of inserts to the WAL, checkpoints it into
+    /// the database file and reopens the database. Rows lost or duplicated between the
+    /// WAL and the database file point at a bug in the checkpoint, and a TRUNCATE
+    /// checkpoint must also leave an empty WAL behind for the reopen to start from.
+    ///
+    /// Execution:
+    ///     INSERT INTO <t> VALUES (...)   -- for each insert
+    ///     PRAGMA wal_checkpoint(<mode>)
+    ///     REOPEN_DATABASE
+    ///     SELECT * FROM <t>
+    ///
+    /// Assertion:
+    /// - The checkpoint reports no more frames checkpointed than there are in the WAL.
+    /// - A TRUNCATE checkpoint that was not blocked reports an empty WAL, and the WAL
+    ///   file is zero bytes long.
+    /// - The table has the model's content, inserts included, after the reopen.
+    WalCheckpointIntegrity {
+        table: String,
+        inserts: Vec<Insert>,
+        mode: CheckpointMode,
+    },
+    /// MmapSizeNeutral runs the same read/write workload on a scratch table under each
+    /// of several `PRAGMA mmap_size` values, starting with `0` (no memory-mapped I/O),
+    /// and compares what it reads back. Reads through a mapping and through regular
+    /// I/O are different paths in the pager, so a difference points at a coherence
+    /// bug between them.
+    ///
+    /// Execution (once per mmap size):
+    ///     PRAGMA mmap_size = <size>
+    ///     CREATE TABLE <t> (id INTEGER PRIMARY KEY, val TEXT, data BLOB)
+    ///     INSERT INTO <t> VALUES (<id>, <val>, X'...')   -- for each workload row
+    ///     UPDATE <t> SET val = val || '!' WHERE id % 2 = 0
+    ///     DELETE FROM <t> WHERE id % 3 = 0
+    ///     SELECT id, val, data FROM <t> ORDER BY id
+    ///     SELECT count(*), sum(length(data)) FROM <t>
+    ///     DROP TABLE <t>
+    ///
+    /// Assertion:
+    /// - Every mmap size reads back exactly what `mmap_size = 0` did, byte for byte.
+    MmapSizeNeutral {
+        /// (id, val, blob length) of the inserted rows, some blobs spanning overflow pages
+        workload: Vec<(i64, String, usize)>,
+        /// Always starts with 0, the baseline the other sizes are compared against
+        mmap_sizes: Vec<i64>,
+    },
+    /// JoinStrategyEquivalence runs the same join through the execution strategies the
+    /// planner picks between and checks that they agree. An equi-join on unindexed
+    /// columns is eligible for a hash join, the same condition hidden in a CASE can
+    /// only run as a nested loop scan, and once the right table's join columns are
+    /// indexed the equi-join becomes an index lookup. The rows inserted into the right
+    /// table repeat join keys of the left table, each twice, and some have NULL keys.
+    ///
+    /// Execution:
+    ///     INSERT INTO <r> VALUES (...)
+    ///     SELECT l.*, r.* FROM <l> AS l [LEFT] JOIN <r> AS r ON l.<a> = r.<b> AND ...
+    ///     SELECT l.*, r.* FROM <l> AS l [LEFT] JOIN <r> AS r ON CASE WHEN ... THEN 1 ELSE 0 END
+    ///     CREATE INDEX <index> ON <r> (<b>, ...)
+    ///     -- the same queries again
+    ///     DROP INDEX <index>
+    ///
+    /// Assertion:
+    /// - For inner and left joins, with and without the index, the equi-join returns
+    ///   the same multiset of rows as the nested loop scan.
+    JoinStrategyEquivalence {
+        left_table: String,
+        right_table: String,
+        /// (left column, right column) pairs the join compares with `=`
+        join_columns: Vec<(String, String)>,
+        /// Rows added to the right table, with duplicate and NULL join keys
+        insert: Insert,
+        /// Index on the right table's join columns
+        index: CreateIndex,
+    },
     /// Property used to subsititute a property with its queries only
     Queries {
         queries: Vec<Query>,
     },
 }
 
+/// Journal modes [Property::JournalModeSwitch] switches into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::IntoStaticStr)]
+#[strum(serialize_all = "lowercase")]
+pub enum JournalMode {
+    Delete,
+    Truncate,
+    Persist,
+    Memory,
+    Wal,
+    Off,
+}
+
+impl JournalMode {
+    pub const ALL: [JournalMode; 6] = [
+        JournalMode::Delete,
+        JournalMode::Truncate,
+        JournalMode::Persist,
+        JournalMode::Memory,
+        JournalMode::Wal,
+        JournalMode::Off,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        self.into()
+    }
+}
+
+/// Checkpoint modes [Property::WalCheckpointIntegrity] runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::IntoStaticStr)]
+#[strum(serialize_all = "UPPERCASE")]
+pub enum CheckpointMode {
+    Passive,
+    Truncate,
+}
+
+impl CheckpointMode {
+    pub const ALL: [CheckpointMode; 2] = [CheckpointMode::Passive, CheckpointMode::Truncate];
+
+    pub fn as_str(&self) -> &'static str {
+        self.into()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InteractiveQueryInfo {
     pub start_with_immediate: bool,
     pub end_with_commit: bool,
 }
 
+/// Queries a property runs on a connection other than its own
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecondConnectionInfo {
+    pub queries: Vec<Query>,
+}
+
 impl Property {
     /// Property Does some sort of fault injection
     pub fn check_tables(&self) -> bool {
@@ -233,7 +1434,9 @@ impl Property {
                 | Property::DoubleCreateFailure { .. }
                 | Property::DeleteSelect { .. }
                 | Property::DropSelect { .. }
+                | Property::ViewContent { .. }
                 | Property::SavepointRollback { .. }
+                | Property::TransactionRollback { .. }
                 | Property::Queries { .. }
         )
     }
@@ -244,14 +1447,64 @@ impl Property {
             | Property::DoubleCreateFailure { queries, .. }
             | Property::DeleteSelect { queries, .. }
             | Property::DropSelect { queries, .. }
+            | Property::ViewContent { queries, .. }
             | Property::SavepointRollback { queries, .. }
+            | Property::TransactionRollback { queries, .. }
             | Property::Queries { queries } => Some(queries),
             Property::FsyncNoWait { .. } | Property::FaultyQuery { .. } => None,
-            Property::SequenceMonotonicity { .. } => None,
+            Property::SequenceMonotonicity { .. } | Property::SkipScanCorrectness { .. } => None,
+            Property::JournalModeSwitch { .. } | Property::ReplaceIntoSemantics { .. } => None,
+            Property::DropIndexFallsBackToScan { .. }
+            | Property::PartialIndex { .. }
+            | Property::GlobVsLikeCaseSensitivity { .. }
+            | Property::ExpressionIndexQueryMatch { .. }
+            | Property::NumericTextComparison { .. }
+            | Property::AggregateViewReadOnly { .. }
+            | Property::CrossDatabaseTransactionAtomicity { .. }
+            | Property::BindingLifecycle { .. }
+            | Property::CollatedPrimaryKeyUniqueness { .. }
+            | Property::DifferentialAgainstReference { .. }
+            | Property::ReturningClauseResult { .. }
+            | Property::RowValueInSubquery { .. }
+            | Property::SavepointWithConstraintFailure { .. }
+            | Property::RecursiveTriggerTerminates { .. }
+            | Property::ForeignKeyCheckReportsOrphans { .. }
+            | Property::NotNullConstraint { .. }
+            | Property::UniqueViolation { .. }
+            | Property::CorrelatedSubquery { .. }
+            | Property::UpsertDoUpdate { .. }
+            | Property::CheckConstraint { .. }
+            | Property::DefaultValue { .. }
+            | Property::AutoincrementMonotonic { .. }
+            | Property::NoOpUpdateBehavior { .. }
+            | Property::CompoundSelectTypes { .. }
+            | Property::ZeroblobBehavior { .. }
+            | Property::BlobIncrementalIo { .. }
+            | Property::TypeAffinity { .. }
+            | Property::TriggerFires { .. }
+            | Property::TopNPerGroup { .. }
+            | Property::GroupByAggregate { .. }
+            | Property::GroupByHaving { .. }
+            | Property::CountMatchesContent { .. }
+            | Property::RecursiveCte { .. }
+            | Property::ArithmeticOverflow { .. }
+            | Property::WindowFunction { .. }
+            | Property::RangeScanBoundaries { .. }
+            | Property::MmapSizeNeutral { .. }
+            | Property::VacuumPreservesContent { .. }
+            | Property::ConcurrentReadSnapshot { .. }
+            | Property::WalCheckpointIntegrity { .. }
+            | Property::RenameTableFollowThrough { .. }
+            | Property::AddColumnDefault { .. }
+            | Property::JoinStrategyEquivalence { .. } => None,
             Property::SelectLimit { .. }
+            | Property::SelectOrderBy { .. }
+            | Property::SelectDistinct { .. }
             | Property::SelectSelectOptimizer { .. }
             | Property::WhereTrueFalseNull { .. }
             | Property::UnionAllPreservesCardinality { .. }
+            | Property::IntersectExcept { .. }
+            | Property::ColumnToColumnComparison { .. }
             | Property::ReadYourUpdatesBack { .. }
             | Property::TableHasExpectedContent { .. }
             | Property::AllTableHaveExpectedContent { .. } => None,
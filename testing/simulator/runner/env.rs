@@ -10,8 +10,8 @@ use bitmaps::Bitmap;
 use garde::Validate;
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
-use sql_generation::generation::GenerationContext;
 use sql_generation::generation::generated_expr::rename_column_refs_in_expr;
+use sql_generation::generation::{GenerationContext, NameCounter};
 use sql_generation::model::query::transaction::Rollback;
 use sql_generation::model::table::{SimValue, Table};
 use tracing::trace;
@@ -19,7 +19,8 @@ use turso_core::Database;
 use turso_parser::ast::ColumnConstraint;
 
 use crate::generation::Shadow;
-use crate::model::Query;
+use crate::generation::scenario::ReferenceDb;
+use crate::model::{Query, added_column_value};
 use crate::profiles::Profile;
 use crate::runner::SimIO;
 use crate::runner::cli::IoBackend;
@@ -807,12 +808,17 @@ where
                         }
                         committed.columns.push(column.clone());
                         let new_col_count = committed.columns.len();
-                        // Add NULL only for rows that need it.
+                        let values = committed
+                            .columns
+                            .iter()
+                            .map(|column| added_column_value(committed, column))
+                            .collect::<Vec<_>>();
+                        // Add the columns' values only for rows that need them.
                         // Rows inserted after ADD COLUMN in the same transaction
                         // already have the correct number of values.
                         for row in &mut committed.rows {
                             while row.len() < new_col_count {
-                                row.push(SimValue::NULL);
+                                row.push(values[row.len()].clone());
                             }
                         }
                     }
@@ -1071,6 +1077,10 @@ pub(crate) struct SimulatorEnv {
     pub(crate) attached_dbs: Vec<String>,
     /// Sequences are global objects, not affected by transactions/savepoints
     pub sequences: Vec<ShadowSequence>,
+    /// Suffixes for generated names, shared by every connection's generation context
+    pub(crate) name_counter: NameCounter,
+    /// SQLite copy of the model that differential checks compare results against
+    pub(crate) reference_db: Option<ReferenceDb>,
 }
 
 impl UnwindSafe for SimulatorEnv {}
@@ -1097,6 +1107,8 @@ impl SimulatorEnv {
             committed_tables: self.committed_tables.clone(),
             attached_dbs: self.attached_dbs.clone(),
             sequences: self.sequences.clone(),
+            name_counter: self.name_counter.clone(),
+            reference_db: None,
         }
     }
 
@@ -1104,6 +1116,8 @@ impl SimulatorEnv {
         self.clear_tables();
         self.connections.iter_mut().for_each(|c| c.disconnect());
         self.rng = ChaCha8Rng::seed_from_u64(self.opts.seed);
+        self.name_counter = NameCounter::default();
+        self.reference_db = None;
 
         let latency_prof = &self.profile.io.latency;
 
@@ -1169,6 +1183,7 @@ impl SimulatorEnv {
             turso_core::OpenFlags::default(),
             turso_core::DatabaseOpts::new()
                 .with_autovacuum(true)
+                .with_vacuum(true)
                 .with_attach(true)
                 .with_generated_columns(true),
             None,
@@ -1390,6 +1405,7 @@ impl SimulatorEnv {
             turso_core::OpenFlags::default(),
             turso_core::DatabaseOpts::new()
                 .with_autovacuum(true)
+                .with_vacuum(true)
                 .with_attach(true)
                 .with_generated_columns(true),
             None,
@@ -1437,6 +1453,8 @@ impl SimulatorEnv {
             connection_last_query: Bitmap::new(),
             attached_dbs,
             sequences: Vec::new(),
+            name_counter: NameCounter::default(),
+            reference_db: None,
         }
     }
 
@@ -1505,6 +1523,8 @@ impl SimulatorEnv {
         struct ConnectionGenContext<'a> {
             tables: &'a Vec<sql_generation::model::table::Table>,
             opts: &'a sql_generation::generation::Opts,
+            name_counter: &'a NameCounter,
+            attached_dbs: &'a [String],
         }
 
         impl<'a> GenerationContext for ConnectionGenContext<'a> {
@@ -1515,6 +1535,14 @@ impl SimulatorEnv {
             fn opts(&self) -> &sql_generation::generation::Opts {
                 self.opts
             }
+
+            fn name_counter(&self) -> &NameCounter {
+                self.name_counter
+            }
+
+            fn attached_dbs(&self) -> &[String] {
+                self.attached_dbs
+            }
         }
 
         let tables = self.get_conn_tables(conn_index).tables();
@@ -1522,6 +1550,8 @@ impl SimulatorEnv {
         ConnectionGenContext {
             opts: &self.profile.query.gen_opts,
             tables,
+            name_counter: &self.name_counter,
+            attached_dbs: &self.attached_dbs,
         }
     }
 
@@ -24,7 +24,7 @@ use turso_core::{Connection, LimboError, Result, Value};
 ///   The model is consistent and the engine is consistent; only B's
 ///   per-connection schema cache is one beat behind. Treat it like
 ///   any other transient retry trigger.
-fn is_recoverable_tx_error(err: &LimboError) -> bool {
+pub(crate) fn is_recoverable_tx_error(err: &LimboError) -> bool {
     matches!(
         err,
         LimboError::WriteWriteConflict
@@ -3,7 +3,7 @@ use core_tester::common::{limbo_exec_rows, try_limbo_exec_rows, TempDatabase};
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 use sql_generation::{
-    generation::{Arbitrary, GenerationContext, Opts},
+    generation::{Arbitrary, GenerationContext, NameCounter, Opts},
     model::{
         query::{Create, Insert, Select},
         table::{Column, ColumnType, Table},
@@ -29,6 +29,7 @@ fn rng_from_time_or_env() -> (ChaCha8Rng, u64) {
 struct FuzzTestContext {
     opts: Opts,
     tables: Vec<Table>,
+    name_counter: NameCounter,
 }
 
 impl FuzzTestContext {
@@ -36,6 +37,7 @@ impl FuzzTestContext {
         Self {
             opts: Opts::default(),
             tables: Vec::new(),
+            name_counter: NameCounter::default(),
         }
     }
 
@@ -52,6 +54,10 @@ impl GenerationContext for FuzzTestContext {
     fn opts(&self) -> &Opts {
         &self.opts
     }
+
+    fn name_counter(&self) -> &NameCounter {
+        &self.name_counter
+    }
 }
 
 // Convert a table's CREATE statement to use INTEGER PRIMARY KEY (rowid alias)